@@ -0,0 +1,4 @@
+pub mod resource_bundler;
+pub mod template_validator;
+
+pub use resource_bundler::embed_resources;
@@ -0,0 +1,76 @@
+use std::{env, fs, path::{Path, PathBuf}};
+
+use crate::template_validator::validate_template;
+
+
+pub static BUNDLE_FILE_NAME: &str = "angust_resources.rs";
+
+/*
+ * Build-script helper embedding the resources of an Angust application into its binary.
+ * Call it from build.rs with the files and directories to embed, relative to the crate root:
+ *
+ *     angust_build::embed_resources(&["angust.config.json", "src/index.html", "src/app", "src/styles"]);
+ *
+ * and register the bundle at startup with angust::include_resources!().
+ * The templates (.html files) are validated, failing the build on syntax errors.
+ */
+pub fn embed_resources(resource_paths: &[&str]) {
+    let crate_root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is not set"));
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is not set"));
+
+    let mut resource_files = Vec::new();
+    for resource_path in resource_paths {
+        println!("cargo:rerun-if-changed={}", resource_path);
+        collect_files(&crate_root.join(resource_path), &mut resource_files);
+    }
+    resource_files.sort();
+
+    let mut template_errors = Vec::new();
+    let mut bundle = String::from("&[\n");
+
+    for file_path in resource_files.iter() {
+        let relative_path = normalize_path(file_path.strip_prefix(&crate_root).unwrap_or(file_path));
+
+        if file_path.extension().is_some_and(|extension| extension == "html") {
+            let template_content = fs::read_to_string(file_path)
+                .unwrap_or_else(|er| panic!("Failed to read template {}: {}", relative_path, er));
+            template_errors.extend(
+                validate_template(&template_content).into_iter().map(|er| format!("{}: {}", relative_path, er))
+            );
+        }
+
+        bundle.push_str(&format!("    ({:?}, include_bytes!({:?}) as &[u8]),\n", relative_path, file_path.display().to_string()));
+    }
+    bundle.push(']');
+
+    if !template_errors.is_empty() {
+        panic!("Invalid templates:\n{}", template_errors.join("\n"));
+    }
+
+    fs::write(out_dir.join(BUNDLE_FILE_NAME), bundle)
+        .expect("Failed to write the resource bundle");
+}
+
+// The Rust sources next to the templates are skipped
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        if path.extension().is_none_or(|extension| extension != "rs") {
+            files.push(path.to_path_buf());
+        }
+        return;
+    }
+
+    let entries = fs::read_dir(path)
+        .unwrap_or_else(|er| panic!("Failed to read resource directory {}: {}", path.display(), er));
+    for entry in entries.flatten() {
+        collect_files(&entry.path(), files);
+    }
+}
+
+// Resources are looked up by their path relative to the crate root, with forward slashes
+fn normalize_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<String>>()
+        .join("/")
+}
@@ -0,0 +1,81 @@
+use angust_syntax::{expression_parser::parse_expression, template_patterns::{FOR_ALIAS_REGEX, FOR_IMPLICIT_VARIABLES, FOR_LOOP_REGEX, FOR_TRACK_REGEX, PLACEHOLDER_REGEX}};
+use kuchiki::{traits::TendrilSink, NodeData};
+
+
+/*
+ * Checks the syntax of the directives, bindings and placeholders of a template,
+ * returning a description of each invalid one.
+ */
+pub fn validate_template(template_content: &str) -> Vec<String> {
+    let dom = kuchiki::parse_html().one(template_content);
+    let mut errors = Vec::new();
+
+    for node in dom.descendants() {
+        match node.data() {
+            NodeData::Element(ref elem_data) => {
+                let tag_name = elem_data.name.local.to_string();
+                for (attribute_name, attribute) in elem_data.attributes.borrow().map.iter() {
+                    let attribute_name = attribute_name.local.to_string();
+
+                    if let Err(er) = validate_attribute(&attribute_name, &attribute.value) {
+                        errors.push(format!("<{}> {}=\"{}\": {}", tag_name, attribute_name, attribute.value, er));
+                    }
+                }
+            },
+            NodeData::Text(ref text) => {
+                errors.extend(validate_placeholders(&text.borrow()));
+            },
+            _ => {},
+        }
+    }
+
+    errors
+}
+
+fn validate_attribute(attribute_name: &str, value: &str) -> Result<(), String> {
+    match attribute_name {
+        "@for" => validate_for_directive(value),
+        "@else" => Ok(()),
+        _ if attribute_name.starts_with(['@', '[', '(']) => validate_expression(value.trim()),
+        _ => Ok(()),
+    }
+}
+
+fn validate_for_directive(value: &str) -> Result<(), String> {
+    let mut clauses = value.split(';').map(str::trim);
+    if !FOR_LOOP_REGEX.is_match(clauses.next().unwrap_or_default()) {
+        return Err("Expected 'item of items'".to_string());
+    }
+    for clause in clauses.filter(|clause| !clause.is_empty()) {
+        match FOR_TRACK_REGEX.captures(clause) {
+            Some(track_captures) => validate_expression(track_captures[1].trim())
+                .map_err(|er| format!("Invalid track expression '{}': {}", &track_captures[1], er))?,
+            None => match FOR_ALIAS_REGEX.captures(clause) {
+                Some(alias_captures) if FOR_IMPLICIT_VARIABLES.contains(&&alias_captures[2]) => {},
+                Some(alias_captures) => return Err(format!("Unknown variable '{}' in clause '{}'", &alias_captures[2], clause)),
+                None => return Err(format!("Invalid clause '{}', expected 'let alias = index' or 'track expression'", clause)),
+            },
+        }
+    }
+    Ok(())
+}
+
+fn validate_placeholders(text: &str) -> Vec<String> {
+    PLACEHOLDER_REGEX.captures_iter(text)
+        .filter_map(|captures| {
+            let expression = captures[1].trim();
+            validate_expression(expression)
+                .err()
+                .map(|er| format!("{{{{ {} }}}}: {}", expression, er))
+        })
+        .collect()
+}
+
+/*
+ * Checks that the whole input is a valid template expression.
+ */
+fn validate_expression(input: &str) -> Result<(), String> {
+    parse_expression(input)
+        .map(|_| ())
+        .map_err(|er| er.to_string())
+}
@@ -0,0 +1,21 @@
+use angust_build::template_validator::validate_template;
+
+
+#[test]
+fn valid_templates_have_no_errors() {
+    let template = r#"<div @for="item of items; let i = index; track item.id" [class.active]="i == 0" @onclick="select(item)">{{ item.name | uppercase }}</div>"#;
+    assert!(validate_template(template).is_empty());
+}
+
+#[test]
+fn trailing_input_of_expressions_is_reported() {
+    let errors = validate_template(r#"<div @if="count > 1 )">{{ count count }}</div>"#);
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn invalid_for_clauses_are_reported() {
+    let errors = validate_template(r#"<div @for="item of items; let i = position"></div>"#);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("let i = position"));
+}
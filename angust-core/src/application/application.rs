@@ -2,11 +2,11 @@ use skia_safe::{gpu::gl::FramebufferInfo, Point};
 use winit::{application::ApplicationHandler, dpi::PhysicalSize, event::{ElementState, KeyEvent, Modifiers, MouseButton, MouseScrollDelta, WindowEvent}, event_loop::{ActiveEventLoop, ControlFlow, EventLoop}};
 use gl_rs as gl;
 use glutin::{config::GlConfig, display::GetGlDisplay, prelude::GlDisplay, surface::GlSurface};
-use std::{ffi::CString, num::NonZeroU32};
+use std::{ffi::CString, num::NonZeroU32, rc::Rc, time::{Duration, Instant}};
 
-use crate::{parsing::{css::stylesheet_parser::{self, Stylesheet}, html::html_parser::{self, ParsingContext}}, rendering::{elements::{component::{no_state::NoState, reactivity::ComponentEvent}, element::EventType}, renderer::Renderer}, window::WindowingSystem};
+use crate::{parsing::{css::{global_stylesheet, style_context::RestyleImpact, stylesheet_parser::Stylesheet}, html::{compiled_nodes::CompiledNodes, html_parser::{self, ParsingContext}, template_diagnostics::TemplateDiagnostics}}, rendering::{elements::{component::{no_state::NoState, reactivity::ComponentEvent}, element::EventType}, renderer::Renderer}, window::WindowingSystem};
 
-use super::{angust_configuration::AngustConfiguration, event_loop_proxy::set_event_loop_proxy, resource_loader::configuration_loader::load_angust_configuration, ui_initializer::load_resources};
+use super::{angust_configuration::AngustConfiguration, event_handling::application_input_event_handler::{create_event_payload, create_keyboard_event_payload}, event_loop_proxy::set_event_loop_proxy, resource_loader::configuration_loader::load_angust_configuration, ui_initializer::load_resources};
 
 
 pub struct Application<State> {
@@ -24,8 +24,12 @@ pub struct Application<State> {
 
     mouse_position: Option<Point>,
     is_mouse_pressed: bool,
+    last_click: Option<(Instant, Point)>,
 }
 
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
+
 impl<State> Application<State> {
     // Initialization
     pub fn new(initial_state: State, app_title: String) -> Self {
@@ -40,12 +44,17 @@ impl<State> Application<State> {
         
         // Load UI
         let angust_config = load_angust_configuration();
-        let (dom, stylesheets) = load_resources(&angust_config);
-        let stylesheet = stylesheet_parser::parse_stylesheet(&stylesheets);
-        let mut parsing_context: ParsingContext<NoState> = ParsingContext::new(Some(angust_config.clone()), Some(stylesheet.clone()), None, None, None);
+        let (dom, source_locations, stylesheet) = load_resources(&angust_config);
+        global_stylesheet::set_global_stylesheet(stylesheet.clone());
+        let mut parsing_context: ParsingContext<NoState> = ParsingContext::new(Some(angust_config.clone()), global_stylesheet::get_global_stylesheet(), None, None, None);
+        parsing_context.diagnostics = TemplateDiagnostics::new(
+            angust_config.pathing_config.index_html_path.clone(), Rc::new(source_locations)
+        );
+        parsing_context.compiled_nodes = Some(Rc::new(CompiledNodes::compile(&dom, &parsing_context.stylesheet)));
 
         let ui_body = html_parser::map_dom_to_elements::<NoState>(&dom, None, &mut parsing_context)
             .expect("Failed to map DOM to elements");
+        parsing_context.diagnostics.flush();
 
         // Initialize renderer and layout
         let mut renderer = Renderer::new(
@@ -68,6 +77,7 @@ impl<State> Application<State> {
             modifiers: Modifiers::default(),
             mouse_position: None,
             is_mouse_pressed: false,
+            last_click: None,
             renderer,
         }
     }
@@ -121,6 +131,39 @@ impl<State> Application<State> {
         self.windowing_system.window.request_redraw();
     }
 
+    // Returns whether the click completes a double click
+    fn register_click(&mut self, mouse_position: Point) -> bool {
+        let now = Instant::now();
+        let is_double_click = self.last_click.is_some_and(|(click_time, click_position)| {
+            now.duration_since(click_time) <= DOUBLE_CLICK_INTERVAL &&
+            (click_position.x - mouse_position.x).abs() <= DOUBLE_CLICK_DISTANCE &&
+            (click_position.y - mouse_position.y).abs() <= DOUBLE_CLICK_DISTANCE
+        });
+
+        self.last_click = if is_double_click { None } else { Some((now, mouse_position)) };
+        is_double_click
+    }
+
+    /*
+     * Restyles the elements affected by the interaction state changes of the last event (:hover, :active, :focus),
+     * computing the layout again if their sizes can change. Returns whether the window needs to be redrawn.
+     */
+    fn update_interactive_styles(&mut self) -> bool {
+        self.update_rendering(RestyleImpact::None)
+    }
+
+    // Restyles the elements, and lays them out if the patch of the element tree or the restyle affect the layout
+    fn update_rendering(&mut self, patch_impact: RestyleImpact) -> bool {
+        match patch_impact.max(self.renderer.restyle()) {
+            RestyleImpact::None => false,
+            RestyleImpact::Repaint => true,
+            RestyleImpact::Layout => {
+                self.renderer.layout();
+                true
+            },
+        }
+    }
+
     fn handle_redraw_requested(&mut self) {
         self.renderer.render_frame(&mut self.windowing_system.gr_context);
         self.windowing_system.gr_context.flush_and_submit();
@@ -166,27 +209,74 @@ impl<State> ApplicationHandler<ComponentEvent> for Application<State> {
                         self.is_mouse_pressed = true;
                         if let Some(mouse_position) = self.mouse_position {
                             self.renderer.handle_event(mouse_position, EventType::MouseDown);
-                            self.renderer.propagate_event(mouse_position, &EventType::MouseClick);
-                            
-                            self.windowing_system.window.request_redraw();
+
+                            let event_payload = create_event_payload(mouse_position, Some(button), &self.modifiers);
+                            self.renderer.propagate_event(mouse_position, &EventType::MouseDown, &event_payload);
+
+                            if self.update_interactive_styles() {
+                                self.windowing_system.window.request_redraw();
+                            }
                         }
                     },
                     (ElementState::Released, MouseButton::Left) => {
                         self.is_mouse_pressed = false;
                         if let Some(mouse_position) = self.mouse_position {
                             self.renderer.handle_event(mouse_position, EventType::MouseUp);
-                            self.windowing_system.window.request_redraw();
+
+                            let event_payload = create_event_payload(mouse_position, Some(button), &self.modifiers);
+                            self.renderer.propagate_event(mouse_position, &EventType::MouseUp, &event_payload);
+
+                            // The click reaches the elements released over that were pressed, see ElementEventHandlers
+                            self.renderer.propagate_event(mouse_position, &EventType::MouseClick, &event_payload);
+                            if self.register_click(mouse_position) {
+                                self.renderer.propagate_event(mouse_position, &EventType::DoubleClick, &event_payload);
+                            }
+
+                            if self.update_interactive_styles() {
+                                self.windowing_system.window.request_redraw();
+                            }
                         }
                     },
-                    _ => ()
+                    (element_state, _) => {
+                        if let Some(mouse_position) = self.mouse_position {
+                            let event_type = if element_state == ElementState::Pressed { EventType::MouseDown } else { EventType::MouseUp };
+                            let event_payload = create_event_payload(mouse_position, Some(button), &self.modifiers);
+                            self.renderer.propagate_event(mouse_position, &event_type, &event_payload);
+
+                            if self.update_interactive_styles() {
+                                self.windowing_system.window.request_redraw();
+                            }
+                        }
+                    }
                 }
             },
             WindowEvent::CursorMoved { position, .. } => {
                 self.mouse_position = Some(Point::new(position.x as f32, position.y as f32));
 
-                if self.is_mouse_pressed {
-                    if let Some(mouse_position) = self.mouse_position {
+                if let Some(mouse_position) = self.mouse_position {
+                    if self.is_mouse_pressed {
                         self.renderer.handle_event(mouse_position, EventType::MouseDrag);
+                    } else {
+                        self.renderer.handle_event(mouse_position, EventType::MouseMove);
+                    }
+
+                    // The handlers changing the state request a redraw through their state change events
+                    let event_payload = create_event_payload(mouse_position, None, &self.modifiers);
+                    self.renderer.propagate_event(mouse_position, &EventType::MouseMove, &event_payload);
+                    
+                    if self.update_interactive_styles() || self.is_mouse_pressed {
+                        self.windowing_system.window.request_redraw();
+                    }
+                }
+            },
+            WindowEvent::CursorLeft { .. } => {
+                if let Some(mouse_position) = self.mouse_position.take() {
+                    self.renderer.handle_event(mouse_position, EventType::MouseLeave);
+
+                    let event_payload = create_event_payload(mouse_position, None, &self.modifiers);
+                    self.renderer.propagate_event(mouse_position, &EventType::MouseLeave, &event_payload);
+
+                    if self.update_interactive_styles() {
                         self.windowing_system.window.request_redraw();
                     }
                 }
@@ -199,16 +289,33 @@ impl<State> ApplicationHandler<ComponentEvent> for Application<State> {
             
                 if let Some(mouse_position) = self.mouse_position {
                     self.renderer.handle_event(mouse_position, EventType::MouseRoll(scroll_delta));
+
+                    let mut event_payload = create_event_payload(mouse_position, None, &self.modifiers);
+                    event_payload.scroll_delta = scroll_delta;
+                    self.renderer.propagate_event(mouse_position, &EventType::MouseRoll(scroll_delta), &event_payload);
+
                     self.windowing_system.window.request_redraw();
                 }
             },
             WindowEvent::KeyboardInput {
-                event: KeyEvent { logical_key, .. },
+                event: KeyEvent { logical_key, text, state, .. },
                 ..
             } => {
                 if self.modifiers.state().super_key() && logical_key == "q" {
                     event_loop.exit();
                 }
+
+                let mouse_position = self.mouse_position.unwrap_or_default();
+                let event_payload = create_keyboard_event_payload(mouse_position, &logical_key, text.as_deref(), &self.modifiers);
+                if state == ElementState::Pressed {
+                    self.renderer.propagate_event(mouse_position, &EventType::KeyDown, &event_payload);
+                    if event_payload.text.is_some() {
+                        self.renderer.propagate_event(mouse_position, &EventType::Input, &event_payload);
+                    }
+                } else {
+                    self.renderer.propagate_event(mouse_position, &EventType::KeyUp, &event_payload);
+                }
+
                 self.windowing_system.window.request_redraw();
             }
             _ => (),
@@ -219,10 +326,19 @@ impl<State> ApplicationHandler<ComponentEvent> for Application<State> {
 
     fn user_event(&mut self, _: &ActiveEventLoop, event: ComponentEvent) {
         match event {
-            ComponentEvent::StateChange(component_id) => {
-                self.renderer.react_to_state_change(component_id);
-                self.renderer.layout();
-                self.windowing_system.window.request_redraw();
+            ComponentEvent::StateChange(component_id, property_name) => {
+                // The patched elements keep their interaction state, and are restyled before the layout
+                let patch_impact = self.renderer.react_to_state_change(component_id, &property_name);
+                if self.update_rendering(patch_impact) {
+                    self.windowing_system.window.request_redraw();
+                }
+            },
+            // The state changes of the handler of the output are reported by their own events
+            ComponentEvent::OutputEmitted(mut output_event) => {
+                self.renderer.react_to_output(&mut output_event);
+                if self.update_interactive_styles() {
+                    self.windowing_system.window.request_redraw();
+                }
             },
         }
     }
@@ -0,0 +1,62 @@
+use skia_safe::Point;
+use winit::{event::{Modifiers, MouseButton as WinitMouseButton}, keyboard::Key};
+
+use crate::rendering::elements::event_payload::{EventPayload, KeyModifiers, MouseButton};
+
+
+/*
+ * Translates the winit input state into the event payload passed to the component event handlers.
+ */
+pub fn create_event_payload(
+    cursor_position: Point,
+    mouse_button: Option<WinitMouseButton>,
+    modifiers: &Modifiers,
+) -> EventPayload {
+    EventPayload::new(
+        cursor_position,
+        mouse_button.map(map_mouse_button),
+        map_modifiers(modifiers),
+    )
+}
+
+pub fn create_keyboard_event_payload(
+    cursor_position: Point,
+    logical_key: &Key,
+    text: Option<&str>,
+    modifiers: &Modifiers,
+) -> EventPayload {
+    let mut event_payload = create_event_payload(cursor_position, None, modifiers);
+    event_payload.key = Some(map_key_name(logical_key));
+    event_payload.text = text.map(str::to_string);
+    event_payload
+}
+
+fn map_key_name(logical_key: &Key) -> String {
+    match logical_key {
+        Key::Character(character) => character.to_string(),
+        Key::Named(named_key) => format!("{:?}", named_key),
+        _ => String::from("Unidentified"),
+    }
+}
+
+fn map_mouse_button(mouse_button: WinitMouseButton) -> MouseButton {
+    match mouse_button {
+        WinitMouseButton::Left => MouseButton::Left,
+        WinitMouseButton::Right => MouseButton::Right,
+        WinitMouseButton::Middle => MouseButton::Middle,
+        WinitMouseButton::Back => MouseButton::Other(3),
+        WinitMouseButton::Forward => MouseButton::Other(4),
+        WinitMouseButton::Other(button) => MouseButton::Other(button),
+    }
+}
+
+fn map_modifiers(modifiers: &Modifiers) -> KeyModifiers {
+    let state = modifiers.state();
+
+    KeyModifiers {
+        shift: state.shift_key(),
+        control: state.control_key(),
+        alt: state.alt_key(),
+        super_key: state.super_key(),
+    }
+}
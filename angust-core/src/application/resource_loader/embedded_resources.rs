@@ -0,0 +1,57 @@
+use std::{borrow::Cow, collections::HashMap, fs, path::{Path, PathBuf}};
+
+use once_cell::sync::OnceCell;
+
+use super::path_navigator;
+
+
+/*
+ * Resources embedded into the binary at compile time, keyed by their path relative to the project root.
+ * The bundle is generated by angust_build::embed_resources in the build script of the application,
+ * and registered with include_resources!(); the resources missing from it are read from the project directory.
+ */
+static EMBEDDED_RESOURCES: OnceCell<HashMap<String, &'static [u8]>> = OnceCell::new();
+
+#[macro_export]
+macro_rules! include_resources {
+    () => {
+        $crate::application::resource_loader::embedded_resources::set_embedded_resources(
+            include!(concat!(env!("OUT_DIR"), "/angust_resources.rs"))
+        )
+    };
+}
+
+pub fn set_embedded_resources(resources: &'static [(&'static str, &'static [u8])]) {
+    let resources_map = resources.iter()
+        .map(|(path, content)| (path.to_string(), *content))
+        .collect();
+
+    let _ = EMBEDDED_RESOURCES.set(resources_map);
+}
+
+pub fn read_resource(relative_path: &str) -> Option<Cow<'static, [u8]>> {
+    if Path::new(relative_path).is_absolute() {
+        return fs::read(relative_path).ok().map(Cow::Owned);
+    }
+
+    let normalized_path = normalize_path(relative_path);
+
+    if let Some(content) = EMBEDDED_RESOURCES.get().and_then(|resources| resources.get(&normalized_path)) {
+        return Some(Cow::Borrowed(*content));
+    }
+
+    let path = PathBuf::from(path_navigator::identify_project_root_path()).join(&normalized_path);
+    fs::read(path).ok().map(Cow::Owned)
+}
+
+pub fn read_resource_to_string(relative_path: &str) -> Option<String> {
+    read_resource(relative_path)
+        .and_then(|content| String::from_utf8(content.into_owned()).ok())
+}
+
+fn normalize_path(path: &str) -> String {
+    path.split(['/', '\\'])
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect::<Vec<&str>>()
+        .join("/")
+}
@@ -1,22 +1,13 @@
-use std::{fs, path::PathBuf};
-
-use super::path_navigator;
+use super::embedded_resources;
 
 pub fn load_index_html(index_html_relative_path: String) -> Option<String> {
-    let project_root_path = path_navigator::identify_project_root_path();
-    let path = 
-        project_root_path + "/" +
-        index_html_relative_path.as_str();
-
-    fs::read_to_string(PathBuf::from(path))
-        .map_or(None, |content| Some(content))
+    embedded_resources::read_resource_to_string(&index_html_relative_path)
 }
 
 pub fn load_html(html_directory_relative_path: String, html_file_relative_path: String) -> Option<String> {
     let path = 
-        path_navigator::get_html_directory_path(html_directory_relative_path) + "/" +
+        html_directory_relative_path + "/" +
         html_file_relative_path.as_str();
 
-    fs::read_to_string(PathBuf::from(path))
-        .map_or(None, |content| Some(content))
+    embedded_resources::read_resource_to_string(&path)
 }
\ No newline at end of file
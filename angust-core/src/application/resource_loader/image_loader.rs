@@ -1,12 +1,17 @@
-use image::{ImageReader, DynamicImage, ImageError};
+use std::io::{Error, ErrorKind};
 
-use crate::application::resource_loader::path_navigator;
+use image::{DynamicImage, ImageError};
+
+use super::embedded_resources;
 
 
 pub fn load_image(image_directory_relative_path: String, image_file_relative_path: String) -> Result<DynamicImage, ImageError> {
     let path = 
-        path_navigator::get_image_directory_path(image_directory_relative_path) + "/" +
+        image_directory_relative_path + "/" +
         image_file_relative_path.as_str();
-        
-    ImageReader::open(path)?.decode()
-}
\ No newline at end of file
+
+    let content = embedded_resources::read_resource(&path)
+        .ok_or_else(|| ImageError::IoError(Error::new(ErrorKind::NotFound, format!("Image not found: {}", path))))?;
+
+    image::load_from_memory(&content)
+}
@@ -1,5 +1,6 @@
 
 pub mod configuration_loader;
+pub mod embedded_resources;
 pub mod html_loader;
 pub mod stylesheet_loader;
 pub mod image_loader;
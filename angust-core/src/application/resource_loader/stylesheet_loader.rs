@@ -1,41 +1,48 @@
-use std::{fs, path::PathBuf};
-
-use regex::Regex;
-
-use crate::application::resource_loader::path_navigator;
-
-
-pub fn load_stylesheet(styles_dir_relative_path: &String, stylesheet_relative_path: &String) -> Option<String> {
-    let styles_relative_path = styles_dir_relative_path.clone() + "/" + stylesheet_relative_path;
-    let path = path_navigator::get_styles_path(styles_relative_path);
-
-    let initial_string = fs::read_to_string(PathBuf::from(path))
-        .map_or(None, |content| Some(content));
-
-    let import_regex = Regex::new(r#"@import\s+url\("([^"]+)"\);"#).unwrap();
-
-    // Detect import statements and load the imported stylesheets
-    let mut styles: String = String::new();
-
-    if let Some(content) = initial_string {
-        let lines = content.lines();
+use crate::{application::resource_loader::embedded_resources, parsing::{css::stylesheet_parser::{self, Stylesheet}, diagnostics::Diagnostic}};
+
+
+/*
+ * Loads and parses a stylesheet along with the stylesheets it imports, @import url("other.css"); or @import "other.css";.
+ * The imported stylesheets come before the importing one, so that its rules override theirs.
+ * Syntax errors and the imported stylesheets that cannot be loaded are added to the diagnostics,
+ * along with warnings for the other at-rules (@media, @supports, @layer, ...), which are not supported and ignored.
+ */
+pub fn load_stylesheet(styles_dir_relative_path: &str, stylesheet_relative_path: &str, diagnostics: &mut Vec<Diagnostic>) -> Option<Stylesheet> {
+    let styles_relative_path = format!("{}/{}", styles_dir_relative_path, stylesheet_relative_path);
+    let content = embedded_resources::read_resource_to_string(&styles_relative_path)?;
+    let parsed_stylesheet = stylesheet_parser::parse_stylesheet(&content, Some(&styles_relative_path), diagnostics);
+
+    let mut stylesheet = Stylesheet::default();
+    for at_rule in parsed_stylesheet.at_rules.iter() {
+        let location = Some(at_rule.location);
+        if at_rule.name != "import" {
+            diagnostics.push(Diagnostic::warning(format!("Unsupported at-rule @{}, ignored", at_rule.name)).at(Some(styles_relative_path.clone()), location));
+            continue;
+        }
 
-        for line in lines {
-            if let Some(caps) = import_regex.captures(line) {
-                let imported_path = caps.get(1).map_or("", |m| m.as_str()); // Capture the path inside the url()
+        let Some(imported_path) = import_path(&at_rule.prelude) else {
+            diagnostics.push(Diagnostic::error(format!("Invalid @import {}", at_rule.prelude)).at(Some(styles_relative_path.clone()), location));
+            continue;
+        };
+        match load_stylesheet(styles_dir_relative_path, &imported_path, diagnostics) {
+            Some(imported_stylesheet) => stylesheet.extend(imported_stylesheet),
+            None => diagnostics.push(
+                Diagnostic::error(format!("Failed to load imported stylesheet '{}'", imported_path)).at(Some(styles_relative_path.clone()), location)
+            ),
+        }
+    }
 
-                let imported_content = load_stylesheet(styles_dir_relative_path, &imported_path.to_string())
-                    .unwrap_or_else(|| {
-                        panic!("Failed to load imported stylesheet: {}", imported_path);
-                    });
+    stylesheet.extend(parsed_stylesheet);
+    Some(stylesheet)
+}
 
-                styles.push_str(&imported_content);
-                continue;
-            }
-            styles.push_str(line);
-            styles.push('\n');
-        }
+// Path of an @import, written as url("theme.css"), url(theme.css) or "theme.css", possibly followed by media queries
+fn import_path(prelude: &str) -> Option<String> {
+    let url = match prelude.strip_prefix("url(") {
+        Some(url_arguments) => url_arguments.split_once(')')?.0.trim(),
+        None => prelude.split_whitespace().next()?,
     };
 
-    Some(styles)
-}
\ No newline at end of file
+    let path = url.trim_matches(|character| character == '"' || character == '\'');
+    (!path.is_empty()).then(|| path.to_string())
+}
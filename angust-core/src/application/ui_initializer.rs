@@ -1,12 +1,12 @@
 
 use kuchiki::NodeRef;
 
-use crate::parsing::html::html_parser::parse_html_content;
+use crate::parsing::{css::stylesheet_parser::Stylesheet, diagnostics, html::{html_parser::parse_html_content, template_diagnostics::SourceLocations}};
 
 use super::{angust_configuration::AngustConfiguration, resource_loader::{html_loader, stylesheet_loader}};
 
 
-pub fn load_resources(angust_config: &AngustConfiguration) -> (NodeRef, String) {
+pub fn load_resources(angust_config: &AngustConfiguration) -> (NodeRef, SourceLocations, Stylesheet) {
     let html_content = html_loader::load_index_html(
         angust_config.pathing_config.index_html_path.clone()
     ).unwrap_or_else(|| {
@@ -14,13 +14,15 @@ pub fn load_resources(angust_config: &AngustConfiguration) -> (NodeRef, String)
     });
 
     let dom = parse_html_content(html_content.as_str());
+    let source_locations = SourceLocations::compute(&html_content, &dom);
     
-    let stylesheets = stylesheet_loader::load_stylesheet(
-        &angust_config.pathing_config.styles_dir_path, &String::from("styles.css")
+    let mut stylesheet_diagnostics = Vec::new();
+    let stylesheet = stylesheet_loader::load_stylesheet(
+        &angust_config.pathing_config.styles_dir_path, "styles.css", &mut stylesheet_diagnostics
     ).unwrap_or_else(|| {
         panic!("Failed to load stylesheet")
     });
+    diagnostics::report_all(stylesheet_diagnostics);
 
-
-    (dom, stylesheets)
-}
\ No newline at end of file
+    (dom, source_locations, stylesheet)
+}
@@ -2,15 +2,15 @@ use skia_safe::Color;
 
 use crate::rendering::elements::styles::{Border, Styles};
 
-use super::dimension_parser::parse_dimension;
+use super::dimension_parser::parse_fixed_dimension;
 
 
-pub fn update_appearance_style(styles: &mut Styles, key: &str, value: &str) {
+pub fn update_appearance_style(styles: &mut Styles, key: &str, value: &str) -> Result<(), String> {
     match key {
         "background-color" => styles.background_color = parse_color(value),
         "color" => styles.text_color = parse_color(value),
         "border-width" => styles.border = Some(Border {
-            width: parse_dimension(value).unwrap_or_default(),
+            width: parse_fixed_dimension(value)?,
             color: styles.border.unwrap_or_default().color,
             radius: styles.border.unwrap_or_default().radius,
         }),
@@ -19,8 +19,10 @@ pub fn update_appearance_style(styles: &mut Styles, key: &str, value: &str) {
             color: parse_color(value).unwrap_or(Color::BLACK),
             radius: styles.border.unwrap_or_default().radius,
         }),
-        _ => println!("Unhandled color property: {}", key),
+        _ => return Err(format!("Unsupported color property '{}'", key)),
     }
+
+    Ok(())
 }
 
 pub fn parse_color(value: &str) -> Option<Color> {
@@ -0,0 +1,195 @@
+use crate::rendering::elements::styles::CalcSum;
+
+
+/*
+ * Parses a calc() expression, e.g. calc(100% - 2 * 1rem), into the sum of its terms per unit.
+ * Lengths can be added and subtracted, and multiplied or divided by numbers; parentheses and nested calc() group terms.
+ * As in CSS, the + and - operators must be surrounded by whitespace, so that they are not read as signs.
+ */
+pub fn parse_calc(value: &str) -> Result<CalcSum, String> {
+    let mut parser = CalcParser {
+        characters: value.trim().chars().collect(),
+        position: 0,
+    };
+
+    if !parser.consume_function_start() {
+        return Err("Expected calc(".to_string());
+    }
+    let result = parser.parse_group()?;
+    if parser.position < parser.characters.len() {
+        return Err(format!("Unexpected '{}' after calc()", parser.characters[parser.position..].iter().collect::<String>()));
+    }
+
+    match result {
+        CalcValue::Length(sum) => Ok(sum),
+        CalcValue::Number(_) => Err("calc() must result in a length".to_string()),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CalcValue {
+    Number(f32),
+    Length(CalcSum),
+}
+
+struct CalcParser {
+    characters: Vec<char>,
+    position: usize,
+}
+
+impl CalcParser {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.characters.get(self.position + offset).copied()
+    }
+
+    // Returns whether whitespace was skipped
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.position;
+        while self.peek(0).is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+        self.position > start
+    }
+
+    // Consumes calc( or (, ignoring the case of the function name
+    fn consume_function_start(&mut self) -> bool {
+        let remaining: String = self.characters[self.position..].iter().take(5).collect();
+        if remaining.eq_ignore_ascii_case("calc(") {
+            self.position += 5;
+            true
+        } else if self.peek(0) == Some('(') {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Sum after its opening parenthesis, up to and including the closing one
+    fn parse_group(&mut self) -> Result<CalcValue, String> {
+        let sum = self.parse_sum()?;
+        self.skip_whitespace();
+        match self.peek(0) {
+            Some(')') => {
+                self.position += 1;
+                Ok(sum)
+            },
+            Some(character) => Err(format!("Unexpected '{}' in calc()", character)),
+            None => Err("Unclosed parenthesis in calc()".to_string()),
+        }
+    }
+
+    fn parse_sum(&mut self) -> Result<CalcValue, String> {
+        let mut result = self.parse_product()?;
+
+        loop {
+            let is_preceded_by_whitespace = self.skip_whitespace();
+            let operator = match self.peek(0) {
+                Some(operator @ ('+' | '-')) => operator,
+                _ => return Ok(result),
+            };
+            if !is_preceded_by_whitespace || !self.peek(1).is_some_and(char::is_whitespace) {
+                return Err(format!("The '{}' operator of calc() must be surrounded by whitespace", operator));
+            }
+            self.position += 1;
+
+            let operand = self.parse_product()?;
+            let sign = if operator == '+' { 1.0 } else { -1.0 };
+            result = match (result, operand) {
+                (CalcValue::Number(left), CalcValue::Number(right)) => CalcValue::Number(left + sign * right),
+                (CalcValue::Length(left), CalcValue::Length(right)) => CalcValue::Length(add(left, scale(right, sign))),
+                _ => return Err(format!("Cannot use '{}' between a number and a length in calc()", operator)),
+            };
+        }
+    }
+
+    fn parse_product(&mut self) -> Result<CalcValue, String> {
+        let mut result = self.parse_operand()?;
+
+        loop {
+            let position = self.position;
+            self.skip_whitespace();
+            let operator = match self.peek(0) {
+                Some(operator @ ('*' | '/')) => operator,
+                _ => {
+                    // The whitespace before a + or - operator is checked by the sum
+                    self.position = position;
+                    return Ok(result);
+                },
+            };
+            self.position += 1;
+
+            let operand = self.parse_operand()?;
+            result = match (operator, result, operand) {
+                ('*', CalcValue::Number(left), CalcValue::Number(right)) => CalcValue::Number(left * right),
+                ('*', CalcValue::Length(length), CalcValue::Number(factor)) | ('*', CalcValue::Number(factor), CalcValue::Length(length)) => {
+                    CalcValue::Length(scale(length, factor))
+                },
+                ('*', CalcValue::Length(_), CalcValue::Length(_)) => return Err("Cannot multiply two lengths in calc()".to_string()),
+                ('/', _, CalcValue::Number(0.0)) => return Err("Division by zero in calc()".to_string()),
+                ('/', CalcValue::Number(left), CalcValue::Number(divisor)) => CalcValue::Number(left / divisor),
+                ('/', CalcValue::Length(length), CalcValue::Number(divisor)) => CalcValue::Length(scale(length, 1.0 / divisor)),
+                _ => return Err("Cannot divide by a length in calc()".to_string()),
+            };
+        }
+    }
+
+    // Number, length, or parenthesized sum
+    fn parse_operand(&mut self) -> Result<CalcValue, String> {
+        self.skip_whitespace();
+        if self.consume_function_start() {
+            return self.parse_group();
+        }
+
+        let start = self.position;
+        if self.peek(0).is_some_and(|character| character == '+' || character == '-') {
+            self.position += 1;
+        }
+        while self.peek(0).is_some_and(|character| character.is_ascii_digit() || character == '.') {
+            self.position += 1;
+        }
+        let number_text: String = self.characters[start..self.position].iter().collect();
+        let number = number_text.parse::<f32>()
+            .map_err(|_| match self.peek(0) {
+                Some(character) => format!("Unexpected '{}' in calc()", character),
+                None => "Unexpected end of calc()".to_string(),
+            })?;
+
+        let unit_start = self.position;
+        while self.peek(0).is_some_and(|character| character.is_ascii_alphabetic() || character == '%') {
+            self.position += 1;
+        }
+        let unit: String = self.characters[unit_start..self.position].iter().collect();
+
+        let term = match unit.to_ascii_lowercase().as_str() {
+            "" => return Ok(CalcValue::Number(number)),
+            "px" => CalcSum { px: number, ..CalcSum::default() },
+            "vh" => CalcSum { vh: number, ..CalcSum::default() },
+            "vw" => CalcSum { vw: number, ..CalcSum::default() },
+            "rem" => CalcSum { rem: number, ..CalcSum::default() },
+            "%" => CalcSum { percent: number, ..CalcSum::default() },
+            _ => return Err(format!("Unsupported unit '{}' in calc()", unit)),
+        };
+        Ok(CalcValue::Length(term))
+    }
+}
+
+fn add(left: CalcSum, right: CalcSum) -> CalcSum {
+    CalcSum {
+        px: left.px + right.px,
+        vh: left.vh + right.vh,
+        vw: left.vw + right.vw,
+        rem: left.rem + right.rem,
+        percent: left.percent + right.percent,
+    }
+}
+
+fn scale(sum: CalcSum, factor: f32) -> CalcSum {
+    CalcSum {
+        px: sum.px * factor,
+        vh: sum.vh * factor,
+        vw: sum.vw * factor,
+        rem: sum.rem * factor,
+        percent: sum.percent * factor,
+    }
+}
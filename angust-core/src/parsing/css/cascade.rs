@@ -0,0 +1,272 @@
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+
+use once_cell::sync::Lazy;
+
+use crate::rendering::elements::styles::{Border, SizingPolicy, Styles};
+
+use super::{selector_matcher::{self, StyledElement}, selector_parser::Specificity, stylesheet_parser::{self, Declaration, StyleRule, Stylesheet}};
+
+
+/*
+ * Default styles of the elements, overridden by the declarations of the application.
+ * The properties left out take their initial value, see Styles::default.
+ */
+static USER_AGENT_CSS: &str = "
+div, button, img, ng-content {
+    display: block;
+}
+";
+
+static USER_AGENT_STYLESHEET: Lazy<Stylesheet> = Lazy::new(|| {
+    stylesheet_parser::parse_stylesheet(USER_AGENT_CSS, Some("user-agent stylesheet"), &mut Vec::new())
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeclarationOrigin {
+    UserAgent,
+    Stylesheet,
+    Inline,
+}
+
+// Declaration winning the cascade for its property
+pub struct CascadedDeclaration<'a> {
+    pub declaration: &'a Declaration,
+    pub origin: DeclarationOrigin,
+}
+
+/*
+ * Rules of the user-agent stylesheet and of the stylesheet of the application whose selectors may match an element of a template,
+ * computed once per element when the template is compiled, so that the cascade only matches these against the path of the element.
+ */
+#[derive(Debug)]
+pub struct CandidateRules {
+    stylesheet: Option<Arc<Stylesheet>>, // Stylesheet the rules were selected from
+    user_agent_rule_indices: Vec<usize>,
+    stylesheet_rule_indices: Vec<usize>,
+}
+
+impl CandidateRules {
+    pub fn compute(element: &StyledElement, bound_attributes: &[&str], stylesheet: &Option<Arc<Stylesheet>>) -> Self {
+        let candidate_rule_indices = |stylesheet: &Stylesheet| -> Vec<usize> {
+            stylesheet.rules.iter().enumerate()
+                .filter(|(_, rule)| rule.selectors.iter().any(|selector| selector_matcher::may_match_selector(selector, element, bound_attributes)))
+                .map(|(rule_index, _)| rule_index)
+                .collect()
+        };
+
+        CandidateRules {
+            stylesheet: stylesheet.clone(),
+            user_agent_rule_indices: candidate_rule_indices(&USER_AGENT_STYLESHEET),
+            stylesheet_rule_indices: stylesheet.as_deref().map(candidate_rule_indices).unwrap_or_default(),
+        }
+    }
+
+    fn is_selected_from(&self, stylesheet: Option<&Stylesheet>) -> bool {
+        match (self.stylesheet.as_deref(), stylesheet) {
+            (Some(selected_stylesheet), Some(stylesheet)) => std::ptr::eq(selected_stylesheet, stylesheet),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+/*
+ * Precedence of a declaration in the cascade, compared field by field:
+ * - importance: normal user-agent < normal author < !important author < !important user-agent declarations
+ * - inline declarations over stylesheet rules
+ * - specificity of the most specific selector of the rule matching the element
+ * - source order, later declarations winning
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct CascadePrecedence {
+    importance: u8,
+    is_inline: bool,
+    specificity: Specificity,
+    source_order: (usize, usize), // (Rule index, declaration index)
+}
+
+/*
+ * Runs the cascade for the last element of the path: gathers the declarations of the user-agent stylesheet,
+ * of the stylesheet rules matching the element and of its inline styles, and keeps the winning declaration of each property.
+ * The returned declarations are in increasing precedence order.
+ */
+pub fn cascade_declarations<'a>(
+    element_path: &[Rc<StyledElement>],
+    stylesheet: Option<&'a Stylesheet>,
+    inline_declarations: &'a [Declaration],
+) -> Vec<CascadedDeclaration<'a>> {
+    let mut winning_declarations: HashMap<&str, (CascadePrecedence, CascadedDeclaration<'a>)> = HashMap::new();
+
+    let mut add_declaration = |declaration: &'a Declaration, origin: DeclarationOrigin, precedence: CascadePrecedence| {
+        let is_winning = winning_declarations.get(declaration.name.as_str())
+            .is_none_or(|(winning_precedence, _)| precedence >= *winning_precedence);
+        if is_winning {
+            winning_declarations.insert(&declaration.name, (precedence, CascadedDeclaration { declaration, origin }));
+        }
+    };
+
+    for (origin, rule_index, rule) in rules_to_match(element_path, stylesheet) {
+        let specificity = rule.selectors.iter()
+            .filter(|selector| selector_matcher::matches_selector(selector, element_path))
+            .map(|selector| selector.specificity())
+            .max();
+        let specificity = match specificity {
+            Some(specificity) => specificity,
+            None => continue,
+        };
+
+        for (declaration_index, declaration) in rule.declarations.iter().enumerate() {
+            add_declaration(declaration, origin, CascadePrecedence {
+                importance: importance(origin, declaration.is_important),
+                is_inline: false,
+                specificity,
+                source_order: (rule_index, declaration_index),
+            });
+        }
+    }
+
+    for (declaration_index, declaration) in inline_declarations.iter().enumerate() {
+        add_declaration(declaration, DeclarationOrigin::Inline, CascadePrecedence {
+            importance: importance(DeclarationOrigin::Inline, declaration.is_important),
+            is_inline: true,
+            specificity: Specificity::default(),
+            source_order: (0, declaration_index),
+        });
+    }
+
+    let mut cascaded_declarations: Vec<(CascadePrecedence, CascadedDeclaration<'a>)> = winning_declarations.into_values().collect();
+    cascaded_declarations.sort_by_key(|(precedence, _)| *precedence);
+    cascaded_declarations.into_iter().map(|(_, cascaded_declaration)| cascaded_declaration).collect()
+}
+
+/*
+ * Whether the styles of the last element of the path depend on the interaction state of the elements of the path,
+ * that is whether a rule with pseudo-classes matches it for some state.
+ */
+pub fn depends_on_interaction_state(element_path: &[Rc<StyledElement>], stylesheet: Option<&Stylesheet>) -> bool {
+    rules_to_match(element_path, stylesheet)
+        .flat_map(|(_, _, rule)| rule.selectors.iter())
+        .any(|selector| selector.has_interaction_pseudo_classes() && selector_matcher::matches_selector_in_any_state(selector, element_path))
+}
+
+// Rules of the user-agent stylesheet and of the given one to match against the last element of the path, with their origin and index
+fn rules_to_match<'p, 'a: 'p>(
+    element_path: &'p [Rc<StyledElement>],
+    stylesheet: Option<&'a Stylesheet>,
+) -> impl Iterator<Item = (DeclarationOrigin, usize, &'a StyleRule)> + 'p {
+    let candidate_rules = element_path.last()
+        .and_then(|element| element.candidate_rules.as_deref())
+        .filter(|candidate_rules| candidate_rules.is_selected_from(stylesheet));
+
+    [(&*USER_AGENT_STYLESHEET, DeclarationOrigin::UserAgent)].into_iter()
+        .chain(stylesheet.map(|stylesheet| (stylesheet, DeclarationOrigin::Stylesheet)))
+        .flat_map(move |(stylesheet, origin)| {
+            let rule_indices: Box<dyn Iterator<Item = usize>> = match (candidate_rules, origin) {
+                (Some(candidate_rules), DeclarationOrigin::UserAgent) => Box::new(candidate_rules.user_agent_rule_indices.iter().copied()),
+                (Some(candidate_rules), _) => Box::new(candidate_rules.stylesheet_rule_indices.iter().copied()),
+                (None, _) => Box::new(0..stylesheet.rules.len()),
+            };
+            rule_indices.map(move |rule_index| (origin, rule_index, &stylesheet.rules[rule_index]))
+        })
+}
+
+fn importance(origin: DeclarationOrigin, is_important: bool) -> u8 {
+    match (origin, is_important) {
+        (DeclarationOrigin::UserAgent, false) => 0,
+        (_, false) => 1,
+        (DeclarationOrigin::UserAgent, true) => 3,
+        (_, true) => 2,
+    }
+}
+
+/*
+ * CSS-wide keywords, which any property accepts:
+ * inherit takes the value of the parent, initial the initial value of the property (see Styles::default),
+ * and unset behaves as inherit for inherited properties and as initial for the others.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CssWideKeyword {
+    Inherit,
+    Initial,
+    Unset,
+}
+
+pub fn parse_css_wide_keyword(value: &str) -> Option<CssWideKeyword> {
+    match value.to_ascii_lowercase().as_str() {
+        "inherit" => Some(CssWideKeyword::Inherit),
+        "initial" => Some(CssWideKeyword::Initial),
+        "unset" => Some(CssWideKeyword::Unset),
+        _ => None,
+    }
+}
+
+// Properties inherited by default from the parent, see css_parser::merge_styles
+static INHERITED_PROPERTIES: [&str; 6] = ["color", "white-space", "font-size", "font-weight", "font-family", "font-style"];
+
+pub fn apply_css_wide_keyword(
+    styles: &mut Styles,
+    property: &str,
+    keyword: CssWideKeyword,
+    parent_styles: Option<&Styles>,
+) -> Result<(), String> {
+    let initial_styles = Styles::default();
+    let is_inherited = match keyword {
+        CssWideKeyword::Inherit => true,
+        CssWideKeyword::Initial => false,
+        CssWideKeyword::Unset => INHERITED_PROPERTIES.contains(&property),
+    };
+
+    let source_styles = match (is_inherited, parent_styles) {
+        (true, Some(parent_styles)) => parent_styles,
+        _ => &initial_styles,
+    };
+    copy_property(source_styles, styles, property)
+}
+
+// Copies the value of a single property, leaving the other properties sharing its field (e.g. border) untouched
+fn copy_property(source_styles: &Styles, styles: &mut Styles, property: &str) -> Result<(), String> {
+    let source_sizing_policy = source_styles.sizing_policy.unwrap_or_default();
+    let source_border = source_styles.border.unwrap_or_default();
+
+    match property {
+        "display" => styles.display = source_styles.display,
+        "flex-direction" => styles.flex_direction = source_styles.flex_direction,
+        "flex-wrap" => styles.flex_wrap = source_styles.flex_wrap,
+        "justify-content" => styles.justify_content = source_styles.justify_content,
+        "align-items" => styles.align_items = source_styles.align_items,
+        "align-content" => styles.align_content = source_styles.align_content,
+        "overflow" => styles.overflow = source_styles.overflow,
+        "flex-grow" => styles.flex_grow = source_styles.flex_grow,
+        "flex-shrink" => styles.flex_shrink = source_styles.flex_shrink,
+        "margin" => styles.margin = source_styles.margin,
+        "padding" => styles.padding = source_styles.padding,
+        "spacing" => styles.spacing = source_styles.spacing,
+        "width" => sizing_policy_mut(styles).width = source_sizing_policy.width,
+        "height" => sizing_policy_mut(styles).height = source_sizing_policy.height,
+        "min-width" => sizing_policy_mut(styles).min_width = source_sizing_policy.min_width,
+        "max-width" => sizing_policy_mut(styles).max_width = source_sizing_policy.max_width,
+        "min-height" => sizing_policy_mut(styles).min_height = source_sizing_policy.min_height,
+        "max-height" => sizing_policy_mut(styles).max_height = source_sizing_policy.max_height,
+        "background-color" => styles.background_color = source_styles.background_color,
+        "border-width" => border_mut(styles).width = source_border.width,
+        "border-color" => border_mut(styles).color = source_border.color,
+        "border-radius" => border_mut(styles).radius = source_border.radius,
+        "color" => styles.text_color = source_styles.text_color,
+        "white-space" => styles.white_space = source_styles.white_space,
+        "font-size" => styles.font_size = source_styles.font_size,
+        "font-weight" => styles.font_weight = source_styles.font_weight,
+        "font-family" => styles.font_family = source_styles.font_family,
+        "font-style" => styles.font_style = source_styles.font_style,
+        _ => return Err(format!("Unknown style property '{}'", property)),
+    }
+
+    Ok(())
+}
+
+fn sizing_policy_mut(styles: &mut Styles) -> &mut SizingPolicy {
+    styles.sizing_policy.get_or_insert_with(SizingPolicy::default)
+}
+
+fn border_mut(styles: &mut Styles) -> &mut Border {
+    styles.border.get_or_insert_with(Border::default)
+}
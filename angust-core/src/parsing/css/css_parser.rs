@@ -1,69 +1,89 @@
-use kuchiki::Attributes;
+use std::{rc::Rc, sync::Arc};
 
 use crate::rendering::elements::styles::Styles;
 
-use super::{appearance_parser::update_appearance_style, dimension_parser::update_dimension_style, layout_parser::update_layout_style, stylesheet_parser::Stylesheet, text_parser::update_text_style};
+use super::{appearance_parser::update_appearance_style, cascade::{self, CascadedDeclaration, CssWideKeyword, DeclarationOrigin}, custom_properties::{self, CustomProperties}, dimension_parser::update_dimension_style, layout_parser::update_layout_style, selector_matcher::StyledElement, stylesheet_parser::{self, Declaration, Stylesheet}, text_parser::update_text_style};
 
 
+/*
+ * Computes the styles of the last element of the path: the winning declaration of each property in the cascade
+ * (user-agent defaults, matching stylesheet rules and inline styles, see cascade.rs) is applied over the initial values
+ * and the inherited values taken from the parent, once its var() functions are substituted (see custom_properties.rs).
+ * Invalid declarations are skipped, and described in the returned warnings.
+ */
 pub fn parse_styles(
-    attributes: &Attributes, 
+    element_path: &[Rc<StyledElement>], 
     parent_styles: Option<&Styles>, 
-    stylesheet: &Option<Stylesheet>
+    stylesheet: &Option<Arc<Stylesheet>>,
+    warnings: &mut Vec<String>,
 ) -> Styles {
+    // Inherited properties start from the values of the parent, so that initial can reset them
     let mut styles = Styles::default();
-    
-    if let Some(class_names) = attributes.get("class") {
-        styles = parse_class_styles(class_names, stylesheet);
-    }
-
-    if let Some(style_attr) = attributes.get("style") {
-        styles = parse_inline_styles(style_attr);
-    }
-
     if let Some(parent) = parent_styles {
         merge_styles(parent, &mut styles);
     }
 
-    styles
-}
-
-fn parse_class_styles(class_names: &str, stylesheet_option: &Option<Stylesheet>) -> Styles {
-    if stylesheet_option.is_none() {
-        return Styles::default();
-    }
-    let stylesheet = stylesheet_option.as_ref().unwrap();
+    let inline_declarations = parse_inline_declarations(element_path, warnings);
+    let cascaded_declarations = cascade::cascade_declarations(element_path, stylesheet.as_deref(), &inline_declarations);
+    let custom_properties = update_custom_properties(element_path, &cascaded_declarations, warnings);
 
-    let mut styles = Styles::default();
+    for cascaded_declaration in cascaded_declarations {
+        let declaration = cascaded_declaration.declaration;
+        if custom_properties::is_custom_property(&declaration.name) {
+            continue;
+        }
 
-    class_names.split_whitespace().for_each(|class_name| {
-        if let Some(class_styles) = stylesheet.classes.iter()
-            .find(|class| class.name == class_name) {
-            
-            class_styles.properties.iter().for_each(|property| {
-                dispatch_by_key_and_update_style(&mut styles, &property.name, &property.value);
-            });
+        // A declaration referencing an undefined custom property without fallback behaves as unset
+        let result = match custom_properties::substitute_variables(&declaration.value, &mut |name| custom_properties.get(name).cloned()) {
+            Ok(value) => match cascade::parse_css_wide_keyword(&value) {
+                Some(keyword) => cascade::apply_css_wide_keyword(&mut styles, &declaration.name, keyword, parent_styles),
+                None => dispatch_by_key_and_update_style(&mut styles, &declaration.name, &value),
+            },
+            Err(er) => cascade::apply_css_wide_keyword(&mut styles, &declaration.name, CssWideKeyword::Unset, parent_styles)
+                .and(Err(format!("Invalid value of property '{}': {}", declaration.name, er))),
+        };
+
+        match (result, cascaded_declaration.origin) {
+            (Ok(()), _) => {},
+            (Err(warning), DeclarationOrigin::Inline) => warnings.push(warning),
+            (Err(warning), _) => warnings.push(format!("{} (stylesheet rule at line {})", warning, declaration.location.line)),
         }
-    });
+    }
 
     styles
 }
 
-fn parse_inline_styles(style_str: &str) -> Styles {
-    let mut styles = Styles::default();
+/*
+ * Computes the custom properties of the last element of the path, for the elements passing the styles
+ * of their parent through (e.g. body), whose other properties are not computed.
+ */
+pub fn parse_custom_properties(element_path: &[Rc<StyledElement>], stylesheet: &Option<Arc<Stylesheet>>, warnings: &mut Vec<String>) {
+    let inline_declarations = parse_inline_declarations(element_path, warnings);
+    let cascaded_declarations = cascade::cascade_declarations(element_path, stylesheet.as_deref(), &inline_declarations);
+    update_custom_properties(element_path, &cascaded_declarations, warnings);
+}
 
-    // Split the style string by semicolons to get "key: value" pairs
-    style_str.split(';').filter_map(|item| {
-        let parts: Vec<&str> = item.splitn(2, ':').map(str::trim).collect();
-        if parts.len() == 2 {
-            Some((parts[0], parts[1]))
-        } else {
-            None
-        }
-    }).for_each(|(key, value)| {
-        dispatch_by_key_and_update_style(&mut styles, key, value);
-    });
+fn parse_inline_declarations(element_path: &[Rc<StyledElement>], warnings: &mut Vec<String>) -> Vec<Declaration> {
+    let mut syntax_errors = Vec::new();
+    let inline_declarations = element_path.last()
+        .and_then(|element| element.get_attribute("style"))
+        .map(|style_attr| stylesheet_parser::parse_declarations(style_attr, &mut syntax_errors))
+        .unwrap_or_default();
+    warnings.extend(syntax_errors.into_iter().map(|syntax_error| syntax_error.message));
+    inline_declarations
+}
 
-    styles
+// Keeps the custom properties on the element, for its descendants to inherit them
+fn update_custom_properties(
+    element_path: &[Rc<StyledElement>], 
+    cascaded_declarations: &[CascadedDeclaration], 
+    warnings: &mut Vec<String>,
+) -> Rc<CustomProperties> {
+    let custom_properties = custom_properties::compute_custom_properties(element_path, cascaded_declarations, warnings);
+    if let Some(element) = element_path.last() {
+        element.set_custom_properties(custom_properties.clone());
+    }
+    custom_properties
 }
 
 static LAYOUT_PROPERTIES: [&str; 11] = ["display", "flex-direction", "flex-wrap", "justify-content", "align-items", "flex-grow", "flex-shrink", "margin", "padding", "spacing", "overflow"];
@@ -71,17 +91,17 @@ static DIMENSION_PROPERTIES: [&str; 6] = ["width", "height", "min-width", "max-w
 static APPEARANCE_PROPERTIES: [&str; 5] = ["background-color", "color", "border-width", "border-color", "border-radius"];
 static TEXT_PROPERTIES: [&str; 6] = ["white-space", "font-size", "font-weight", "font-family", "font-style", "text-align"];
 
-fn dispatch_by_key_and_update_style(styles: &mut Styles, key: &str, value: &str) {
+fn dispatch_by_key_and_update_style(styles: &mut Styles, key: &str, value: &str) -> Result<(), String> {
     if LAYOUT_PROPERTIES.contains(&key) {
-        update_layout_style(styles, key, value);
+        update_layout_style(styles, key, value)
     } else if DIMENSION_PROPERTIES.contains(&key) {
-        update_dimension_style(styles, key, value);
+        update_dimension_style(styles, key, value)
     } else if APPEARANCE_PROPERTIES.contains(&key) {
-        update_appearance_style(styles, key, value);
+        update_appearance_style(styles, key, value)
     } else if TEXT_PROPERTIES.contains(&key) {
-        update_text_style(styles, key, value);
+        update_text_style(styles, key, value)
     } else {
-        println!("Unknown style key: {}", key);
+        Err(format!("Unknown style property '{}'", key))
     }
 }
 
@@ -0,0 +1,193 @@
+use crate::parsing::diagnostics::{Diagnostic, SourceLocation};
+
+
+/*
+ * Tokens of a stylesheet. Words cover identifiers, numbers and dimensions (e.g. flex, 10px, 50%, --primary-color),
+ * and the other characters with no meaning to the parser are kept as single character delimiters (e.g. . # ! > *).
+ * The text of the tokens is kept as written, so that values can be serialized back from their tokens.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Word,
+    AtKeyword,
+    String,
+    Whitespace,
+    Colon,
+    Semicolon,
+    Comma,
+    LeftBrace,
+    RightBrace,
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Delim,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub location: SourceLocation,
+}
+
+impl Token {
+    pub fn is_delim(&self, delim: char) -> bool {
+        self.kind == TokenKind::Delim && self.text.starts_with(delim)
+    }
+}
+
+/*
+ * Splits a stylesheet into tokens, dropping the comments.
+ * Unterminated comments and strings are reported in the diagnostics, and closed at the end of the input and line respectively.
+ */
+pub fn tokenize(source: &str, diagnostics: &mut Vec<Diagnostic>) -> Vec<Token> {
+    let mut tokenizer = Tokenizer {
+        characters: source.chars().collect(),
+        position: 0,
+        location: SourceLocation { line: 1, column: 1 },
+    };
+    let mut tokens = Vec::new();
+
+    while let Some(character) = tokenizer.peek(0) {
+        let location = tokenizer.location;
+
+        let kind = match character {
+            '/' if tokenizer.peek(1) == Some('*') => {
+                if !tokenizer.skip_comment() {
+                    diagnostics.push(Diagnostic::error("Unterminated comment".to_string()).at(None, Some(location)));
+                }
+                continue;
+            },
+            '"' | '\'' => {
+                let (text, is_terminated) = tokenizer.consume_string(character);
+                if !is_terminated {
+                    diagnostics.push(Diagnostic::error("Unterminated string".to_string()).at(None, Some(location)));
+                }
+                tokens.push(Token { kind: TokenKind::String, text, location });
+                continue;
+            },
+            '@' if tokenizer.peek(1).is_some_and(is_word_character) => {
+                tokenizer.advance();
+                let name = tokenizer.consume_while(is_word_character);
+                tokens.push(Token { kind: TokenKind::AtKeyword, text: format!("@{}", name), location });
+                continue;
+            },
+            character if character.is_whitespace() => {
+                tokenizer.consume_while(char::is_whitespace);
+                tokens.push(Token { kind: TokenKind::Whitespace, text: " ".to_string(), location });
+                continue;
+            },
+            character if is_word_character(character) => {
+                let text = tokenizer.consume_while(is_word_character);
+                tokens.push(Token { kind: TokenKind::Word, text, location });
+                continue;
+            },
+            ':' => TokenKind::Colon,
+            ';' => TokenKind::Semicolon,
+            ',' => TokenKind::Comma,
+            '{' => TokenKind::LeftBrace,
+            '}' => TokenKind::RightBrace,
+            '(' => TokenKind::LeftParen,
+            ')' => TokenKind::RightParen,
+            '[' => TokenKind::LeftBracket,
+            ']' => TokenKind::RightBracket,
+            _ => TokenKind::Delim,
+        };
+
+        tokenizer.advance();
+        tokens.push(Token { kind, text: character.to_string(), location });
+    }
+
+    tokens
+}
+
+// Escaped characters (e.g. .sm\:flex) are kept in the word, with their backslash
+fn is_word_character(character: char) -> bool {
+    character.is_alphanumeric() || matches!(character, '-' | '_' | '%' | '\\') || !character.is_ascii()
+}
+
+struct Tokenizer {
+    characters: Vec<char>,
+    position: usize,
+    location: SourceLocation,
+}
+
+impl Tokenizer {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.characters.get(self.position + offset).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let character = self.peek(0)?;
+        self.position += 1;
+
+        if character == '\n' {
+            self.location = SourceLocation { line: self.location.line + 1, column: 1 };
+        } else {
+            self.location.column += 1;
+        }
+        Some(character)
+    }
+
+    fn consume_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut text = String::new();
+        while let Some(character) = self.peek(0).filter(|character| predicate(*character)) {
+            // An escape takes the following character along, e.g. the colon of .sm\:flex
+            if character == '\\' {
+                text.push(character);
+                self.advance();
+                match self.advance() {
+                    Some(escaped_character) => text.push(escaped_character),
+                    None => break,
+                }
+                continue;
+            }
+
+            text.push(character);
+            self.advance();
+        }
+        text
+    }
+
+    // Returns whether the comment is terminated
+    fn skip_comment(&mut self) -> bool {
+        self.advance();
+        self.advance();
+
+        while let Some(character) = self.advance() {
+            if character == '*' && self.peek(0) == Some('/') {
+                self.advance();
+                return true;
+            }
+        }
+        false
+    }
+
+    // Returns the string with its quotes, and whether it is terminated before the end of the line
+    fn consume_string(&mut self, quote: char) -> (String, bool) {
+        let mut text = String::from(quote);
+        self.advance();
+
+        while let Some(character) = self.peek(0) {
+            match character {
+                '\n' => return (text, false),
+                '\\' => {
+                    text.push(character);
+                    self.advance();
+                    if let Some(escaped_character) = self.advance() {
+                        text.push(escaped_character);
+                    }
+                },
+                character => {
+                    text.push(character);
+                    self.advance();
+                    if character == quote {
+                        return (text, true);
+                    }
+                },
+            }
+        }
+        (text, false)
+    }
+}
@@ -0,0 +1,154 @@
+use std::{collections::HashMap, rc::Rc};
+
+use super::{cascade::{self, CascadedDeclaration, CssWideKeyword}, selector_matcher::StyledElement};
+
+
+/*
+ * Custom properties of an element, e.g. --primary-color, by name, with their var() references substituted.
+ * As in CSS, all custom properties are inherited, and the map is shared with the parent when the element declares none.
+ */
+pub type CustomProperties = HashMap<String, String>;
+
+pub fn is_custom_property(name: &str) -> bool {
+    name.starts_with("--")
+}
+
+/*
+ * Custom properties of the parent of the last element of the path. The root of the template of a component
+ * inherits the ones of the component's tag, while the root of the application template inherits none.
+ */
+pub fn inherited_custom_properties(element_path: &[Rc<StyledElement>]) -> Rc<CustomProperties> {
+    match element_path.len() {
+        0 => Rc::default(),
+        1 => element_path[0].host.as_ref()
+            .map(|host| host.custom_properties.borrow().clone())
+            .unwrap_or_default(),
+        length => element_path[length - 2].custom_properties.borrow().clone(),
+    }
+}
+
+/*
+ * Computes the custom properties of the last element of the path from the winning declarations of the cascade,
+ * over the ones inherited from its parent. A custom property referencing an undefined or cyclic one is invalid,
+ * and is left out as if it was not declared, which is described in the returned warnings.
+ */
+pub fn compute_custom_properties(
+    element_path: &[Rc<StyledElement>],
+    cascaded_declarations: &[CascadedDeclaration],
+    warnings: &mut Vec<String>,
+) -> Rc<CustomProperties> {
+    let inherited = inherited_custom_properties(element_path);
+
+    let declared: HashMap<&str, &str> = cascaded_declarations.iter()
+        .filter(|cascaded_declaration| is_custom_property(&cascaded_declaration.declaration.name))
+        .map(|cascaded_declaration| (cascaded_declaration.declaration.name.as_str(), cascaded_declaration.declaration.value.as_str()))
+        .collect();
+    if declared.is_empty() {
+        return inherited;
+    }
+
+    let mut resolver = CustomPropertyResolver { inherited: &inherited, declared: &declared, resolved: HashMap::new(), resolving: Vec::new(), warnings };
+    let mut custom_properties = (*inherited).clone();
+    for name in declared.keys() {
+        match resolver.resolve(name) {
+            Some(value) => custom_properties.insert(name.to_string(), value),
+            None => custom_properties.remove(*name),
+        };
+    }
+    Rc::new(custom_properties)
+}
+
+struct CustomPropertyResolver<'a> {
+    inherited: &'a CustomProperties,
+    declared: &'a HashMap<&'a str, &'a str>,
+    resolved: HashMap<String, Option<String>>,
+    resolving: Vec<String>, // Custom properties whose value is being substituted, to detect cycles
+    warnings: &'a mut Vec<String>,
+}
+
+impl CustomPropertyResolver<'_> {
+    // Value of a custom property for the element, None if it is undefined or invalid
+    fn resolve(&mut self, name: &str) -> Option<String> {
+        if let Some(value) = self.resolved.get(name) {
+            return value.clone();
+        }
+        let declared_value = match self.declared.get(name) {
+            Some(declared_value) => *declared_value,
+            None => return self.inherited.get(name).cloned(),
+        };
+        if self.resolving.iter().any(|resolving_name| resolving_name == name) {
+            self.warnings.push(format!("Custom property '{}' references itself", name));
+            return None;
+        }
+
+        let value = match cascade::parse_css_wide_keyword(declared_value) {
+            Some(CssWideKeyword::Initial) => None,
+            Some(CssWideKeyword::Inherit | CssWideKeyword::Unset) => self.inherited.get(name).cloned(),
+            None => {
+                self.resolving.push(name.to_string());
+                let value = substitute_variables(declared_value, &mut |referenced_name| self.resolve(referenced_name));
+                self.resolving.pop();
+
+                value.map_err(|er| self.warnings.push(format!("Invalid value of custom property '{}': {}", name, er))).ok()
+            },
+        };
+        self.resolved.insert(name.to_string(), value.clone());
+        value
+    }
+}
+
+/*
+ * Replaces the var(--name) and var(--name, fallback) functions of a value with the value of the custom properties,
+ * the fallback being used when the custom property is undefined. Fails if neither is available.
+ */
+pub fn substitute_variables(value: &str, lookup: &mut dyn FnMut(&str) -> Option<String>) -> Result<String, String> {
+    let mut substituted_value = String::new();
+    let mut remaining = value;
+
+    while let Some(function_start) = find_var_function(remaining) {
+        substituted_value.push_str(&remaining[..function_start]);
+
+        let arguments_start = function_start + "var(".len();
+        let arguments_end = find_closing_parenthesis(remaining, arguments_start)
+            .ok_or_else(|| "Unclosed var() function".to_string())?;
+        let arguments = &remaining[arguments_start..arguments_end];
+
+        let (name, fallback) = match arguments.split_once(',') {
+            Some((name, fallback)) => (name.trim(), Some(fallback.trim())),
+            None => (arguments.trim(), None),
+        };
+        if !is_custom_property(name) {
+            return Err(format!("Invalid custom property name '{}' in var()", name));
+        }
+
+        match (lookup(name), fallback) {
+            (Some(custom_property_value), _) => substituted_value.push_str(&custom_property_value),
+            (None, Some(fallback)) => substituted_value.push_str(&substitute_variables(fallback, lookup)?),
+            (None, None) => return Err(format!("Undefined custom property '{}'", name)),
+        }
+        remaining = &remaining[arguments_end + 1..];
+    }
+
+    substituted_value.push_str(remaining);
+    Ok(substituted_value)
+}
+
+// Start of the first var( function, not being the end of another function name
+fn find_var_function(value: &str) -> Option<usize> {
+    value.match_indices("var(")
+        .map(|(index, _)| index)
+        .find(|index| !value[..*index].ends_with(|character: char| character.is_alphanumeric() || character == '-' || character == '_'))
+}
+
+fn find_closing_parenthesis(value: &str, start: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (index, character) in value[start..].char_indices() {
+        match character {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(start + index),
+            ')' => depth -= 1,
+            _ => {},
+        }
+    }
+    None
+}
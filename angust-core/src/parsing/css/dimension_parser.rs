@@ -1,21 +1,37 @@
-use crate::rendering::elements::styles::{Dimension, SizingPolicy, Styles, Unit};
+use crate::rendering::elements::styles::{CalcSum, Dimension, Length, SizingPolicy, Styles, Unit, ROOT_FONT_SIZE};
 
+use super::calc_parser;
 
-pub fn update_dimension_style(styles: &mut Styles, key: &str, value: &str) {
-    let dimension = parse_dimension(value);
+
+pub fn update_dimension_style(styles: &mut Styles, key: &str, value: &str) -> Result<(), String> {
+    let length = parse_length(value);
     if styles.sizing_policy.is_none() {
         styles.sizing_policy = Some(SizingPolicy::default());
     }
     let sizing_policy = styles.sizing_policy.as_mut().unwrap();
 
     match key {
-        "width" => sizing_policy.width = dimension,
-        "height" => sizing_policy.height = dimension,
-        "min-width" => sizing_policy.min_width = dimension,
-        "max-width" => sizing_policy.max_width = dimension,
-        "min-height" => sizing_policy.min_height = dimension,
-        "max-height" => sizing_policy.max_height = dimension,
-        _ => {}
+        "width" => sizing_policy.width = length,
+        "height" => sizing_policy.height = length,
+        "min-width" => sizing_policy.min_width = length,
+        "max-width" => sizing_policy.max_width = length,
+        "min-height" => sizing_policy.min_height = length,
+        "max-height" => sizing_policy.max_height = length,
+        _ => return Err(format!("Unsupported dimension property '{}'", key)),
+    }
+
+    Ok(())
+}
+
+/*
+ * Value of a sizing property, a calc() expression with a single unit being kept as a plain dimension,
+ * e.g. calc(100% / 3) as 33.33%.
+ */
+pub fn parse_length(value: &str) -> Option<Length> {
+    let value = value.trim();
+    match is_calc(value) {
+        true => calc_parser::parse_calc(value).ok().map(length_from_calc_sum),
+        false => parse_dimension(value).map(Length::Dimension),
     }
 }
 
@@ -32,6 +48,10 @@ pub fn parse_dimension(value: &str) -> Option<Dimension> {
     }
 }
 
+fn is_calc(value: &str) -> bool {
+    value.get(..5).is_some_and(|function_start| function_start.eq_ignore_ascii_case("calc("))
+}
+
 fn parse_unit(value: &str) -> Option<Unit> {
     match value {
         "px" => Some(Unit::Px),
@@ -42,3 +62,67 @@ fn parse_unit(value: &str) -> Option<Unit> {
         _ => None,
     }
 }
+
+fn length_from_calc_sum(sum: CalcSum) -> Length {
+    let terms = [(sum.px, Unit::Px), (sum.vh, Unit::Vh), (sum.vw, Unit::Vw), (sum.rem, Unit::Rem), (sum.percent, Unit::Percent)];
+    let mut non_zero_terms = terms.iter().filter(|(value, _)| *value != 0.0);
+
+    match (non_zero_terms.next(), non_zero_terms.next()) {
+        (None, _) => Length::Dimension(Dimension { value: 0.0, unit: Unit::Px }),
+        (Some((value, unit)), None) => Length::Dimension(Dimension { value: *value, unit: *unit }),
+        (Some(_), Some(_)) => Length::Calc(sum),
+    }
+}
+
+/*
+ * Dimension of the properties read as pixels before layout (margins, paddings, spacings, borders and font sizes),
+ * whose rems and calc() expressions are resolved here. Percentages and viewport units cannot be resolved
+ * before layout, so the values using them are rejected rather than read as pixels.
+ */
+pub fn parse_fixed_dimension(value: &str) -> Result<Dimension, String> {
+    let value = value.trim();
+    let sum = match is_calc(value) {
+        true => calc_parser::parse_calc(value).map_err(|er| format!("Invalid dimension '{}': {}", value, er))?,
+        false => match parse_dimension(value) {
+            Some(Dimension { value, unit: Unit::Px }) => CalcSum { px: value, ..CalcSum::default() },
+            Some(Dimension { value, unit: Unit::Rem }) => CalcSum { rem: value, ..CalcSum::default() },
+            Some(_) => return Err(unsupported_units_error(value)),
+            None => return Err(format!("Invalid dimension '{}'", value)),
+        },
+    };
+
+    match sum.percent == 0.0 && sum.vh == 0.0 && sum.vw == 0.0 {
+        true => Ok(Dimension { value: sum.px + sum.rem * ROOT_FONT_SIZE, unit: Unit::Px }),
+        false => Err(unsupported_units_error(value)),
+    }
+}
+
+fn unsupported_units_error(value: &str) -> String {
+    format!("Percentages and viewport units are not supported in '{}', only px and rem", value)
+}
+
+// Splits a value into its space-separated components, keeping the functions whole, e.g. calc(100% - 8px) 4px
+pub fn split_components(value: &str) -> Vec<&str> {
+    let mut components = Vec::new();
+    let mut depth = 0;
+    let mut component_start = None;
+
+    for (index, character) in value.char_indices() {
+        match character {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            character if character.is_whitespace() && depth <= 0 => {
+                if let Some(start) = component_start.take() {
+                    components.push(&value[start..index]);
+                }
+                continue;
+            },
+            _ => {},
+        }
+        component_start.get_or_insert(index);
+    }
+    if let Some(start) = component_start {
+        components.push(&value[start..]);
+    }
+    components
+}
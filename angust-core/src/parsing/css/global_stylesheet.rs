@@ -0,0 +1,21 @@
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+
+use super::stylesheet_parser::Stylesheet;
+
+
+/*
+ * Stylesheet of the application (styles.css and the stylesheets it imports), set at startup
+ * and applied to index.html as well as to the templates of the components.
+ */
+static GLOBAL_STYLESHEET: Lazy<RwLock<Option<Arc<Stylesheet>>>> = Lazy::new(|| RwLock::new(None));
+
+pub fn set_global_stylesheet(stylesheet: Stylesheet) {
+    let mut global_stylesheet = GLOBAL_STYLESHEET.write().unwrap();
+    *global_stylesheet = Some(Arc::new(stylesheet));
+}
+
+pub fn get_global_stylesheet() -> Option<Arc<Stylesheet>> {
+    GLOBAL_STYLESHEET.read().unwrap().clone()
+}
@@ -1,9 +1,9 @@
 use crate::rendering::elements::styles::{AlignContent, AlignItems, Dimension, DisplayType, FlexDirection, FlexWrap, JustifyContent, Margin, Overflow, Padding, Spacing, Styles};
 
-use super::dimension_parser::parse_dimension;
+use super::dimension_parser::{parse_fixed_dimension, split_components};
 
 
-pub fn update_layout_style(styles: &mut Styles, key: &str, value: &str) {
+pub fn update_layout_style(styles: &mut Styles, key: &str, value: &str) -> Result<(), String> {
     match key {
         "display" => styles.display = parse_display(value),
         "flex-direction" => styles.flex_direction = parse_flex_direction(value),
@@ -14,11 +14,13 @@ pub fn update_layout_style(styles: &mut Styles, key: &str, value: &str) {
         "overflow" => styles.overflow = parse_overflow(value),
         "flex-grow" => styles.flex_grow = value.parse().ok(),
         "flex-shrink" => styles.flex_shrink = value.parse().ok(),
-        "margin" => styles.margin = parse_margin(value),
-        "padding" => styles.padding = parse_padding(value),
-        "spacing" => styles.spacing = parse_spacing(value),
-        _ => println!("Unhandled layout property: {}", key),
+        "margin" => styles.margin = Some(parse_margin(value)?),
+        "padding" => styles.padding = Some(parse_padding(value)?),
+        "spacing" => styles.spacing = Some(parse_spacing(value)?),
+        _ => return Err(format!("Unsupported layout property '{}'", key)),
     }
+
+    Ok(())
 }
 
 fn parse_display(value: &str) -> Option<DisplayType> {
@@ -91,13 +93,13 @@ fn parse_overflow(value: &str) -> Option<Overflow> {
     }
 }
 
-fn parse_margin(value: &str) -> Option<Margin> {
-    let parts: Vec<&str> = value.split_whitespace().collect();
+fn parse_margin(value: &str) -> Result<Margin, String> {
+    let parts: Vec<&str> = split_components(value);
     let dimensions = parts.iter().map(
-        |value| parse_dimension(value)
-    ).collect::<Option<Vec<Dimension>>>()?;
+        |value| parse_fixed_dimension(value)
+    ).collect::<Result<Vec<Dimension>, String>>()?;
 
-    Some(Margin {
+    Ok(Margin {
         top: dimensions.get(0).cloned().unwrap_or_default(),
         right: dimensions.get(1).cloned().unwrap_or_else(|| dimensions.get(0).cloned().unwrap_or_default()),
         bottom: dimensions.get(2).cloned().unwrap_or_else(|| dimensions.get(0).cloned().unwrap_or_default()),
@@ -105,7 +107,7 @@ fn parse_margin(value: &str) -> Option<Margin> {
     })
 }
 
-fn parse_padding(value: &str) -> Option<Padding> {
+fn parse_padding(value: &str) -> Result<Padding, String> {
     parse_margin(value).map(|margin| Padding {
         top: margin.top,
         right: margin.right,
@@ -114,13 +116,13 @@ fn parse_padding(value: &str) -> Option<Padding> {
     })
 }
 
-fn parse_spacing(value: &str) -> Option<Spacing> {
-    let parts: Vec<&str> = value.split_whitespace().collect();
+fn parse_spacing(value: &str) -> Result<Spacing, String> {
+    let parts: Vec<&str> = split_components(value);
     if parts.len() == 2 {
-        let x_dim = parse_dimension(parts[0])?;
-        let y_dim = parse_dimension(parts[1])?;
-        Some(Spacing { spacing_x: x_dim, spacing_y: y_dim })
+        let x_dim = parse_fixed_dimension(parts[0])?;
+        let y_dim = parse_fixed_dimension(parts[1])?;
+        Ok(Spacing { spacing_x: x_dim, spacing_y: y_dim })
     } else {
-        None
+        Err(format!("Expected two dimensions, found '{}'", value))
     }
 }
\ No newline at end of file
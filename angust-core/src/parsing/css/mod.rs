@@ -1,8 +1,16 @@
 pub mod css_parser;
 
 pub mod stylesheet_parser;
+pub mod css_tokenizer;
+pub mod selector_parser;
+pub mod selector_matcher;
+pub mod global_stylesheet;
+pub mod cascade;
+pub mod style_context;
+pub mod custom_properties;
 
 mod layout_parser;
 mod dimension_parser;
+mod calc_parser;
 mod appearance_parser;
 mod text_parser;
\ No newline at end of file
@@ -0,0 +1,201 @@
+use std::{cell::{Cell, RefCell}, rc::Rc};
+
+use kuchiki::Attributes;
+
+use crate::rendering::elements::{interaction_state::InteractionState, styles::Styles};
+
+use super::{cascade::CandidateRules, custom_properties::CustomProperties, selector_parser::{AttributeOperator, Combinator, CompoundSelector, PseudoClass, Selector, SimpleSelector}};
+
+
+/*
+ * Element as seen by the selectors: its tag name and attributes, with the property bindings applied.
+ * The elements being parsed are kept from the root of the template to the current element, see ParsingContext::element_path.
+ * Paths are shared by the rendered elements, which keep the interaction state and computed styles of their
+ * styled element up to date, so that their descendants can be restyled against them, see StyleContext.
+ */
+#[derive(Debug, Clone)]
+pub struct StyledElement {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub interaction_state: Cell<InteractionState>,
+    pub styles: Cell<Option<Styles>>, // None for the elements passing the styles of their parent through (e.g. body)
+    pub custom_properties: RefCell<Rc<CustomProperties>>, // Computed for every element, so that they are inherited through body
+    pub host: Option<Rc<StyledElement>>, // For the root of a component's template, the tag whose custom properties it inherits
+    pub candidate_rules: Option<Rc<CandidateRules>>, // None to match all the rules, e.g. for the tag of a component
+    // Numbers of changes since the element was parsed, for the style contexts to detect them without comparing values
+    pub interaction_state_changes: Cell<u64>,
+    pub style_changes: Cell<u64>, // Of the styles or custom properties
+}
+
+impl StyledElement {
+    pub fn new(name: &str, attributes: &Attributes) -> Self {
+        StyledElement {
+            name: name.to_ascii_lowercase(),
+            attributes: attributes.map.iter()
+                .map(|(name, attribute)| (name.local.to_string(), attribute.value.clone()))
+                .collect(),
+            interaction_state: Cell::new(InteractionState::default()),
+            styles: Cell::new(None),
+            custom_properties: RefCell::default(),
+            host: None,
+            candidate_rules: None,
+            interaction_state_changes: Cell::new(0),
+            style_changes: Cell::new(0),
+        }
+    }
+
+    // Returns whether the state changed
+    pub fn set_interaction_state(&self, interaction_state: InteractionState) -> bool {
+        if self.interaction_state.get() == interaction_state {
+            return false;
+        }
+        self.interaction_state.set(interaction_state);
+        self.interaction_state_changes.set(self.interaction_state_changes.get() + 1);
+        true
+    }
+
+    pub fn set_styles(&self, styles: Styles) {
+        if self.styles.get() != Some(styles) {
+            self.styles.set(Some(styles));
+            self.style_changes.set(self.style_changes.get() + 1);
+        }
+    }
+
+    // Returns whether the custom properties changed
+    pub fn set_custom_properties(&self, custom_properties: Rc<CustomProperties>) -> bool {
+        let mut current_custom_properties = self.custom_properties.borrow_mut();
+        if *current_custom_properties == custom_properties {
+            return false;
+        }
+        *current_custom_properties = custom_properties;
+        self.style_changes.set(self.style_changes.get() + 1);
+        true
+    }
+
+    pub fn get_attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(attribute_name, _)| attribute_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn has_class(&self, class_name: &str) -> bool {
+        self.get_attribute("class").is_some_and(|class_names| class_names.split_whitespace().any(|name| name == class_name))
+    }
+
+    // A [disabled] binding evaluating to false leaves the attribute formatted as "false"
+    pub fn is_disabled(&self) -> bool {
+        self.get_attribute("disabled").is_some_and(|value| value != "false")
+    }
+
+    // Buttons and the elements with a tabindex get focused when pressed
+    pub fn is_focusable(&self) -> bool {
+        self.name == "button" || self.get_attribute("tabindex").is_some()
+    }
+}
+
+/*
+ * Whether the selector matches the last element of the path, the other elements being its ancestors.
+ * The compounds are matched from right to left, backtracking over the ancestors for descendant combinators.
+ */
+pub fn matches_selector(selector: &Selector, element_path: &[Rc<StyledElement>]) -> bool {
+    matches_path(selector, element_path, false)
+}
+
+// Whether the selector matches the last element of the path for some interaction state of the elements, see StyleContext
+pub fn matches_selector_in_any_state(selector: &Selector, element_path: &[Rc<StyledElement>]) -> bool {
+    matches_path(selector, element_path, true)
+}
+
+/*
+ * Whether the subject of the selector may match the element whatever its ancestors, its interaction state
+ * and the values of its bound attributes, which are unknown until its template is rendered, see CandidateRules.
+ */
+pub fn may_match_selector(selector: &Selector, element: &StyledElement, bound_attributes: &[&str]) -> bool {
+    let subject = match selector.compounds.last() {
+        Some(subject) => subject,
+        None => return false,
+    };
+
+    subject.simple_selectors.iter().all(|simple_selector| {
+        let selected_attribute = match simple_selector {
+            SimpleSelector::Id(_) => Some("id"),
+            SimpleSelector::Class(_) => Some("class"),
+            SimpleSelector::Attribute { name, .. } => Some(name.as_str()),
+            SimpleSelector::PseudoClass(PseudoClass::Disabled) => Some("disabled"),
+            _ => None,
+        };
+        selected_attribute.is_some_and(|attribute| bound_attributes.contains(&attribute))
+            || matches_simple_selector(simple_selector, element, true, true)
+    })
+}
+
+fn matches_path(selector: &Selector, element_path: &[Rc<StyledElement>], is_any_state: bool) -> bool {
+    match (selector.compounds.last(), element_path.split_last()) {
+        (Some(subject), Some((element, ancestors))) => {
+            matches_compound_selector(subject, element, ancestors.is_empty(), is_any_state)
+                && matches_ancestors(selector, selector.compounds.len() - 1, ancestors, is_any_state)
+        },
+        _ => false,
+    }
+}
+
+// Whether the compounds left of the matched one at compound_index match the ancestors
+fn matches_ancestors(selector: &Selector, compound_index: usize, ancestors: &[Rc<StyledElement>], is_any_state: bool) -> bool {
+    if compound_index == 0 {
+        return true;
+    }
+    let compound = &selector.compounds[compound_index - 1];
+
+    match selector.combinators[compound_index - 1] {
+        Combinator::Child => match ancestors.split_last() {
+            Some((parent, ancestors)) => {
+                matches_compound_selector(compound, parent, ancestors.is_empty(), is_any_state) && matches_ancestors(selector, compound_index - 1, ancestors, is_any_state)
+            },
+            None => false,
+        },
+        Combinator::Descendant => (0..ancestors.len()).rev().any(|ancestor_index| {
+            matches_compound_selector(compound, &ancestors[ancestor_index], ancestor_index == 0, is_any_state)
+                && matches_ancestors(selector, compound_index - 1, &ancestors[..ancestor_index], is_any_state)
+        }),
+    }
+}
+
+// The element is the root of the path when it has no ancestors, see PseudoClass::Root
+fn matches_compound_selector(compound: &CompoundSelector, element: &StyledElement, is_root: bool, is_any_state: bool) -> bool {
+    compound.simple_selectors.iter().all(|simple_selector| matches_simple_selector(simple_selector, element, is_root, is_any_state))
+}
+
+fn matches_simple_selector(simple_selector: &SimpleSelector, element: &StyledElement, is_root: bool, is_any_state: bool) -> bool {
+    match simple_selector {
+        SimpleSelector::Universal => true,
+        SimpleSelector::Type(name) => element.name == *name,
+        SimpleSelector::Id(id) => element.get_attribute("id") == Some(id.as_str()),
+        SimpleSelector::Class(class_name) => element.has_class(class_name),
+        SimpleSelector::Attribute { name, matcher } => match (element.get_attribute(name), matcher) {
+            (Some(_), None) => true,
+            (Some(value), Some((operator, expected_value))) => matches_attribute_value(value, *operator, expected_value),
+            (None, _) => false,
+        },
+        SimpleSelector::PseudoClass(pseudo_class) => {
+            let interaction_state = element.interaction_state.get();
+            match pseudo_class {
+                PseudoClass::Hover => is_any_state || interaction_state.is_hovered,
+                PseudoClass::Active => is_any_state || interaction_state.is_active,
+                PseudoClass::Focus => is_any_state || interaction_state.is_focused,
+                PseudoClass::Disabled => element.is_disabled(),
+                PseudoClass::Root => is_root,
+            }
+        },
+    }
+}
+
+fn matches_attribute_value(value: &str, operator: AttributeOperator, expected_value: &str) -> bool {
+    match operator {
+        AttributeOperator::Equals => value == expected_value,
+        AttributeOperator::Includes => value.split_whitespace().any(|word| word == expected_value),
+        AttributeOperator::DashMatch => value == expected_value || value.starts_with(&format!("{}-", expected_value)),
+        AttributeOperator::Prefix => !expected_value.is_empty() && value.starts_with(expected_value),
+        AttributeOperator::Suffix => !expected_value.is_empty() && value.ends_with(expected_value),
+        AttributeOperator::Substring => !expected_value.is_empty() && value.contains(expected_value),
+    }
+}
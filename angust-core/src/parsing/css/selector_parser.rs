@@ -0,0 +1,292 @@
+use super::css_tokenizer::{Token, TokenKind};
+
+
+/*
+ * Selector of a style rule, e.g. .toolbar > button.primary, made of compound selectors linked by combinators,
+ * from left to right: combinators[i] links compounds[i] to compounds[i + 1]. The last compound is the subject of the selector.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    pub compounds: Vec<CompoundSelector>,
+    pub combinators: Vec<Combinator>,
+}
+
+impl Selector {
+    pub fn specificity(&self) -> Specificity {
+        let mut specificity = Specificity::default();
+
+        for simple_selector in self.compounds.iter().flat_map(|compound| compound.simple_selectors.iter()) {
+            match simple_selector {
+                SimpleSelector::Universal => {},
+                SimpleSelector::Type(_) => specificity.types += 1,
+                SimpleSelector::Id(_) => specificity.ids += 1,
+                SimpleSelector::Class(_) | SimpleSelector::Attribute { .. } | SimpleSelector::PseudoClass(_) => specificity.classes += 1,
+            }
+        }
+        specificity
+    }
+
+    // Whether the selector has pseudo-classes other than :root, which does not depend on the state of the elements
+    pub fn has_interaction_pseudo_classes(&self) -> bool {
+        self.compounds.iter()
+            .flat_map(|compound| compound.simple_selectors.iter())
+            .any(|simple_selector| matches!(simple_selector, SimpleSelector::PseudoClass(pseudo_class) if *pseudo_class != PseudoClass::Root))
+    }
+}
+
+/*
+ * Specificity of a selector, compared by id count, then by class, attribute and pseudo-class count, then by type count.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Specificity {
+    pub ids: u32,
+    pub classes: u32,
+    pub types: u32,
+}
+
+// Simple selectors applying to the same element, e.g. button.primary[type="submit"]:hover
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundSelector {
+    pub simple_selectors: Vec<SimpleSelector>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimpleSelector {
+    Universal,
+    Type(String),
+    Id(String),
+    Class(String),
+    Attribute { name: String, matcher: Option<(AttributeOperator, String)> },
+    PseudoClass(PseudoClass),
+}
+
+/*
+ * Operators of attribute selectors: [name=value], [name~=value] (whitespace separated word), [name|=value] (value or value-),
+ * [name^=value] (prefix), [name$=value] (suffix) and [name*=value] (substring).
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttributeOperator {
+    Equals,
+    Includes,
+    DashMatch,
+    Prefix,
+    Suffix,
+    Substring,
+}
+
+/*
+ * Pseudo-classes matching the interaction state of the element (see InteractionState), or its position in the template.
+ * Templates are styled apart from each other, so :root matches the root element of each template, e.g. for the custom properties.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PseudoClass {
+    Hover,
+    Active,
+    Focus,
+    Disabled,
+    Root,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combinator {
+    Descendant,
+    Child,
+}
+
+/*
+ * Parses the tokens of a single selector of a selector list.
+ */
+pub fn parse_selector(tokens: &[Token]) -> Result<Selector, String> {
+    let mut parser = SelectorParser { tokens, position: 0 };
+    parser.skip_whitespace();
+
+    let mut compounds = vec![parser.parse_compound_selector()?];
+    let mut combinators = Vec::new();
+
+    loop {
+        let has_whitespace = parser.skip_whitespace();
+
+        let combinator = match parser.peek() {
+            None => break,
+            Some(token) if token.is_delim('>') => {
+                parser.position += 1;
+                parser.skip_whitespace();
+                Combinator::Child
+            },
+            Some(token) if token.is_delim('+') || token.is_delim('~') => {
+                return Err(format!("Unsupported combinator '{}'", token.text));
+            },
+            Some(_) if has_whitespace => Combinator::Descendant,
+            Some(token) => return Err(format!("Unexpected '{}'", token.text)),
+        };
+
+        combinators.push(combinator);
+        compounds.push(parser.parse_compound_selector()?);
+    }
+
+    Ok(Selector { compounds, combinators })
+}
+
+struct SelectorParser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl<'a> SelectorParser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    // Returns whether whitespace was skipped
+    fn skip_whitespace(&mut self) -> bool {
+        let start_position = self.position;
+        while self.peek().is_some_and(|token| token.kind == TokenKind::Whitespace) {
+            self.position += 1;
+        }
+        self.position > start_position
+    }
+
+    fn expect_word(&mut self, context: &str) -> Result<String, String> {
+        match self.next() {
+            Some(token) if token.kind == TokenKind::Word => Ok(unescape(&token.text)),
+            Some(token) => Err(format!("Expected {}, found '{}'", context, token.text)),
+            None => Err(format!("Expected {}", context)),
+        }
+    }
+
+    fn parse_compound_selector(&mut self) -> Result<CompoundSelector, String> {
+        let mut simple_selectors = Vec::new();
+
+        // The type or universal selector comes first
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Word => {
+                self.position += 1;
+                simple_selectors.push(SimpleSelector::Type(token.text.to_ascii_lowercase()));
+            },
+            Some(token) if token.is_delim('*') => {
+                self.position += 1;
+                simple_selectors.push(SimpleSelector::Universal);
+            },
+            _ => {},
+        }
+
+        while let Some(token) = self.peek() {
+            let simple_selector = match token.kind {
+                TokenKind::Delim if token.is_delim('.') => {
+                    self.position += 1;
+                    SimpleSelector::Class(self.expect_word("a class name")?)
+                },
+                TokenKind::Delim if token.is_delim('#') => {
+                    self.position += 1;
+                    SimpleSelector::Id(self.expect_word("an id")?)
+                },
+                TokenKind::LeftBracket => {
+                    self.position += 1;
+                    self.parse_attribute_selector()?
+                },
+                TokenKind::Colon => {
+                    self.position += 1;
+                    SimpleSelector::PseudoClass(self.parse_pseudo_class()?)
+                },
+                TokenKind::Word => return Err(format!("Unexpected '{}', the type selector must come first", token.text)),
+                _ => break,
+            };
+            simple_selectors.push(simple_selector);
+        }
+
+        if simple_selectors.is_empty() {
+            return match self.peek() {
+                Some(token) => Err(format!("Unexpected '{}'", token.text)),
+                None => Err("Expected a selector".to_string()),
+            };
+        }
+        Ok(CompoundSelector { simple_selectors })
+    }
+
+    // Pseudo-class after its colon, e.g. hover
+    fn parse_pseudo_class(&mut self) -> Result<PseudoClass, String> {
+        if self.peek().is_some_and(|token| token.kind == TokenKind::Colon) {
+            return Err("Pseudo-elements are not supported".to_string());
+        }
+
+        let name = self.expect_word("a pseudo-class name")?;
+        match name.to_ascii_lowercase().as_str() {
+            "hover" => Ok(PseudoClass::Hover),
+            "active" => Ok(PseudoClass::Active),
+            "focus" => Ok(PseudoClass::Focus),
+            "disabled" => Ok(PseudoClass::Disabled),
+            "root" => Ok(PseudoClass::Root),
+            _ if self.peek().is_some_and(|token| token.kind == TokenKind::LeftParen) => Err(format!("Unsupported pseudo-class ':{}()'", name)),
+            _ => Err(format!("Unsupported pseudo-class ':{}'", name)),
+        }
+    }
+
+    // Attribute selector after its opening bracket, e.g. type="submit"]
+    fn parse_attribute_selector(&mut self) -> Result<SimpleSelector, String> {
+        self.skip_whitespace();
+        let name = self.expect_word("an attribute name")?.to_ascii_lowercase();
+        self.skip_whitespace();
+
+        let operator = match self.next() {
+            Some(token) if token.kind == TokenKind::RightBracket => return Ok(SimpleSelector::Attribute { name, matcher: None }),
+            Some(token) if token.is_delim('=') => AttributeOperator::Equals,
+            Some(token) if token.kind == TokenKind::Delim => {
+                let operator = match token.text.as_str() {
+                    "~" => AttributeOperator::Includes,
+                    "|" => AttributeOperator::DashMatch,
+                    "^" => AttributeOperator::Prefix,
+                    "$" => AttributeOperator::Suffix,
+                    "*" => AttributeOperator::Substring,
+                    _ => return Err(format!("Unknown attribute operator '{}'", token.text)),
+                };
+                match self.next() {
+                    Some(token) if token.is_delim('=') => operator,
+                    _ => return Err(format!("Expected '=' after '{}' in attribute selector", token.text)),
+                }
+            },
+            Some(token) => return Err(format!("Unexpected '{}' in attribute selector", token.text)),
+            None => return Err("Unclosed attribute selector".to_string()),
+        };
+
+        self.skip_whitespace();
+        let value = match self.next() {
+            Some(token) if token.kind == TokenKind::Word => unescape(&token.text),
+            Some(token) if token.kind == TokenKind::String => unquote(&token.text),
+            _ => return Err(format!("Expected a value for attribute '{}'", name)),
+        };
+
+        self.skip_whitespace();
+        match self.next() {
+            Some(token) if token.kind == TokenKind::RightBracket => {},
+            _ => return Err("Unclosed attribute selector".to_string()),
+        }
+
+        Ok(SimpleSelector::Attribute { name, matcher: Some((operator, value)) })
+    }
+}
+
+// Removes the backslashes of the escaped characters, e.g. sm\:flex
+fn unescape(text: &str) -> String {
+    let mut unescaped_text = String::new();
+    let mut characters = text.chars();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' => unescaped_text.extend(characters.next()),
+            character => unescaped_text.push(character),
+        }
+    }
+    unescaped_text
+}
+
+fn unquote(text: &str) -> String {
+    let quote = text.chars().next().unwrap_or('"');
+    let content = text.strip_prefix(quote).unwrap_or(text);
+    unescape(content.strip_suffix(quote).unwrap_or(content))
+}
@@ -0,0 +1,203 @@
+use std::{cell::Cell, rc::Rc, sync::Arc};
+
+use crate::rendering::elements::{element::EventType, interaction_state::InteractionState, styles::{Border, Styles}};
+
+use super::{cascade, css_parser, custom_properties::CustomProperties, selector_matcher::StyledElement, stylesheet_parser::Stylesheet};
+
+
+thread_local! {
+    // Whether the interaction state of an element or the custom properties of a component's tag changed since the last restyle
+    static HAS_PENDING_RESTYLE: Cell<bool> = const { Cell::new(false) };
+}
+
+/*
+ * What the styles of a rendered element were computed from: its path in the template, shared with the elements
+ * of the path, and the stylesheet. It lets the element be restyled when the interaction state of the elements of its path,
+ * the styles or the custom properties of its ancestors change, e.g. when the button containing it gets hovered.
+ */
+pub struct StyleContext {
+    element_path: Vec<Rc<StyledElement>>,
+    stylesheet: Option<Arc<Stylesheet>>,
+    is_text: bool, // Text nodes are not part of the path, and inherit the styles of its last element
+    depends_on_interaction_state: bool,
+    computed_from: StyleInputs,
+}
+
+// Numbers of changes of the inputs of the computed styles, see StyledElement::style_changes
+#[derive(Clone, Copy, PartialEq)]
+struct StyleInputs {
+    interaction_state_changes: u64, // Of the elements of the path, 0 for the elements whose styles do not depend on them
+    ancestor_style_changes: u64, // Including the tag of the component the template belongs to
+}
+
+// How restyling elements affects their rendering, from the least to the most
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RestyleImpact {
+    #[default]
+    None,
+    Repaint, // Only colors or border radii changed
+    Layout,
+}
+
+impl RestyleImpact {
+    pub fn between(previous_styles: &Styles, styles: &Styles) -> Self {
+        if previous_styles == styles {
+            return RestyleImpact::None;
+        }
+
+        let repainted_styles = Styles {
+            background_color: styles.background_color,
+            text_color: styles.text_color,
+            border: match (previous_styles.border, styles.border) {
+                (Some(previous_border), Some(border)) => Some(Border { color: border.color, radius: border.radius, ..previous_border }),
+                (previous_border, _) => previous_border,
+            },
+            ..*previous_styles
+        };
+        match repainted_styles == *styles {
+            true => RestyleImpact::Repaint,
+            false => RestyleImpact::Layout,
+        }
+    }
+}
+
+impl StyleContext {
+    pub fn new(element_path: &[Rc<StyledElement>], stylesheet: &Option<Arc<Stylesheet>>) -> Self {
+        let depends_on_interaction_state = cascade::depends_on_interaction_state(element_path, stylesheet.as_deref());
+        Self::create(element_path, stylesheet, false, depends_on_interaction_state)
+    }
+
+    // Context of a text node, whose path ends with its parent element
+    pub fn for_text(element_path: &[Rc<StyledElement>], stylesheet: &Option<Arc<Stylesheet>>) -> Self {
+        Self::create(element_path, stylesheet, true, false)
+    }
+
+    fn create(element_path: &[Rc<StyledElement>], stylesheet: &Option<Arc<Stylesheet>>, is_text: bool, depends_on_interaction_state: bool) -> Self {
+        let mut style_context = StyleContext {
+            element_path: element_path.to_vec(),
+            stylesheet: stylesheet.clone(),
+            is_text,
+            depends_on_interaction_state,
+            computed_from: StyleInputs { interaction_state_changes: 0, ancestor_style_changes: 0 },
+        };
+        style_context.computed_from = style_context.current_inputs();
+        style_context
+    }
+
+    // Element the context belongs to, None for text nodes
+    fn element(&self) -> Option<&StyledElement> {
+        match self.is_text {
+            true => None,
+            false => self.element_path.last().map(Rc::as_ref),
+        }
+    }
+
+    pub fn custom_properties(&self) -> Option<Rc<CustomProperties>> {
+        self.element().map(|element| element.custom_properties.borrow().clone())
+    }
+
+    pub fn interaction_state(&self) -> InteractionState {
+        self.element().map(|element| element.interaction_state.get()).unwrap_or_default()
+    }
+
+    fn set_interaction_state(&self, interaction_state: InteractionState) {
+        if self.element().is_some_and(|element| element.set_interaction_state(interaction_state)) {
+            HAS_PENDING_RESTYLE.set(true);
+        }
+    }
+
+    /*
+     * Updates the interaction state of the element after a mouse event.
+     * Besides buttons and the elements with a tabindex, the elements listening to keyboard events are focusable.
+     */
+    pub fn update_interaction_state(&self, is_within_bounds: bool, event_type: &EventType, has_keyboard_handlers: bool) {
+        if let Some(element) = self.element() {
+            let is_focusable = has_keyboard_handlers || element.is_focusable();
+            let interaction_state = element.interaction_state.get()
+                .after_event(is_within_bounds, event_type, is_focusable, element.is_disabled());
+            self.set_interaction_state(interaction_state);
+        }
+    }
+
+    /*
+     * Recomputes the styles if their inputs changed since they were last computed, returning the new styles.
+     * The elements are restyled from the root to the leaves, so that the styles of the parent are up to date.
+     */
+    pub fn restyle(&mut self) -> Option<Styles> {
+        if self.current_inputs() == self.computed_from {
+            return None;
+        }
+        self.update_pass_through_custom_properties();
+
+        // The parent is the closest ancestor with computed styles, as when the template is parsed
+        let parent_styles = self.ancestors().iter().rev().find_map(|ancestor| ancestor.styles.get());
+        let styles = match self.element() {
+            Some(element) => {
+                // The invalid declarations were reported when the template was parsed
+                let styles = css_parser::parse_styles(&self.element_path, parent_styles.as_ref(), &self.stylesheet, &mut Vec::new());
+                element.set_styles(styles);
+                styles
+            },
+            None => {
+                let mut styles = Styles::default();
+                if let Some(parent_styles) = &parent_styles {
+                    css_parser::merge_styles(parent_styles, &mut styles);
+                }
+                styles
+            },
+        };
+
+        self.computed_from = self.current_inputs();
+        Some(styles)
+    }
+
+    // The ancestors passing the styles of their parent through (e.g. body) have no context updating their custom properties
+    fn update_pass_through_custom_properties(&self) {
+        for (index, ancestor) in self.ancestors().iter().enumerate() {
+            if ancestor.styles.get().is_none() {
+                css_parser::parse_custom_properties(&self.element_path[..=index], &self.stylesheet, &mut Vec::new());
+            }
+        }
+    }
+
+    fn ancestors(&self) -> &[Rc<StyledElement>] {
+        match self.is_text {
+            true => &self.element_path[..],
+            false => &self.element_path[..self.element_path.len().saturating_sub(1)],
+        }
+    }
+
+    fn current_inputs(&self) -> StyleInputs {
+        let interaction_state_changes = match self.depends_on_interaction_state {
+            true => self.element_path.iter().map(|element| element.interaction_state_changes.get()).sum(),
+            false => 0,
+        };
+        let host_style_changes = self.element_path.first()
+            .and_then(|root| root.host.as_ref())
+            .map_or(0, |host| host.style_changes.get());
+        let ancestor_style_changes = self.ancestors().iter().map(|ancestor| ancestor.style_changes.get()).sum::<u64>() + host_style_changes;
+
+        StyleInputs { interaction_state_changes, ancestor_style_changes }
+    }
+}
+
+pub fn request_restyle() {
+    HAS_PENDING_RESTYLE.set(true);
+}
+
+// Returns whether the elements need to be restyled since the last call, resetting it
+pub fn take_pending_restyle() -> bool {
+    HAS_PENDING_RESTYLE.replace(false)
+}
+
+/*
+ * Replaces the context of an element rendered again from its template, keeping its interaction state,
+ * which leaves the new context to be restyled if the state is not the default one.
+ */
+pub fn replace_style_context(style_context: &mut Option<StyleContext>, new_style_context: Option<StyleContext>) {
+    if let (Some(style_context), Some(new_style_context)) = (style_context.as_ref(), new_style_context.as_ref()) {
+        let is_disabled = new_style_context.element().is_some_and(StyledElement::is_disabled);
+        new_style_context.set_interaction_state(style_context.interaction_state().adjusted_to_disabled(is_disabled));
+    }
+    *style_context = new_style_context;
+}
@@ -1,69 +1,365 @@
+use crate::parsing::diagnostics::{Diagnostic, SourceLocation};
 
-#[derive(Debug, Clone)]
+use super::{css_tokenizer::{self, Token, TokenKind}, selector_parser::{self, Selector}};
+
+
+/*
+ * Parsed stylesheet: its style rules and at-rules, in source order.
+ */
+#[derive(Debug, Clone, Default)]
 pub struct Stylesheet {
-    pub classes: Vec<StyleClass>,
+    pub rules: Vec<StyleRule>,
+    pub at_rules: Vec<AtRule>,
 }
 
-impl Default for Stylesheet {
-    fn default() -> Self {
-        Stylesheet {
-            classes: Vec::new(),
-        }
+impl Stylesheet {
+    // Appends the rules of a stylesheet loaded after this one, e.g. the stylesheets imported by styles.css
+    pub fn extend(&mut self, stylesheet: Stylesheet) {
+        self.rules.extend(stylesheet.rules);
+        self.at_rules.extend(stylesheet.at_rules);
     }
 }
 
+/*
+ * Rule such as .title, .toolbar > button { color: red; }. It applies to the elements matched by any of its selectors.
+ */
 #[derive(Debug, Clone)]
-pub struct StyleClass {
+pub struct StyleRule {
+    pub selectors: Vec<Selector>,
+    pub declarations: Vec<Declaration>,
+    pub location: SourceLocation,
+}
+
+/*
+ * Declaration such as color: red !important. Property names are lowercased, except custom properties (--name).
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
     pub name: String,
-    pub properties: Vec<Property>,
+    pub value: String,
+    pub is_important: bool,
+    pub location: SourceLocation,
 }
 
+/*
+ * At-rule such as @import url("theme.css"); or @media (max-width: 600px) { ... }, with its name lowercased, without the @.
+ */
 #[derive(Debug, Clone)]
-pub struct Property {
+pub struct AtRule {
     pub name: String,
-    pub value: String,
+    pub prelude: String,
+    pub block: Option<AtRuleBlock>,
+    pub location: SourceLocation,
+}
+
+#[derive(Debug, Clone)]
+pub enum AtRuleBlock {
+    Rules(Stylesheet),
+    Declarations(Vec<Declaration>),
+}
+
+// At-rules whose block holds style rules, the block of the other ones holds declarations (e.g. @font-face)
+static RULE_LIST_AT_RULES: [&str; 5] = ["media", "supports", "layer", "container", "document"];
+
+/*
+ * Parses a stylesheet into its rules. Syntax errors are reported in the diagnostics, located in the stylesheet,
+ * and the parser recovers at the end of the faulty declaration or rule, as browsers do.
+ */
+pub fn parse_stylesheet(stylesheet: &str, stylesheet_path: Option<&str>, diagnostics: &mut Vec<Diagnostic>) -> Stylesheet {
+    let mut syntax_errors = Vec::new();
+    let tokens = css_tokenizer::tokenize(stylesheet, &mut syntax_errors);
+
+    let mut parser = StylesheetParser { tokens, position: 0, syntax_errors };
+    let parsed_stylesheet = parser.parse_rule_list(false);
+
+    diagnostics.extend(parser.syntax_errors.into_iter().map(|syntax_error| {
+        let location = syntax_error.location;
+        syntax_error.at(stylesheet_path.map(str::to_string), location)
+    }));
+    parsed_stylesheet
+}
+
+/*
+ * Parses the declarations of a style attribute, e.g. "width: 100px; color: red".
+ */
+pub fn parse_declarations(declarations: &str, diagnostics: &mut Vec<Diagnostic>) -> Vec<Declaration> {
+    let mut syntax_errors = Vec::new();
+    let tokens = css_tokenizer::tokenize(declarations, &mut syntax_errors);
+
+    let mut parser = StylesheetParser { tokens, position: 0, syntax_errors };
+    let parsed_declarations = parser.parse_declaration_list(false);
+
+    diagnostics.extend(parser.syntax_errors);
+    parsed_declarations
+}
+
+struct StylesheetParser {
+    tokens: Vec<Token>,
+    position: usize,
+    syntax_errors: Vec<Diagnostic>,
 }
 
-pub fn parse_stylesheet(stylesheet: &String) -> Stylesheet {
-    let mut classes = Vec::new();
-    let mut current_class = None;
+impl StylesheetParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek_kind(&self) -> Option<TokenKind> {
+        self.peek().map(|token| token.kind)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek_kind() == Some(TokenKind::Whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn current_location(&self) -> SourceLocation {
+        self.peek()
+            .or_else(|| self.tokens.last())
+            .map_or(SourceLocation { line: 1, column: 1 }, |token| token.location)
+    }
+
+    fn add_error(&mut self, message: String, location: SourceLocation) {
+        self.syntax_errors.push(Diagnostic::error(message).at(None, Some(location)));
+    }
+
+    // Rules of the stylesheet, or of the block of an at-rule up to its closing brace
+    fn parse_rule_list(&mut self, is_nested: bool) -> Stylesheet {
+        let mut stylesheet = Stylesheet::default();
+
+        loop {
+            self.skip_whitespace();
+
+            let token = match self.peek() {
+                Some(token) => token.clone(),
+                None => {
+                    if is_nested {
+                        self.add_error("Unclosed block, expected '}'".to_string(), self.current_location());
+                    }
+                    return stylesheet;
+                },
+            };
+
+            match token.kind {
+                TokenKind::RightBrace if is_nested => {
+                    self.position += 1;
+                    return stylesheet;
+                },
+                TokenKind::RightBrace => {
+                    self.add_error("Unexpected '}'".to_string(), token.location);
+                    self.position += 1;
+                },
+                TokenKind::AtKeyword => stylesheet.at_rules.extend(self.parse_at_rule()),
+                _ => stylesheet.rules.extend(self.parse_style_rule()),
+            }
+        }
+    }
+
+    fn parse_style_rule(&mut self) -> Option<StyleRule> {
+        let location = self.current_location();
+        let (prelude, terminator) = self.consume_until(&[TokenKind::LeftBrace, TokenKind::Semicolon]);
+
+        if terminator != Some(TokenKind::LeftBrace) {
+            self.add_error(format!("Expected '{{' after selector '{}'", serialize_tokens(&prelude)), location);
+            return None;
+        }
+
+        let declarations = self.parse_declaration_list(true);
+
+        // A rule with an invalid selector is dropped as a whole, as browsers do
+        let selectors = split_top_level(&prelude, TokenKind::Comma).iter()
+            .map(|selector_tokens| {
+                selector_parser::parse_selector(selector_tokens)
+                    .map_err(|er| format!("Invalid selector '{}': {}", serialize_tokens(selector_tokens), er))
+            })
+            .collect::<Result<Vec<Selector>, String>>();
+
+        match selectors {
+            Ok(selectors) => Some(StyleRule { selectors, declarations, location }),
+            Err(er) => {
+                self.add_error(er, location);
+                None
+            },
+        }
+    }
+
+    fn parse_at_rule(&mut self) -> Option<AtRule> {
+        let at_keyword = self.next()?;
+        let name = at_keyword.text.trim_start_matches('@').to_ascii_lowercase();
+        let (prelude, terminator) = self.consume_until(&[TokenKind::LeftBrace, TokenKind::Semicolon]);
+
+        let block = match terminator {
+            Some(TokenKind::Semicolon) => None,
+            Some(_) if RULE_LIST_AT_RULES.contains(&name.as_str()) => Some(AtRuleBlock::Rules(self.parse_rule_list(true))),
+            Some(_) => Some(AtRuleBlock::Declarations(self.parse_declaration_list(true))),
+            None => {
+                self.add_error(format!("Expected ';' or '{{' after @{}", name), at_keyword.location);
+                return None;
+            },
+        };
+
+        Some(AtRule { name, prelude: serialize_tokens(&prelude), block, location: at_keyword.location })
+    }
+
+    // Declarations of a style attribute, or of a block up to its closing brace
+    fn parse_declaration_list(&mut self, is_nested: bool) -> Vec<Declaration> {
+        let mut declarations = Vec::new();
 
-    for line in stylesheet.lines() {
-        let line = line.trim();
-        if line.starts_with(".") {
-            if let Some(class) = current_class.take() {
-                classes.push(class);
+        loop {
+            self.skip_whitespace();
+
+            match self.peek_kind() {
+                Some(TokenKind::Semicolon) => self.position += 1,
+                Some(TokenKind::RightBrace) if is_nested => {
+                    self.position += 1;
+                    return declarations;
+                },
+                Some(_) => declarations.extend(self.parse_declaration()),
+                None => {
+                    if is_nested {
+                        self.add_error("Unclosed block, expected '}'".to_string(), self.current_location());
+                    }
+                    return declarations;
+                },
             }
+        }
+    }
+
+    fn parse_declaration(&mut self) -> Option<Declaration> {
+        let location = self.current_location();
+        let name_token = self.next()?;
+
+        if name_token.kind != TokenKind::Word {
+            self.add_error(format!("Expected a property name, found '{}'", name_token.text), location);
+            self.consume_declaration_value();
+            return None;
+        }
+
+        self.skip_whitespace();
+        if self.peek_kind() != Some(TokenKind::Colon) {
+            self.add_error(format!("Expected ':' after property '{}'", name_token.text), location);
+            self.consume_declaration_value();
+            return None;
+        }
+        self.position += 1;
+
+        let mut value_tokens = self.consume_declaration_value();
+        let is_important = strip_important(&mut value_tokens);
+        let value = serialize_tokens(&value_tokens);
+
+        if value.is_empty() {
+            self.add_error(format!("Missing value of property '{}'", name_token.text), location);
+            return None;
+        }
 
-            // Remove leading left brace
-            let class_name = line[1..]
-                .split('{')
-                .next()
-                .unwrap_or("")
-                .trim();
-
-            current_class = Some(StyleClass {
-                name: class_name.to_string(),
-                properties: Vec::new(),
-            });
-        } else if let Some(class) = &mut current_class {
-            let parts: Vec<&str> = line.split(':').map(str::trim).collect(); 
-
-            if parts.len() == 2 {
-                class.properties.push(Property {
-                    name: parts[0].trim().to_string(),
-                    value: parts[1].split(';').next().unwrap_or("").trim().to_string(), // Remove trailing semicolon
-                });
+        let name = if name_token.text.starts_with("--") {
+            name_token.text
+        } else {
+            name_token.text.to_ascii_lowercase()
+        };
+        Some(Declaration { name, value, is_important, location })
+    }
+
+    // Tokens up to the end of the declaration, leaving the closing brace of the block to the declaration list
+    fn consume_declaration_value(&mut self) -> Vec<Token> {
+        let mut value_tokens = Vec::new();
+        let mut depth: usize = 0;
+
+        while let Some(token) = self.peek() {
+            match token.kind {
+                TokenKind::Semicolon if depth == 0 => {
+                    self.position += 1;
+                    break;
+                },
+                TokenKind::RightBrace if depth == 0 => break,
+                TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightParen | TokenKind::RightBracket | TokenKind::RightBrace => depth = depth.saturating_sub(1),
+                _ => {},
             }
+            value_tokens.push(token.clone());
+            self.position += 1;
         }
+
+        value_tokens
     }
 
-    if let Some(class) = current_class {
-        classes.push(class);
+    /*
+     * Consumes the tokens up to one of the terminators outside of parentheses and brackets,
+     * returning them and the terminator found, which is consumed as well.
+     */
+    fn consume_until(&mut self, terminators: &[TokenKind]) -> (Vec<Token>, Option<TokenKind>) {
+        let mut tokens = Vec::new();
+        let mut depth: usize = 0;
+
+        while let Some(token) = self.next() {
+            match token.kind {
+                kind if depth == 0 && terminators.contains(&kind) => return (tokens, Some(kind)),
+                TokenKind::LeftParen | TokenKind::LeftBracket => depth += 1,
+                TokenKind::RightParen | TokenKind::RightBracket => depth = depth.saturating_sub(1),
+                _ => {},
+            }
+            tokens.push(token);
+        }
+
+        (tokens, None)
     }
+}
 
-    Stylesheet {
-        classes,
+// Removes a trailing !important from the tokens of a value, returning whether it was there
+fn strip_important(value_tokens: &mut Vec<Token>) -> bool {
+    let significant_tokens: Vec<usize> = value_tokens.iter()
+        .enumerate()
+        .filter(|(_, token)| token.kind != TokenKind::Whitespace)
+        .map(|(index, _)| index)
+        .collect();
+
+    match significant_tokens.as_slice() {
+        [.., bang_index, important_index]
+            if value_tokens[*bang_index].is_delim('!') && value_tokens[*important_index].text.eq_ignore_ascii_case("important") => {
+            value_tokens.truncate(*bang_index);
+            true
+        },
+        _ => false,
+    }
+}
+
+fn split_top_level(tokens: &[Token], separator: TokenKind) -> Vec<Vec<Token>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth: usize = 0;
+
+    for token in tokens {
+        match token.kind {
+            kind if kind == separator && depth == 0 => {
+                parts.push(Vec::new());
+                continue;
+            },
+            TokenKind::LeftParen | TokenKind::LeftBracket => depth += 1,
+            TokenKind::RightParen | TokenKind::RightBracket => depth = depth.saturating_sub(1),
+            _ => {},
+        }
+        parts.last_mut().unwrap().push(token.clone());
+    }
+
+    parts
+}
+
+// Text of the tokens as written, with the whitespace collapsed and trimmed
+pub fn serialize_tokens(tokens: &[Token]) -> String {
+    let mut text = String::new();
+
+    for token in tokens {
+        if token.kind == TokenKind::Whitespace && (text.is_empty() || text.ends_with(' ')) {
+            continue;
+        }
+        text.push_str(&token.text);
     }
 
+    text.trim_end().to_string()
 }
@@ -1,17 +1,19 @@
 use crate::rendering::elements::styles::{FontFamily, FontStyle, FontWeight, Styles, WhiteSpace};
 
-use super::dimension_parser::parse_dimension;
+use super::dimension_parser::parse_fixed_dimension;
 
-pub fn update_text_style(styles: &mut Styles, key: &str, value: &str) {
+pub fn update_text_style(styles: &mut Styles, key: &str, value: &str) -> Result<(), String> {
     match key {
         "white-space" => styles.white_space = parse_white_space(value),
-        "font-size" => styles.font_size = parse_dimension(value),
+        "font-size" => styles.font_size = Some(parse_fixed_dimension(value)?),
         "font-weight" => styles.font_weight = parse_font_weight(value),
         "font-family" => styles.font_family = parse_font_family(value),
         "font-style" => styles.font_style = parse_font_style(value),
         // "text-align" => styles.text_align = parse_text_align(value),
-        _ => println!("Unhandled text property: {}", key),
+        _ => return Err(format!("Unsupported text property '{}'", key)),
     }
+
+    Ok(())
 }
 
 fn parse_white_space(value: &str) -> Option<WhiteSpace> {
@@ -0,0 +1,115 @@
+use std::{fmt::{Display, Formatter}, sync::{Arc, RwLock}};
+
+use once_cell::sync::Lazy;
+
+
+/*
+ * Problem found while parsing a template or its styles, e.g. an invalid @if expression or an unknown style property.
+ * Parsing continues after a diagnostic, skipping or falling back on the faulty part of the template.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub template_path: Option<String>,
+    pub location: Option<SourceLocation>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+// 1-based position in the source of a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Diagnostic {
+    pub fn error(message: String) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: String) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn new(severity: Severity, message: String) -> Self {
+        Diagnostic { template_path: None, location: None, severity, message }
+    }
+
+    pub fn at(mut self, template_path: Option<String>, location: Option<SourceLocation>) -> Self {
+        self.template_path = template_path;
+        self.location = location;
+        self
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/*
+ * Pretty form printed by the stderr sink:
+ * warning: Unknown style property 'colour'
+ *   --> src/app/app_component.html:12:9
+ */
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+
+        match (&self.template_path, self.location) {
+            (Some(path), Some(location)) => write!(f, "\n  --> {}:{}:{}", path, location.line, location.column),
+            (Some(path), None) => write!(f, "\n  --> {}", path),
+            (None, Some(location)) => write!(f, "\n  --> {}:{}", location.line, location.column),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/*
+ * Destination of the diagnostics, set by the client application with set_diagnostic_sink:
+ * - Stderr (default): pretty printed to stderr
+ * - Strict: panics on errors, printing warnings to stderr
+ * - Callback: passed to the given function, e.g. to collect them in tests or forward them to a logger
+ */
+pub type DiagnosticCallback = Arc<dyn Fn(&Diagnostic) + Send + Sync>;
+
+#[derive(Clone)]
+pub enum DiagnosticSink {
+    Stderr,
+    Strict,
+    Callback(DiagnosticCallback),
+}
+
+static DIAGNOSTIC_SINK: Lazy<RwLock<DiagnosticSink>> = Lazy::new(|| RwLock::new(DiagnosticSink::Stderr));
+
+pub fn set_diagnostic_sink(sink: DiagnosticSink) {
+    let mut current_sink = DIAGNOSTIC_SINK.write().unwrap();
+    *current_sink = sink;
+}
+
+pub fn report(diagnostic: Diagnostic) {
+    // Cloned so that the lock is released before a callback that may report or set the sink itself
+    let sink = DIAGNOSTIC_SINK.read().unwrap().clone();
+    match sink {
+        DiagnosticSink::Stderr => eprintln!("{}", diagnostic),
+        DiagnosticSink::Strict => match diagnostic.severity {
+            Severity::Error => panic!("{}", diagnostic),
+            Severity::Warning => eprintln!("{}", diagnostic),
+        },
+        DiagnosticSink::Callback(callback) => callback(&diagnostic),
+    }
+}
+
+pub fn report_all(diagnostics: Vec<Diagnostic>) {
+    diagnostics.into_iter().for_each(report);
+}
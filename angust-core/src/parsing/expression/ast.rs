@@ -1,9 +1,13 @@
-use super::expression_parser::{parse_expression, Rule};
+use angust_syntax::expression_parser::{parse_expression, Rule};
 use pest::iterators::Pair;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ASTNode {
     Number(f64),
+    Integer(i64),
+    String(String),
+    Bool(bool),
+    Null,
     Identifier(String),
     FunctionCall(String, Vec<ASTNode>),
     BinaryOperation {
@@ -21,6 +25,28 @@ pub enum ASTNode {
         left: Box<ASTNode>,
         right: Box<ASTNode>,
     },
+    UnaryOperation {
+        operator: Operator,
+        operand: Box<ASTNode>,
+    },
+    Conditional {
+        condition: Box<ASTNode>,
+        then_branch: Box<ASTNode>,
+        else_branch: Box<ASTNode>,
+    },
+    MemberAccess {
+        object: Box<ASTNode>,
+        member: String,
+    },
+    IndexAccess {
+        object: Box<ASTNode>,
+        index: Box<ASTNode>,
+    },
+    Pipe {
+        name: String,
+        input: Box<ASTNode>,
+        args: Vec<ASTNode>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -28,6 +54,7 @@ pub enum Operator {
     Add, Subtract, Multiply, Divide,    // Arithmetic
     Equal, NotEqual, Less, Greater, LessEqual, GreaterEqual,  // Comparison
     And, Or,                            // Logical
+    Not, Negate,                        // Unary
 }
 
 fn str_to_operator(op_str: &str) -> Operator {
@@ -61,16 +88,35 @@ fn parse_pair_to_ast(pair: Pair<Rule>) -> ASTNode {
             parse_expression_content(pair)
         },
         Rule::number => {
-            ASTNode::Number(pair.as_str().parse::<f64>().unwrap())
+            match pair.as_str().parse::<i64>() {
+                Ok(integer) => ASTNode::Integer(integer),
+                Err(_) => ASTNode::Number(pair.as_str().parse::<f64>().unwrap()),
+            }
         },
+        Rule::string => {
+            let quoted = pair.as_str();
+            ASTNode::String(unescape_string(&quoted[1..quoted.len() - 1]))
+        },
+        Rule::boolean => {
+            ASTNode::Bool(pair.as_str() == "true")
+        },
+        Rule::null => ASTNode::Null,
         Rule::identifier => {
             ASTNode::Identifier(pair.as_str().to_string())
         },
+        Rule::pipe_expression =>
+            parse_pipe_expression(pair),
         Rule::function_call => 
             parse_function_call(pair),
-        Rule::logical_expression | Rule::comparison_expression | Rule::additive_expression | Rule::multiplicative_expression => 
+        Rule::conditional_expression =>
+            parse_conditional_expression(pair),
+        Rule::unary_expression =>
+            parse_unary_expression(pair),
+        Rule::postfix_expression =>
+            parse_postfix_expression(pair),
+        Rule::or_expression | Rule::and_expression | Rule::comparison_expression | Rule::additive_expression | Rule::multiplicative_expression => 
             parse_operation(pair),
-        Rule::primary => {
+        Rule::primary | Rule::pipe_argument => {
             let inner = pair.into_inner().next().unwrap();
             parse_pair_to_ast(inner)
         },
@@ -87,6 +133,83 @@ fn parse_expression_content(pair: Pair<Rule>) -> ASTNode {
     }
 }
 
+fn parse_conditional_expression(pair: Pair<Rule>) -> ASTNode {
+    let mut inner_pairs = pair.into_inner();
+    let condition = parse_pair_to_ast(inner_pairs.next().unwrap());
+
+    match (inner_pairs.next(), inner_pairs.next()) {
+        (Some(then_pair), Some(else_pair)) => ASTNode::Conditional {
+            condition: Box::new(condition),
+            then_branch: Box::new(parse_pair_to_ast(then_pair)),
+            else_branch: Box::new(parse_pair_to_ast(else_pair)),
+        },
+        _ => condition,
+    }
+}
+
+// Unary operators apply from the innermost one, e.g. !-x is !(-x)
+fn parse_unary_expression(pair: Pair<Rule>) -> ASTNode {
+    let mut operators = Vec::new();
+    let mut operand = None;
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::unary_operator => operators.push(if inner_pair.as_str() == "!" { Operator::Not } else { Operator::Negate }),
+            _ => operand = Some(parse_pair_to_ast(inner_pair)),
+        }
+    }
+
+    operators.into_iter().rev().fold(operand.unwrap(), |operand, operator| ASTNode::UnaryOperation {
+        operator,
+        operand: Box::new(operand),
+    })
+}
+
+fn parse_postfix_expression(pair: Pair<Rule>) -> ASTNode {
+    let mut inner_pairs = pair.into_inner();
+    let mut current = parse_pair_to_ast(inner_pairs.next().unwrap());
+
+    for accessor_pair in inner_pairs {
+        let rule = accessor_pair.as_rule();
+        let accessor = accessor_pair.into_inner().next().unwrap();
+
+        current = match rule {
+            Rule::member_access => ASTNode::MemberAccess {
+                object: Box::new(current),
+                member: accessor.as_str().to_string(),
+            },
+            _ => ASTNode::IndexAccess {
+                object: Box::new(current),
+                index: Box::new(parse_pair_to_ast(accessor)),
+            },
+        };
+    }
+
+    current
+}
+
+// Supports the escapes \n, \t, \r, and \ followed by any other character standing for itself (e.g. \' or \\)
+fn unescape_string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut characters = text.chars();
+
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            result.push(character);
+            continue;
+        }
+        match characters.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some(escaped_character) => result.push(escaped_character),
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
 fn parse_function_call(
     pair: Pair<Rule>,
 ) -> ASTNode {
@@ -106,6 +229,25 @@ fn parse_function_call(
     ASTNode::FunctionCall(function_name, args)
 }
 
+fn parse_pipe_expression(pair: Pair<Rule>) -> ASTNode {
+    let mut inner_pairs = pair.into_inner();
+    let mut current = parse_pair_to_ast(inner_pairs.next().unwrap());
+
+    for pipe_pair in inner_pairs {
+        let mut pipe_inner_pairs = pipe_pair.into_inner();
+        let name = pipe_inner_pairs.next().unwrap().as_str().to_string();
+        let args = pipe_inner_pairs.map(parse_pair_to_ast).collect();
+
+        current = ASTNode::Pipe {
+            name,
+            input: Box::new(current),
+            args,
+        };
+    }
+
+    current
+}
+
 fn parse_operation(pair: Pair<Rule>) -> ASTNode {
     let mut inner_pairs = pair.clone().into_inner();
     let left = parse_pair_to_ast(inner_pairs.next().unwrap());
@@ -126,7 +268,7 @@ fn parse_operation(pair: Pair<Rule>) -> ASTNode {
                         right: Box::new(right),
                     }
                 },
-                Rule::logical_expression => {
+                Rule::or_expression | Rule::and_expression => {
                     ASTNode::LogicalOperation {
                         operator,
                         left: Box::new(current),
@@ -1,30 +1,48 @@
-use std::any::Any;
-
 use crate::rendering::elements::component::{component_state::ComponentState, functions::component_functions::ComponentFunctions};
 
-use super::ast::{ASTNode, Operator};
+use super::{ast::{ASTNode, Operator}, evaluation_scope::EvaluationScope, pipes::pipe_registry, value::{FromValue, Value}, value_formatter};
 
 pub fn evaluate_ast<State: ComponentState>(
     node: &ASTNode,
     state: &State,
     functions: &ComponentFunctions<State>,
-) -> Result<Box<dyn Any>, String> {
+    scope: &EvaluationScope,
+) -> Result<Value, String> {
     match node {
-        ASTNode::Number(num) => Ok(Box::new(*num)),
+        ASTNode::Number(num) => Ok(Value::Number(*num)),
+        ASTNode::Integer(integer) => Ok(Value::Integer(*integer)),
+        ASTNode::String(value) => Ok(Value::String(value.clone())),
+        ASTNode::Bool(value) => Ok(Value::Bool(*value)),
+        ASTNode::Null => Ok(Value::Null),
         ASTNode::Identifier(name) => {
+            if let Some(variable) = scope.get_variable(name) {
+                return Ok(variable);
+            }
             state.get_property(name)
                 .ok_or_else(
                     || format!("Property {} not found in component state", name)
                 )
         },
         ASTNode::FunctionCall(name, args) => 
-            evaluate_component_function(name, args.clone(), state, functions),
+            evaluate_component_function(name, args.clone(), state, functions, scope),
         ASTNode::BinaryOperation { operator, left, right } => 
-            evaluate_binary_operation(operator, left, right, state, functions),
+            evaluate_binary_operation(operator, left, right, state, functions, scope),
         ASTNode::Comparison { operator, left, right } => 
-            evaluate_comparison(operator, left, right, state, functions),
+            evaluate_comparison(operator, left, right, state, functions, scope),
         ASTNode::LogicalOperation { operator, left, right } => 
-            evaluate_logical_operation(operator, left, right, state, functions),
+            evaluate_logical_operation(operator, left, right, state, functions, scope),
+        ASTNode::UnaryOperation { operator, operand } =>
+            evaluate_unary_operation(operator, operand, state, functions, scope),
+        ASTNode::Conditional { condition, then_branch, else_branch } => {
+            let branch = if evaluate_ast(condition, state, functions, scope)?.is_truthy() { then_branch } else { else_branch };
+            evaluate_ast(branch, state, functions, scope)
+        },
+        ASTNode::MemberAccess { object, member } =>
+            evaluate_member_access(object, member, state, functions, scope),
+        ASTNode::IndexAccess { object, index } =>
+            evaluate_index_access(object, index, state, functions, scope),
+        ASTNode::Pipe { name, input, args } =>
+            evaluate_pipe(name, input, args, state, functions, scope),
     }
 }
 
@@ -33,29 +51,68 @@ fn evaluate_component_function<State: ComponentState>(
     args: Vec<ASTNode>,
     state: &State,
     functions: &ComponentFunctions<State>,
-) -> Result<Box<dyn Any>, String> {
-    let arg_values: Result<Vec<Box<dyn Any>>, String> = args.iter()
-        .map(|arg| evaluate_ast(arg, state, functions))
+    scope: &EvaluationScope,
+) -> Result<Value, String> {
+    let arg_values: Result<Vec<Value>, String> = args.iter()
+        .map(|arg| evaluate_ast(arg, state, functions, scope))
         .collect();
 
     match functions.dynamic_params_functions.get(name) {
-        Some(func) => Ok(func(state, arg_values?)),
+        Some(dynamic_params_function) => (dynamic_params_function.function)(state, arg_values?)
+            .map_err(|er| format!("Error calling function {}: {}", name, er)),
         None => Err(format!("Function {} not found in component functions", name)),
     }
 }
 
+fn evaluate_pipe<State: ComponentState>(
+    name: &str,
+    input: &ASTNode,
+    args: &[ASTNode],
+    state: &State,
+    functions: &ComponentFunctions<State>,
+    scope: &EvaluationScope,
+) -> Result<Value, String> {
+    let input_value = evaluate_ast(input, state, functions, scope)?;
+    let arg_values: Vec<Value> = args.iter()
+        .map(|arg| evaluate_ast(arg, state, functions, scope))
+        .collect::<Result<_, _>>()?;
+
+    pipe_registry::apply_pipe(name, &input_value, &arg_values)
+}
+
 fn evaluate_binary_operation<State: ComponentState>(
     operator: &Operator,
     left: &ASTNode,
     right: &ASTNode,
     state: &State,
     functions: &ComponentFunctions<State>,
-) -> Result<Box<dyn Any>, String> {
-    let left_val = evaluate_ast(left, state, functions)?;
-    let right_val = evaluate_ast(right, state, functions)?;
+    scope: &EvaluationScope,
+) -> Result<Value, String> {
+    let left_val = evaluate_ast(left, state, functions, scope)?;
+    let right_val = evaluate_ast(right, state, functions, scope)?;
+
+    // Adding a string concatenates the text forms of the operands
+    if *operator == Operator::Add && (matches!(left_val, Value::String(_)) || matches!(right_val, Value::String(_))) {
+        return Ok(Value::String(format!("{}{}", format_operand(&left_val)?, format_operand(&right_val)?)));
+    }
 
-    let left_float = *left_val.downcast::<f64>().map_err(|_| "Type mismatch")?;
-    let right_float = *right_val.downcast::<f64>().map_err(|_| "Type mismatch")?;
+    // Integers stay integers, except for divisions and overflows
+    if let (Value::Integer(left_integer), Value::Integer(right_integer)) = (&left_val, &right_val) {
+        let result = match operator {
+            Operator::Add => left_integer.checked_add(*right_integer),
+            Operator::Subtract => left_integer.checked_sub(*right_integer),
+            Operator::Multiply => left_integer.checked_mul(*right_integer),
+            _ => None,
+        };
+        if let Some(result) = result {
+            return Ok(Value::Integer(result));
+        }
+    }
+
+    let (left_float, right_float) = match (left_val.as_number(), right_val.as_number()) {
+        (Some(left_float), Some(right_float)) => (left_float, right_float),
+        _ => return Err(format!("Type mismatch: cannot apply {:?} to {} and {}", operator, left_val.type_name(), right_val.type_name())),
+    };
 
     let result = match operator {
         Operator::Add => left_float + right_float,
@@ -65,7 +122,12 @@ fn evaluate_binary_operation<State: ComponentState>(
         _ => return Err("Unsupported operation for binary operation".to_string()),
     };
 
-    Ok(Box::new(result))
+    Ok(Value::Number(result))
+}
+
+fn format_operand(value: &Value) -> Result<String, String> {
+    value_formatter::format_value(value)
+        .ok_or_else(|| format!("Type mismatch: cannot concatenate {} to a string", value.type_name()))
 }
 
 fn evaluate_comparison<State: ComponentState>(
@@ -74,24 +136,29 @@ fn evaluate_comparison<State: ComponentState>(
     right: &ASTNode,
     state: &State,
     functions: &ComponentFunctions<State>,
-) -> Result<Box<dyn Any>, String> {
-    let left_val = evaluate_ast(left, state, functions)?;
-    let right_val = evaluate_ast(right, state, functions)?;
-
-    let left_float = *left_val.downcast::<f64>().map_err(|_| "Type mismatch")?;
-    let right_float = *right_val.downcast::<f64>().map_err(|_| "Type mismatch")?;
+    scope: &EvaluationScope,
+) -> Result<Value, String> {
+    let left_val = evaluate_ast(left, state, functions, scope)?;
+    let right_val = evaluate_ast(right, state, functions, scope)?;
 
     let result = match operator {
-        Operator::Equal => left_float == right_float,
-        Operator::NotEqual => left_float != right_float,
-        Operator::Less => left_float < right_float,
-        Operator::Greater => left_float > right_float,
-        Operator::LessEqual => left_float <= right_float,
-        Operator::GreaterEqual => left_float >= right_float,
+        Operator::Equal => left_val == right_val,
+        Operator::NotEqual => left_val != right_val,
+        Operator::Less | Operator::Greater | Operator::LessEqual | Operator::GreaterEqual => {
+            let ordering = left_val.compare(&right_val)
+                .ok_or_else(|| format!("Type mismatch: cannot compare {} and {}", left_val.type_name(), right_val.type_name()))?;
+
+            match operator {
+                Operator::Less => ordering.is_lt(),
+                Operator::Greater => ordering.is_gt(),
+                Operator::LessEqual => ordering.is_le(),
+                _ => ordering.is_ge(),
+            }
+        },
         _ => return Err("Unsupported operation for comparison".to_string()),
     };
 
-    Ok(Box::new(result))
+    Ok(Value::Bool(result))
 }
 
 fn evaluate_logical_operation<State: ComponentState>(
@@ -100,18 +167,85 @@ fn evaluate_logical_operation<State: ComponentState>(
     right: &ASTNode,
     state: &State,
     functions: &ComponentFunctions<State>,
-) -> Result<Box<dyn Any>, String> {
-    let left_val = evaluate_ast(left, state, functions)?;
-    let right_val = evaluate_ast(right, state, functions)?;
-
-    let left_bool = *left_val.downcast::<bool>().map_err(|_| "Type mismatch")?;
-    let right_bool = *right_val.downcast::<bool>().map_err(|_| "Type mismatch")?;
+    scope: &EvaluationScope,
+) -> Result<Value, String> {
+    let left_bool = evaluate_ast(left, state, functions, scope)?.is_truthy();
 
+    // The right operand is only evaluated when it decides the result
     let result = match operator {
-        Operator::And => left_bool && right_bool,
-        Operator::Or => left_bool || right_bool,
+        Operator::And => left_bool && evaluate_ast(right, state, functions, scope)?.is_truthy(),
+        Operator::Or => left_bool || evaluate_ast(right, state, functions, scope)?.is_truthy(),
         _ => return Err("Unsupported operation for logical operation".to_string()),
     };
 
-    Ok(Box::new(result))
+    Ok(Value::Bool(result))
+}
+
+fn evaluate_unary_operation<State: ComponentState>(
+    operator: &Operator,
+    operand: &ASTNode,
+    state: &State,
+    functions: &ComponentFunctions<State>,
+    scope: &EvaluationScope,
+) -> Result<Value, String> {
+    let operand_val = evaluate_ast(operand, state, functions, scope)?;
+
+    match (operator, operand_val) {
+        (Operator::Not, operand_val) => Ok(Value::Bool(!operand_val.is_truthy())),
+        (Operator::Negate, Value::Integer(integer)) => Ok(integer.checked_neg().map_or(Value::Number(-(integer as f64)), Value::Integer)),
+        (Operator::Negate, Value::Number(number)) => Ok(Value::Number(-number)),
+        (Operator::Negate, operand_val) => Err(format!("Type mismatch: cannot negate {}", operand_val.type_name())),
+        _ => Err("Unsupported operation for unary operation".to_string()),
+    }
+}
+
+/*
+ * Reads a member of a map (user.name), or the length of a list or string (items.length).
+ */
+fn evaluate_member_access<State: ComponentState>(
+    object: &ASTNode,
+    member: &str,
+    state: &State,
+    functions: &ComponentFunctions<State>,
+    scope: &EvaluationScope,
+) -> Result<Value, String> {
+    let object_val = evaluate_ast(object, state, functions, scope)?;
+
+    match (&object_val, member) {
+        (Value::Map(entries), _) => entries.get(member)
+            .cloned()
+            .ok_or_else(|| format!("Member {} not found in map", member)),
+        (Value::List(items), "length") => Ok(Value::from(items.len())),
+        (Value::String(text), "length") => Ok(Value::from(text.chars().count())),
+        _ => Err(format!("Cannot read member {} of {}", member, object_val.type_name())),
+    }
+}
+
+/*
+ * Reads an item of a list by position (items[0]), or of a map by key (scores['math']).
+ */
+fn evaluate_index_access<State: ComponentState>(
+    object: &ASTNode,
+    index: &ASTNode,
+    state: &State,
+    functions: &ComponentFunctions<State>,
+    scope: &EvaluationScope,
+) -> Result<Value, String> {
+    let object_val = evaluate_ast(object, state, functions, scope)?;
+    let index_val = evaluate_ast(index, state, functions, scope)?;
+
+    match (&object_val, &index_val) {
+        (Value::List(items), Value::Integer(_) | Value::Number(_)) => {
+            let position = usize::from_value(index_val.clone())
+                .ok_or_else(|| format!("Invalid list index {}", index_val))?;
+
+            items.get(position)
+                .cloned()
+                .ok_or_else(|| format!("Index {} out of bounds for a list of {} items", position, items.len()))
+        },
+        (Value::Map(entries), Value::String(key)) => entries.get(key)
+            .cloned()
+            .ok_or_else(|| format!("Key {} not found in map", key)),
+        _ => Err(format!("Type mismatch: cannot index {} with {}", object_val.type_name(), index_val.type_name())),
+    }
 }
@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+
+use crate::rendering::elements::component::functions::component_functions::ComponentFunctions;
+
+use super::ast::ASTNode;
+
+
+/*
+ * Collects the identifiers read by the template expressions, i.e. the state properties the template depends on.
+ * Returns None when an expression calls a component function not declaring the properties it reads, see wrap_fn!.
+ */
+pub fn collect_dependencies<State>(asts: &[ASTNode], functions: &ComponentFunctions<State>) -> Option<HashSet<String>> {
+    let mut dependencies = HashSet::new();
+
+    for ast in asts {
+        collect_node_dependencies(ast, functions, &mut dependencies)?;
+    }
+
+    Some(dependencies)
+}
+
+fn collect_node_dependencies<State>(node: &ASTNode, functions: &ComponentFunctions<State>, dependencies: &mut HashSet<String>) -> Option<()> {
+    match node {
+        ASTNode::Number(_) | ASTNode::Integer(_) | ASTNode::String(_) | ASTNode::Bool(_) | ASTNode::Null => Some(()),
+        ASTNode::Identifier(name) => {
+            dependencies.insert(name.clone());
+            Some(())
+        },
+        ASTNode::FunctionCall(name, args) => {
+            let function_dependencies = functions.dynamic_params_functions.get(name)?.dependencies.as_ref()?;
+            dependencies.extend(function_dependencies.iter().cloned());
+            args.iter().try_for_each(|arg| collect_node_dependencies(arg, functions, dependencies))
+        },
+        ASTNode::BinaryOperation { left, right, .. } |
+        ASTNode::Comparison { left, right, .. } |
+        ASTNode::LogicalOperation { left, right, .. } |
+        ASTNode::IndexAccess { object: left, index: right } => {
+            collect_node_dependencies(left, functions, dependencies)?;
+            collect_node_dependencies(right, functions, dependencies)
+        },
+        ASTNode::UnaryOperation { operand: object, .. } |
+        ASTNode::MemberAccess { object, .. } => collect_node_dependencies(object, functions, dependencies),
+        ASTNode::Conditional { condition, then_branch, else_branch } => {
+            collect_node_dependencies(condition, functions, dependencies)?;
+            collect_node_dependencies(then_branch, functions, dependencies)?;
+            collect_node_dependencies(else_branch, functions, dependencies)
+        },
+        // Pipes are pure, so they only depend on their input and arguments
+        ASTNode::Pipe { input, args, .. } => {
+            collect_node_dependencies(input, functions, dependencies)?;
+            args.iter().try_for_each(|arg| collect_node_dependencies(arg, functions, dependencies))
+        },
+    }
+}
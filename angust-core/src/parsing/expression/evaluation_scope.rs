@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+
+use super::value::Value;
+
+
+/*
+ * Template-local variables (e.g. the variables bound by @for) visible to expressions.
+ * Frames are pushed and popped while traversing the template; inner frames shadow outer ones,
+ * and all frames shadow the component state.
+ */
+#[derive(Default)]
+pub struct EvaluationScope {
+    frames: Vec<HashMap<String, Value>>,
+}
+
+impl EvaluationScope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_frame(&mut self, variables: HashMap<String, Value>) {
+        self.frames.push(variables);
+    }
+
+    pub fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    pub fn get_variable(&self, name: &str) -> Option<Value> {
+        self.frames.iter().rev()
+            .find_map(|frame| frame.get(name))
+            .cloned()
+    }
+}
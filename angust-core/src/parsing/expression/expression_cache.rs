@@ -0,0 +1,34 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use super::ast::{self, ASTNode};
+
+
+thread_local! {
+    static COMPILED_EXPRESSIONS: RefCell<HashMap<String, Result<ASTNode, String>>> = RefCell::new(HashMap::new());
+}
+
+// Beyond it, the cache is emptied before adding an expression, so that its size stays bounded
+const MAX_COMPILED_EXPRESSIONS: usize = 4096;
+
+/*
+ * Parses a template expression into its AST, once per expression source,
+ * as the same expressions are found in several templates, e.g. in the templates of the components of a library.
+ */
+pub fn compile_expression(expression: &str) -> Result<ASTNode, String> {
+    let cached_ast = COMPILED_EXPRESSIONS.with(|expressions| expressions.borrow().get(expression).cloned());
+    if let Some(ast) = cached_ast {
+        return ast;
+    }
+
+    let ast = ast::parse_string_to_ast(expression.to_string())
+        .map_err(|er| er.to_string());
+
+    COMPILED_EXPRESSIONS.with(|expressions| {
+        let mut expressions = expressions.borrow_mut();
+        if expressions.len() >= MAX_COMPILED_EXPRESSIONS {
+            expressions.clear();
+        }
+        expressions.insert(expression.to_string(), ast.clone());
+    });
+    ast
+}
@@ -1,3 +1,8 @@
-mod expression_parser;
 pub mod ast;
-pub mod ast_evaluator;
\ No newline at end of file
+pub mod ast_evaluator;
+pub mod dependency_collector;
+pub mod evaluation_scope;
+pub mod expression_cache;
+pub mod value;
+pub mod value_formatter;
+pub mod pipes;
\ No newline at end of file
@@ -0,0 +1,123 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::parsing::expression::value::Value;
+
+use super::pipe_arguments::{as_number, string_argument};
+
+
+/*
+ * Formats a date given as milliseconds since the Unix epoch (or an opaque SystemTime), in UTC.
+ * Supported tokens: yyyy, yy, MMMM, MMM, MM, M, dd, d, HH, H, hh, h, mm, ss, a.
+ */
+const FORMAT_TOKENS: [&str; 15] = ["yyyy", "yy", "MMMM", "MMM", "MM", "M", "dd", "d", "HH", "H", "hh", "h", "mm", "ss", "a"];
+const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+pub fn date(value: &Value, args: &[Value]) -> Result<Value, String> {
+    let timestamp_millis = timestamp_input(value)?;
+    let format = string_argument(args, 0)?.unwrap_or(String::from("yyyy-MM-dd"));
+
+    Ok(Value::String(format_date(&DateTime::from_timestamp_millis(timestamp_millis), &format)))
+}
+
+fn timestamp_input(value: &Value) -> Result<i64, String> {
+    if let Some(time) = value.downcast_opaque_ref::<SystemTime>() {
+        return match time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => Ok(duration.as_millis() as i64),
+            Err(er) => Ok(-(er.duration().as_millis() as i64)),
+        };
+    }
+
+    as_number(value)
+        .map(|millis| millis.floor() as i64)
+        .ok_or_else(|| "Input is not a timestamp".to_string())
+}
+
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl DateTime {
+    fn from_timestamp_millis(timestamp_millis: i64) -> Self {
+        let seconds = timestamp_millis.div_euclid(1000);
+        let days = seconds.div_euclid(86_400);
+        let seconds_of_day = seconds.rem_euclid(86_400) as u32;
+        let (year, month, day) = civil_from_days(days);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: seconds_of_day / 3600,
+            minute: seconds_of_day % 3600 / 60,
+            second: seconds_of_day % 60,
+        }
+    }
+}
+
+// Converts days since 1970-01-01 to a (year, month, day) date of the proleptic Gregorian calendar
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let shifted_days = days + 719_468;
+    let era = shifted_days.div_euclid(146_097);
+    let day_of_era = shifted_days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+fn format_date(date_time: &DateTime, format: &str) -> String {
+    let mut result = String::new();
+    let mut remaining = format;
+
+    while !remaining.is_empty() {
+        match FORMAT_TOKENS.iter().find(|token| remaining.starts_with(*token)) {
+            Some(token) => {
+                result.push_str(&format_token(date_time, token));
+                remaining = &remaining[token.len()..];
+            },
+            None => {
+                let character = remaining.chars().next().unwrap();
+                result.push(character);
+                remaining = &remaining[character.len_utf8()..];
+            }
+        }
+    }
+
+    result
+}
+
+fn format_token(date_time: &DateTime, token: &str) -> String {
+    let hour_12 = if date_time.hour % 12 == 0 { 12 } else { date_time.hour % 12 };
+    let month_name = MONTH_NAMES[(date_time.month - 1) as usize];
+
+    match token {
+        "yyyy" => format!("{:04}", date_time.year),
+        "yy" => format!("{:02}", date_time.year.rem_euclid(100)),
+        "MMMM" => month_name.to_string(),
+        "MMM" => month_name[..3].to_string(),
+        "MM" => format!("{:02}", date_time.month),
+        "M" => date_time.month.to_string(),
+        "dd" => format!("{:02}", date_time.day),
+        "d" => date_time.day.to_string(),
+        "HH" => format!("{:02}", date_time.hour),
+        "H" => date_time.hour.to_string(),
+        "hh" => format!("{:02}", hour_12),
+        "h" => hour_12.to_string(),
+        "mm" => format!("{:02}", date_time.minute),
+        "ss" => format!("{:02}", date_time.second),
+        "a" => String::from(if date_time.hour < 12 { "AM" } else { "PM" }),
+        _ => token.to_string(),
+    }
+}
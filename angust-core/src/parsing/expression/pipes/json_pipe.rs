@@ -0,0 +1,29 @@
+use serde_json::{Map, Number, Value as JsonValue};
+
+use crate::parsing::expression::value::Value;
+
+
+pub fn json(value: &Value, _: &[Value]) -> Result<Value, String> {
+    let json_value = to_json_value(value)
+        .ok_or_else(|| "Input cannot be converted to JSON".to_string())?;
+
+    serde_json::to_string_pretty(&json_value)
+        .map(Value::String)
+        .map_err(|er| er.to_string())
+}
+
+fn to_json_value(value: &Value) -> Option<JsonValue> {
+    match value {
+        Value::Number(number) => Some(Number::from_f64(*number).map_or(JsonValue::Null, JsonValue::Number)),
+        Value::Integer(integer) => Some(JsonValue::from(*integer)),
+        Value::String(text) => Some(JsonValue::from(text.clone())),
+        Value::Bool(boolean) => Some(JsonValue::from(*boolean)),
+        Value::List(items) => items.iter().map(to_json_value).collect::<Option<Vec<JsonValue>>>().map(JsonValue::Array),
+        Value::Map(entries) => entries.iter()
+            .map(|(key, value)| to_json_value(value).map(|json_value| (key.clone(), json_value)))
+            .collect::<Option<Map<String, JsonValue>>>()
+            .map(JsonValue::Object),
+        Value::Null => Some(JsonValue::Null),
+        Value::Opaque(_) => value.downcast_opaque_ref::<JsonValue>().cloned(),
+    }
+}
@@ -0,0 +1,7 @@
+pub mod pipe_registry;
+pub mod pipe_arguments;
+
+mod text_pipes;
+mod number_pipes;
+mod date_pipe;
+mod json_pipe;
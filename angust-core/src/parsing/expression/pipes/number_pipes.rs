@@ -0,0 +1,122 @@
+use crate::parsing::expression::value::Value;
+
+use super::pipe_arguments::{number_input, string_argument};
+
+
+/*
+ * Number formatting pipes. Digits are configured Angular-style by a digits info string
+ * "{min_integer_digits}.{min_fraction_digits}-{max_fraction_digits}", e.g. '1.2-2'.
+ */
+pub fn number(value: &Value, args: &[Value]) -> Result<Value, String> {
+    let number = number_input(value)?;
+    let digits_info = parse_digits_info(string_argument(args, 0)?, DigitsInfo::new(1, 0, 3))?;
+
+    Ok(Value::String(format_number(number, &digits_info)))
+}
+
+pub fn percent(value: &Value, args: &[Value]) -> Result<Value, String> {
+    let number = number_input(value)?;
+    let digits_info = parse_digits_info(string_argument(args, 0)?, DigitsInfo::new(1, 0, 0))?;
+
+    Ok(Value::String(format_number(number * 100.0, &digits_info) + "%"))
+}
+
+pub fn currency(value: &Value, args: &[Value]) -> Result<Value, String> {
+    let number = number_input(value)?;
+    let currency_code = string_argument(args, 0)?.unwrap_or(String::from("USD"));
+    let digits_info = parse_digits_info(string_argument(args, 1)?, DigitsInfo::new(1, 2, 2))?;
+
+    let formatted_amount = format_number(number.abs(), &digits_info);
+    let sign = if number < 0.0 && formatted_amount.chars().any(|c| c.is_ascii_digit() && c != '0') { "-" } else { "" };
+
+    Ok(Value::String(format!("{}{}{}", sign, currency_symbol(&currency_code), formatted_amount)))
+}
+
+fn currency_symbol(currency_code: &str) -> String {
+    match currency_code {
+        "USD" => String::from("$"),
+        "EUR" => String::from("€"),
+        "GBP" => String::from("£"),
+        "JPY" | "CNY" => String::from("¥"),
+        "INR" => String::from("₹"),
+        "KRW" => String::from("₩"),
+        code => code.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DigitsInfo {
+    pub min_integer_digits: usize,
+    pub min_fraction_digits: usize,
+    pub max_fraction_digits: usize,
+}
+
+impl DigitsInfo {
+    pub fn new(min_integer_digits: usize, min_fraction_digits: usize, max_fraction_digits: usize) -> Self {
+        Self {
+            min_integer_digits,
+            min_fraction_digits,
+            max_fraction_digits: max_fraction_digits.max(min_fraction_digits),
+        }
+    }
+}
+
+pub fn parse_digits_info(digits_info: Option<String>, default: DigitsInfo) -> Result<DigitsInfo, String> {
+    let digits_info = match digits_info {
+        Some(info) => info,
+        None => return Ok(default),
+    };
+    let invalid_format = || format!("Invalid digits info '{}', expected e.g. '1.2-2'", digits_info);
+    let parse_part = |part: Option<&str>, default: usize| -> Result<usize, String> {
+        match part.map(str::trim).filter(|part| !part.is_empty()) {
+            Some(part) => part.parse::<usize>().map_err(|_| invalid_format()),
+            None => Ok(default),
+        }
+    };
+
+    let mut integer_and_fraction = digits_info.splitn(2, '.');
+    let min_integer_digits = parse_part(integer_and_fraction.next(), default.min_integer_digits)?;
+
+    let mut fraction_parts = integer_and_fraction.next().unwrap_or_default().splitn(2, '-');
+    let min_fraction_digits = parse_part(fraction_parts.next(), default.min_fraction_digits)?;
+    let max_fraction_digits = parse_part(fraction_parts.next(), default.max_fraction_digits.max(min_fraction_digits))?;
+
+    Ok(DigitsInfo::new(min_integer_digits, min_fraction_digits, max_fraction_digits))
+}
+
+pub fn format_number(number: f64, digits_info: &DigitsInfo) -> String {
+    let rounded = format!("{:.*}", digits_info.max_fraction_digits, number.abs());
+    let (integer_part, fraction_part) = match rounded.split_once('.') {
+        Some((integer_part, fraction_part)) => (integer_part.to_string(), fraction_part.to_string()),
+        None => (rounded.clone(), String::new()),
+    };
+
+    // Drop trailing zeros beyond the minimum fraction digits
+    let mut fraction_part = fraction_part;
+    while fraction_part.len() > digits_info.min_fraction_digits && fraction_part.ends_with('0') {
+        fraction_part.pop();
+    }
+
+    let padded_integer_part = format!("{:0>width$}", integer_part, width = digits_info.min_integer_digits);
+    let grouped_integer_part = group_thousands(&padded_integer_part);
+
+    let is_zero = rounded.chars().all(|c| c == '0' || c == '.');
+    let sign = if number < 0.0 && !is_zero { "-" } else { "" };
+
+    if fraction_part.is_empty() {
+        format!("{}{}", sign, grouped_integer_part)
+    } else {
+        format!("{}{}.{}", sign, grouped_integer_part, fraction_part)
+    }
+}
+
+fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
@@ -0,0 +1,24 @@
+use crate::parsing::expression::{value::{FromValue, Value}, value_formatter};
+
+/*
+ * Helpers for reading pipe inputs and arguments, which are evaluated expressions of any type.
+ */
+pub fn as_text(value: &Value) -> Result<String, String> {
+    value_formatter::format_value(value)
+        .ok_or_else(|| "Input cannot be displayed as text".to_string())
+}
+
+pub fn as_number(value: &Value) -> Option<f64> {
+    f64::from_value(value.clone())
+}
+
+pub fn string_argument(args: &[Value], index: usize) -> Result<Option<String>, String> {
+    match args.get(index) {
+        Some(arg) => as_text(arg).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn number_input(value: &Value) -> Result<f64, String> {
+    as_number(value).ok_or_else(|| "Input is not a number".to_string())
+}
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use once_cell::sync::Lazy;
+
+use crate::parsing::expression::value::Value;
+
+use super::{date_pipe, json_pipe, number_pipes, text_pipes};
+
+/*
+ * Registry for the pipes usable in template expressions (e.g. {{ price | currency:'EUR' }}).
+ * Populated with the built-in pipes, extended at runtime by the client application with its own named transforms,
+ * which can be registered at any time, including after pipes were applied. A registered pipe replaces the one of the same name.
+ */
+pub type Pipe = Box<dyn Fn(&Value, &[Value]) -> Result<Value, String> + Send + Sync>;
+static PIPE_REGISTRY: Lazy<RwLock<HashMap<String, Arc<Pipe>>>> = Lazy::new(|| {
+    RwLock::new(built_in_pipes().into_iter().map(|(name, pipe)| (name, Arc::new(pipe))).collect())
+});
+
+pub fn initialize_pipe_registry(pipes: HashMap<String, Pipe>) {
+    pipes.into_iter().for_each(|(name, pipe)| register_pipe(&name, pipe));
+}
+
+pub fn register_pipe(name: &str, pipe: Pipe) {
+    let mut registry = PIPE_REGISTRY.write().unwrap();
+    registry.insert(name.to_string(), Arc::new(pipe));
+}
+
+pub fn apply_pipe(name: &str, value: &Value, args: &[Value]) -> Result<Value, String> {
+    // The pipe runs without the registry locked, so that it can apply or register pipes itself
+    let pipe = PIPE_REGISTRY.read().unwrap().get(name).cloned();
+
+    match pipe {
+        Some(pipe) => pipe(value, args).map_err(|er| format!("Pipe '{}' failed: {}", name, er)),
+        None => Err(format!("Pipe '{}' not found in pipe registry", name)),
+    }
+}
+
+fn built_in_pipes() -> HashMap<String, Pipe> {
+    let mut pipes: HashMap<String, Pipe> = HashMap::new();
+
+    pipes.insert("uppercase".to_string(), Box::new(text_pipes::uppercase));
+    pipes.insert("lowercase".to_string(), Box::new(text_pipes::lowercase));
+    pipes.insert("titlecase".to_string(), Box::new(text_pipes::titlecase));
+    pipes.insert("number".to_string(), Box::new(number_pipes::number));
+    pipes.insert("percent".to_string(), Box::new(number_pipes::percent));
+    pipes.insert("currency".to_string(), Box::new(number_pipes::currency));
+    pipes.insert("date".to_string(), Box::new(date_pipe::date));
+    pipes.insert("json".to_string(), Box::new(json_pipe::json));
+
+    pipes
+}
@@ -0,0 +1,32 @@
+use crate::parsing::expression::value::Value;
+
+use super::pipe_arguments::as_text;
+
+
+pub fn uppercase(value: &Value, _: &[Value]) -> Result<Value, String> {
+    Ok(Value::String(as_text(value)?.to_uppercase()))
+}
+
+pub fn lowercase(value: &Value, _: &[Value]) -> Result<Value, String> {
+    Ok(Value::String(as_text(value)?.to_lowercase()))
+}
+
+pub fn titlecase(value: &Value, _: &[Value]) -> Result<Value, String> {
+    let text = as_text(value)?;
+
+    let mut result = String::with_capacity(text.len());
+    let mut is_word_start = true;
+    for character in text.chars() {
+        if character.is_whitespace() {
+            is_word_start = true;
+            result.push(character);
+        } else if is_word_start {
+            is_word_start = false;
+            result.extend(character.to_uppercase());
+        } else {
+            result.extend(character.to_lowercase());
+        }
+    }
+
+    Ok(Value::String(result))
+}
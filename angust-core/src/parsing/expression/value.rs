@@ -0,0 +1,315 @@
+use std::{any::Any, cmp::Ordering, collections::HashMap, fmt::{Display, Formatter}, rc::Rc};
+
+
+/*
+ * Value of a template expression, and of the component state properties and functions used by expressions.
+ * Rust values are converted into values with Value::from, and back with FromValue::from_value.
+ * Values of other types can be passed around as opaque values, e.g. Value::opaque(SystemTime::now()).
+ */
+#[derive(Debug, Clone)]
+pub enum Value {
+    Number(f64),
+    Integer(i64),
+    String(String),
+    Bool(bool),
+    List(Vec<Value>),
+    Map(HashMap<String, Value>),
+    Null,
+    Opaque(Rc<dyn Any>),
+}
+
+impl Value {
+    pub fn opaque<T: Any>(value: T) -> Self {
+        Value::Opaque(Rc::new(value))
+    }
+
+    pub fn downcast_opaque_ref<T: Any>(&self) -> Option<&T> {
+        match self {
+            Value::Opaque(value) => value.downcast_ref::<T>(),
+            _ => None,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "number",
+            Value::Integer(_) => "integer",
+            Value::String(_) => "string",
+            Value::Bool(_) => "bool",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Null => "null",
+            Value::Opaque(_) => "opaque",
+        }
+    }
+
+    // Numbers and integers, without the coercion of strings done by FromValue
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            Value::Integer(integer) => Some(*integer as f64),
+            _ => None,
+        }
+    }
+
+    /*
+     * Truthiness of a value used as a condition (@if, [class.name], &&, ||):
+     * null, false, zero, NaN and empty strings, lists and maps are false, everything else is true.
+     */
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Number(number) => *number != 0.0 && !number.is_nan(),
+            Value::Integer(integer) => *integer != 0,
+            Value::String(text) => !text.is_empty(),
+            Value::Bool(boolean) => *boolean,
+            Value::List(items) => !items.is_empty(),
+            Value::Map(entries) => !entries.is_empty(),
+            Value::Null => false,
+            Value::Opaque(_) => true,
+        }
+    }
+
+    /*
+     * Ordering used by the <, >, <= and >= operators: numbers and integers are compared numerically,
+     * strings lexicographically; other values cannot be ordered.
+     */
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Integer(left), Value::Integer(right)) => Some(left.cmp(right)),
+            (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+            _ => self.as_number()?.partial_cmp(&other.as_number()?),
+        }
+    }
+
+    /*
+     * Converts an output payload, keeping the values of types without a conversion as opaque values.
+     */
+    pub fn from_boxed_any(value: Box<dyn Any>) -> Value {
+        Value::downcast_boxed::<Value>(value)
+            .or_else(Value::downcast_boxed::<String>)
+            .or_else(Value::downcast_boxed::<&'static str>)
+            .or_else(Value::downcast_boxed::<f64>)
+            .or_else(Value::downcast_boxed::<f32>)
+            .or_else(Value::downcast_boxed::<i32>)
+            .or_else(Value::downcast_boxed::<i64>)
+            .or_else(Value::downcast_boxed::<u32>)
+            .or_else(Value::downcast_boxed::<u64>)
+            .or_else(Value::downcast_boxed::<usize>)
+            .or_else(Value::downcast_boxed::<bool>)
+            .or_else(Value::downcast_boxed::<Vec<String>>)
+            .or_else(Value::downcast_boxed::<Vec<f64>>)
+            .or_else(Value::downcast_boxed::<Vec<i32>>)
+            .or_else(Value::downcast_boxed::<Vec<bool>>)
+            .unwrap_or_else(|value| Value::Opaque(Rc::from(value)))
+    }
+
+    fn downcast_boxed<T: Any + Into<Value>>(value: Box<dyn Any>) -> Result<Value, Box<dyn Any>> {
+        value.downcast::<T>().map(|value| (*value).into())
+    }
+}
+
+/*
+ * Equality used by the == and != operators: numbers and integers are compared numerically,
+ * lists and maps item by item, opaque values by identity; values of different types are not equal.
+ */
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Integer(left), Value::Integer(right)) => left == right,
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Bool(left), Value::Bool(right)) => left == right,
+            (Value::List(left), Value::List(right)) => left == right,
+            (Value::Map(left), Value::Map(right)) => left == right,
+            (Value::Null, Value::Null) => true,
+            (Value::Opaque(left), Value::Opaque(right)) => Rc::ptr_eq(left, right),
+            _ => matches!((self.as_number(), other.as_number()), (Some(left), Some(right)) if left == right),
+        }
+    }
+}
+
+// Text form of the values, used to display them and to concatenate them to strings
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Integer(integer) => write!(f, "{}", integer),
+            Value::String(text) => write!(f, "{}", text),
+            Value::Bool(boolean) => write!(f, "{}", boolean),
+            Value::List(items) => {
+                let texts: Vec<String> = items.iter().map(Value::to_string).collect();
+                write!(f, "{}", texts.join(", "))
+            },
+            Value::Map(entries) => {
+                let mut texts: Vec<String> = entries.iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+                texts.sort();
+                write!(f, "{{{}}}", texts.join(", "))
+            },
+            Value::Null => Ok(()),
+            Value::Opaque(_) => write!(f, "[opaque]"),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Number(value as f64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Null
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(value: Vec<T>) -> Self {
+        Value::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> From<HashMap<String, T>> for Value {
+    fn from(value: HashMap<String, T>) -> Self {
+        Value::Map(value.into_iter().map(|(key, value)| (key, value.into())).collect())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+/*
+ * Conversion of a value to a Rust type, e.g. for the state property it is written to
+ * or the argument of the component function it is passed to. Coercion rules:
+ * - f64, f32: numbers and integers, and strings holding a number (e.g. the literal input count="3")
+ * - integer types: integers in range, numbers without fractional part, and strings holding an integer
+ * - bool: booleans, and the strings "true" and "false"
+ * - String: strings, and numbers, integers and booleans in their text form
+ * - Option<T>: null is None, other values are converted to T
+ * - Vec<T>, HashMap<String, T>: lists and maps whose items all convert to T
+ * Client applications can implement it for their own types, e.g. with downcast_opaque_ref.
+ */
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Option<Self>;
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::String(text) => text.trim().parse::<f64>().ok(),
+            value => value.as_number(),
+        }
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(value: Value) -> Option<Self> {
+        f64::from_value(value).map(|number| number as f32)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Bool(boolean) => Some(boolean),
+            Value::String(text) => text.trim().parse::<bool>().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::String(text) => Some(text),
+            Value::Number(_) | Value::Integer(_) | Value::Bool(_) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Null => Some(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::List(items) => items.into_iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Map(entries) => entries.into_iter()
+                .map(|(key, value)| T::from_value(value).map(|value| (key, value)))
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_integer_value {
+    ($($type:ty),*) => {
+        $(
+            impl From<$type> for Value {
+                fn from(value: $type) -> Self {
+                    i64::try_from(value).map_or(Value::Number(value as f64), Value::Integer)
+                }
+            }
+
+            impl FromValue for $type {
+                fn from_value(value: Value) -> Option<Self> {
+                    match value {
+                        Value::Integer(integer) => <$type>::try_from(integer).ok(),
+                        Value::Number(number) if number.is_finite() && number.fract() == 0.0 => <$type>::try_from(number as i64).ok(),
+                        Value::String(text) => text.trim().parse::<$type>().ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_value!(i32, i64, u32, u64, usize);
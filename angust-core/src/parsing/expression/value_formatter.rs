@@ -0,0 +1,41 @@
+use std::{any::Any, fmt::Display, sync::RwLock};
+
+use once_cell::sync::Lazy;
+
+use super::value::Value;
+
+/*
+ * Formatting of the values of template expressions (e.g. {{ count + 1 }}) for display.
+ * Opaque values are displayed by the formatters registered by client applications for their own types;
+ * the most recently registered formatter that accepts a value wins.
+ */
+pub type ValueFormatter = Box<dyn Fn(&dyn Any) -> Option<String> + Send + Sync>;
+
+static VALUE_FORMATTERS: Lazy<RwLock<Vec<ValueFormatter>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+pub fn register_formatter(formatter: ValueFormatter) {
+    let mut formatters = VALUE_FORMATTERS.write().unwrap();
+    formatters.push(formatter);
+}
+
+pub fn register_display_type<T: Display + 'static>() {
+    register_formatter(display_formatter::<T>());
+}
+
+pub fn format_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Opaque(opaque_value) => {
+            let formatters = VALUE_FORMATTERS.read().unwrap();
+            formatters.iter().rev().find_map(|formatter| formatter(opaque_value.as_ref()))
+        },
+        Value::List(items) => items.iter()
+            .map(format_value)
+            .collect::<Option<Vec<String>>>()
+            .map(|texts| texts.join(", ")),
+        value => Some(value.to_string()),
+    }
+}
+
+fn display_formatter<T: Display + 'static>() -> ValueFormatter {
+    Box::new(|value: &dyn Any| value.downcast_ref::<T>().map(|val| val.to_string()))
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use kuchiki::Attributes;
+
+use crate::{parsing::expression::{ast_evaluator, value::Value, value_formatter}, rendering::elements::component::{component_outputs::OutputBinding, component_state::ComponentState}};
+
+use super::{compiled_nodes::{CompiledBinding, CompiledElement, CompiledExpression}, directive_parser, html_parser::ParsingContext};
+
+
+/*
+ * Property bindings of an element, in the forms:
+ * [attr]="expr", [class.name]="bool_expr" and [style.prop]="expr" (or [style.prop.unit]="expr").
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyBinding {
+    Attribute { name: String, expression: String },
+    Class { name: String, expression: String },
+    Style { property: String, unit: Option<String>, expression: String },
+}
+
+impl PropertyBinding {
+    pub fn expression(&self) -> &str {
+        match self {
+            PropertyBinding::Attribute { expression, .. } | PropertyBinding::Class { expression, .. } | PropertyBinding::Style { expression, .. } => expression,
+        }
+    }
+
+    // Attribute of the element set by the binding
+    pub fn bound_attribute(&self) -> &str {
+        match self {
+            PropertyBinding::Attribute { name, .. } => name,
+            PropertyBinding::Class { .. } => "class",
+            PropertyBinding::Style { .. } => "style",
+        }
+    }
+}
+
+pub fn parse_property_bindings(attributes: &Attributes) -> Vec<PropertyBinding> {
+    attributes.map.iter()
+        .filter_map(|(name, attribute)| parse_property_binding(name.local.as_ref(), &attribute.value))
+        .collect()
+}
+
+fn parse_property_binding(attribute_name: &str, expression: &str) -> Option<PropertyBinding> {
+    let target = attribute_name.strip_prefix('[')?.strip_suffix(']')?;
+    let expression = expression.trim().to_string();
+
+    let mut target_parts = target.splitn(3, '.');
+    match (target_parts.next(), target_parts.next(), target_parts.next()) {
+        (Some("class"), Some(class_name), None) => Some(PropertyBinding::Class { name: class_name.to_string(), expression }),
+        (Some("style"), Some(property), unit) => Some(PropertyBinding::Style {
+            property: property.to_string(),
+            unit: unit.map(str::to_string),
+            expression,
+        }),
+        (Some(name), None, None) => Some(PropertyBinding::Attribute { name: name.to_string(), expression }),
+        _ => None,
+    }
+}
+
+// Attributes of an element without its property bindings
+pub fn unbound_attributes(attributes: &Attributes) -> Attributes {
+    let mut unbound_attributes = attributes.clone();
+    unbound_attributes.map.retain(|name, _| parse_property_binding(name.local.as_ref(), "").is_none());
+    unbound_attributes
+}
+
+/*
+ * Evaluates the property bindings of an element against the component state,
+ * returning its attributes with the bound values applied and the binding attributes removed.
+ * Bindings are re-evaluated whenever the component's template is reloaded on state change.
+ * The [attr] bindings of custom components are left out, being the inputs of the component (see parse_component_inputs).
+ */
+pub fn resolve_attribute_bindings<State: ComponentState>(
+    compiled_element: &CompiledElement,
+    is_custom_component: bool,
+    context: &mut ParsingContext<State>,
+) -> Result<Attributes, String> {
+    let mut resolved_attributes = compiled_element.unbound_attributes.clone();
+
+    for CompiledBinding { binding, ast } in compiled_element.property_bindings.iter() {
+        match binding {
+            PropertyBinding::Attribute { .. } if is_custom_component => {},
+            PropertyBinding::Attribute { name, expression } => {
+                let value = evaluate_binding_expression(context, expression, ast)?;
+                let formatted_value = value_formatter::format_value(&value)
+                    .ok_or_else(|| format!("Binding of attribute '{}' evaluated to an unsupported type", name))?;
+
+                resolved_attributes.insert(name.as_str(), formatted_value);
+            },
+            PropertyBinding::Class { name, expression } => {
+                let value = evaluate_binding_expression(context, expression, ast)?;
+                update_class_attribute(&mut resolved_attributes, name, value.is_truthy());
+            },
+            PropertyBinding::Style { property, unit, expression } => {
+                let value = evaluate_binding_expression(context, expression, ast)?;
+                let formatted_value = value_formatter::format_value(&value)
+                    .ok_or_else(|| format!("Binding of style '{}' evaluated to an unsupported type", property))?;
+
+                let style_declaration = format!("{}: {}{}", property, formatted_value, unit.as_deref().unwrap_or_default());
+                append_style_declaration(&mut resolved_attributes, &style_declaration);
+            },
+        }
+    }
+
+    Ok(resolved_attributes)
+}
+
+/*
+ * Evaluates the inputs passed to a custom component, [title]="expr" or title="literal".
+ * Bound values keep their type, literal values are passed as strings and converted to the type of the property;
+ * the component only writes those matching its input properties.
+ */
+pub fn parse_component_inputs<State: ComponentState>(
+    compiled_element: &CompiledElement,
+    context: &mut ParsingContext<State>,
+) -> Result<HashMap<String, Value>, String> {
+    let mut inputs: HashMap<String, Value> = HashMap::new();
+
+    for (name, attribute) in compiled_element.unbound_attributes.map.iter() {
+        let attribute_name = name.local.as_ref();
+        if is_literal_input(attribute_name) {
+            inputs.insert(attribute_name.to_string(), Value::String(attribute.value.clone()));
+        }
+    }
+
+    for CompiledBinding { binding, ast } in compiled_element.property_bindings.iter() {
+        if let PropertyBinding::Attribute { name, expression } = binding {
+            let value = evaluate_binding_expression(context, expression, ast)?;
+            inputs.insert(name.clone(), value);
+        }
+    }
+
+    Ok(inputs)
+}
+
+/*
+ * Parses the output bindings of a custom component, (saved)="on_saved($event)".
+ */
+pub fn parse_component_output_bindings<State: ComponentState>(
+    compiled_element: &CompiledElement,
+    child_component_id: &str,
+    context: &mut ParsingContext<State>,
+) -> Result<Vec<OutputBinding>, String> {
+    let mut output_bindings = Vec::new();
+
+    for handler in compiled_element.handlers.iter() {
+        let output_name = match handler.attribute_name.strip_prefix('(').and_then(|name| name.strip_suffix(')')) {
+            Some(output_name) => output_name,
+            None => continue,
+        };
+
+        let (handler_call, event_arg_index) = directive_parser::parse_output_handler_expression(handler, context)?;
+        output_bindings.push(OutputBinding {
+            child_component_id: child_component_id.to_string(),
+            output_name: output_name.to_string(),
+            handler_call,
+            event_arg_index,
+        });
+    }
+
+    Ok(output_bindings)
+}
+
+static NON_INPUT_ATTRIBUTES: [&str; 3] = ["id", "class", "style"];
+
+fn is_literal_input(attribute_name: &str) -> bool {
+    !NON_INPUT_ATTRIBUTES.contains(&attribute_name) && !attribute_name.starts_with(['@', '[', '('])
+}
+
+fn evaluate_binding_expression<State: ComponentState>(
+    context: &mut ParsingContext<State>,
+    expression: &str,
+    ast: &CompiledExpression,
+) -> Result<Value, String> {
+    let ast = ast.as_ref()
+        .map_err(|_| format!("Failed to parse binding expression '{}'", expression))?;
+    ParsingContext::add_ast(context, ast.clone());
+
+    let (state, functions) = match (context.component_state, context.component_functions) {
+        (Some(state), Some(functions)) => (state, functions),
+        _ => return Err("Property binding used outside of a component".to_string()),
+    };
+
+    ast_evaluator::evaluate_ast::<State>(ast, state, functions, &context.scope)
+}
+
+fn update_class_attribute(attributes: &mut Attributes, class_name: &str, is_class_applied: bool) {
+    let mut class_names: Vec<String> = attributes.get("class").unwrap_or_default()
+        .split_whitespace()
+        .filter(|name| *name != class_name)
+        .map(str::to_string)
+        .collect();
+    if is_class_applied {
+        class_names.push(class_name.to_string());
+    }
+
+    attributes.insert("class", class_names.join(" "));
+}
+
+// Bound styles come last, so they override the literal style declarations
+fn append_style_declaration(attributes: &mut Attributes, style_declaration: &str) {
+    let existing_styles = attributes.get("style").unwrap_or_default().trim().trim_end_matches(';').to_string();
+
+    let styles = if existing_styles.is_empty() {
+        style_declaration.to_string()
+    } else {
+        format!("{}; {}", existing_styles, style_declaration)
+    };
+    attributes.insert("style", styles);
+}
@@ -0,0 +1,160 @@
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+
+use angust_syntax::template_patterns::PLACEHOLDER_REGEX;
+use kuchiki::{Attributes, ElementData, Node, NodeData, NodeRef};
+
+use crate::parsing::{css::{cascade::CandidateRules, selector_matcher::StyledElement, stylesheet_parser::Stylesheet}, expression::{ast::ASTNode, expression_cache}};
+
+use super::{binding_parser::{self, PropertyBinding}, directive_parser::{self, ForDirective}};
+
+
+/*
+ * Nodes of a template with their directives, bindings and text placeholders parsed, and the style rules that may match
+ * their elements. They are compiled once per template, so that rendering it only evaluates them, see template_cache.
+ * Like the source locations, they are keyed by the address of their node in the DOM of the template.
+ */
+#[derive(Default)]
+pub struct CompiledNodes {
+    nodes: HashMap<*const Node, Rc<CompiledNode>>,
+}
+
+impl CompiledNodes {
+    pub fn compile(dom: &NodeRef, stylesheet: &Option<Arc<Stylesheet>>) -> Self {
+        let nodes = dom.inclusive_descendants()
+            .map(|node| (Rc::as_ptr(&node.0), Rc::new(CompiledNode::compile(&node, stylesheet))))
+            .collect();
+
+        CompiledNodes { nodes }
+    }
+
+    pub fn get(&self, node: &NodeRef) -> Option<Rc<CompiledNode>> {
+        self.nodes.get(&Rc::as_ptr(&node.0)).cloned()
+    }
+}
+
+// Expressions failing to parse keep their error, reported each time the template is rendered
+pub type CompiledExpression = Result<ASTNode, String>;
+
+pub enum CompiledNode {
+    Element(CompiledElement),
+    Text(Vec<TextSegment>),
+    Other,
+}
+
+pub struct CompiledElement {
+    pub for_directive: Option<Result<ForDirective, String>>,
+    pub conditional_directive: Option<ConditionalDirective>,
+    pub property_bindings: Vec<CompiledBinding>,
+    pub handlers: Vec<CompiledHandler>, // Of the event directives, @onclick="save", and of the outputs, (saved)="on_saved($event)"
+    pub unbound_attributes: Attributes,
+    pub candidate_rules: Rc<CandidateRules>,
+}
+
+pub enum ConditionalDirective {
+    If(CompiledExpression),
+    ElseIf(CompiledExpression),
+    Else,
+}
+
+pub struct CompiledBinding {
+    pub binding: PropertyBinding,
+    pub ast: CompiledExpression,
+}
+
+pub struct CompiledHandler {
+    pub attribute_name: String,
+    pub expression: String,
+    pub ast: CompiledExpression,
+}
+
+// Part of a text, either literal or a {{ expression }} placeholder
+pub enum TextSegment {
+    Literal(String),
+    Placeholder { expression: String, ast: CompiledExpression },
+}
+
+impl CompiledNode {
+    pub fn compile(node: &NodeRef, stylesheet: &Option<Arc<Stylesheet>>) -> Self {
+        match node.data() {
+            NodeData::Element(ref elem_data) => CompiledNode::Element(compile_element(elem_data, stylesheet)),
+            NodeData::Text(ref text) => CompiledNode::Text(compile_text_segments(text.borrow().trim())),
+            _ => CompiledNode::Other,
+        }
+    }
+
+    pub fn as_element(&self) -> Option<&CompiledElement> {
+        match self {
+            CompiledNode::Element(compiled_element) => Some(compiled_element),
+            _ => None,
+        }
+    }
+}
+
+fn compile_element(elem_data: &ElementData, stylesheet: &Option<Arc<Stylesheet>>) -> CompiledElement {
+    let attributes = elem_data.attributes.borrow();
+
+    let property_bindings: Vec<CompiledBinding> = binding_parser::parse_property_bindings(&attributes).into_iter()
+        .map(|binding| {
+            let ast = expression_cache::compile_expression(binding.expression());
+            CompiledBinding { binding, ast }
+        })
+        .collect();
+
+    let handlers = attributes.map.iter()
+        .filter(|(name, _)| name.local.starts_with("@on") || name.local.starts_with('('))
+        .map(|(name, attribute)| {
+            let expression = attribute.value.trim().to_string();
+            let ast = expression_cache::compile_expression(&expression);
+            CompiledHandler { attribute_name: name.local.to_string(), expression, ast }
+        })
+        .collect();
+
+    // @else wins over an @else-if on the same element
+    let conditional_directive = match (attributes.get("@if"), attributes.get("@else-if"), attributes.contains("@else")) {
+        (Some(expression), _, _) => Some(ConditionalDirective::If(expression_cache::compile_expression(expression.trim()))),
+        (None, _, true) => Some(ConditionalDirective::Else),
+        (None, Some(expression), false) => Some(ConditionalDirective::ElseIf(expression_cache::compile_expression(expression.trim()))),
+        (None, None, false) => None,
+    };
+
+    // The bound attributes are unknown until the template is rendered, so the rules selecting them may match
+    let unbound_attributes = binding_parser::unbound_attributes(&attributes);
+    let bound_attributes: Vec<&str> = property_bindings.iter().map(|compiled_binding| compiled_binding.binding.bound_attribute()).collect();
+    let styled_element = StyledElement::new(elem_data.name.local.as_ref(), &unbound_attributes);
+    let candidate_rules = Rc::new(CandidateRules::compute(&styled_element, &bound_attributes, stylesheet));
+
+    CompiledElement {
+        for_directive: attributes.get("@for").map(|expression| directive_parser::parse_for_directive(expression.trim())),
+        conditional_directive,
+        property_bindings,
+        handlers,
+        unbound_attributes,
+        candidate_rules,
+    }
+}
+
+fn compile_text_segments(text: &str) -> Vec<TextSegment> {
+    let mut segments = Vec::new();
+    let mut literal_start = 0;
+
+    for captures in PLACEHOLDER_REGEX.captures_iter(text) {
+        let (placeholder, expression) = match (captures.get(0), captures.get(1)) {
+            (Some(placeholder), Some(expression)) => (placeholder, expression.as_str().trim()),
+            _ => continue,
+        };
+
+        if placeholder.start() > literal_start {
+            segments.push(TextSegment::Literal(text[literal_start..placeholder.start()].to_string()));
+        }
+        segments.push(TextSegment::Placeholder {
+            expression: expression.to_string(),
+            ast: expression_cache::compile_expression(expression),
+        });
+        literal_start = placeholder.end();
+    }
+
+    if literal_start < text.len() {
+        segments.push(TextSegment::Literal(text[literal_start..].to_string()));
+    }
+    segments
+}
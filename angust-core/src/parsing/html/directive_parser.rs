@@ -1,97 +1,349 @@
-use regex::Regex;
+use std::{collections::HashMap, rc::Rc};
 
-use crate::{parsing::expression::{ast, ast_evaluator}, rendering::elements::component::component_state::ComponentState};
+use angust_syntax::template_patterns::{FOR_ALIAS_REGEX, FOR_IMPLICIT_VARIABLES, FOR_LOOP_REGEX, FOR_TRACK_REGEX};
 
-use super::html_parser::ParsingContext;
+use crate::{parsing::expression::{ast::ASTNode, ast_evaluator, expression_cache, value::Value, value_formatter}, rendering::elements::{component::{component_state::ComponentState, functions::event_handler_call::EventHandlerCall}, element::EventType, element_event_handlers::ElementEventHandlers}};
 
+use super::{compiled_nodes::{CompiledElement, CompiledExpression, CompiledHandler, ConditionalDirective, TextSegment}, html_parser::ParsingContext};
 
-pub fn parse_on_click_attribute<State: ComponentState>(
-    attributes: &kuchiki::Attributes,
-    _: &ParsingContext<State>
-) -> Option<String> {
-    if let Some(on_click_value) = attributes.get("@onclick") {
-        let handler = on_click_value.to_string();
-        let handler = handler.trim_start_matches("handle_event('");
-        let handler = handler.trim_end_matches("')");
-        let handler = handler.to_string();
-        return Some(handler);
+
+/*
+ * Parses the event directives of an element (@onclick="save", @onmouseenter="highlight(index)", ...).
+ */
+pub fn parse_event_directives<State: ComponentState>(
+    compiled_element: &CompiledElement,
+    context: &mut ParsingContext<State>,
+) -> Result<ElementEventHandlers, String> {
+    let mut handlers = Vec::new();
+
+    for handler in compiled_element.handlers.iter() {
+        let directive_name = handler.attribute_name.as_str();
+        if !directive_name.starts_with("@on") {
+            continue;
+        }
+
+        let event_type = get_directive_event_type(directive_name)
+            .ok_or_else(|| format!("Unknown event directive {}", directive_name))?;
+        let handler_call = parse_event_handler_expression(handler, context)?;
+        handlers.push((event_type, handler_call));
+    }
+
+    Ok(ElementEventHandlers::new(handlers))
+}
+
+fn get_directive_event_type(directive_name: &str) -> Option<EventType> {
+    match directive_name {
+        "@onclick" => Some(EventType::MouseClick),
+        "@ondblclick" => Some(EventType::DoubleClick),
+        "@onmousedown" => Some(EventType::MouseDown),
+        "@onmouseup" => Some(EventType::MouseUp),
+        "@onmouseenter" => Some(EventType::MouseEnter),
+        "@onmouseleave" => Some(EventType::MouseLeave),
+        "@onwheel" => Some(EventType::MouseRoll(0.0)),
+        "@onkeydown" => Some(EventType::KeyDown),
+        "@onkeyup" => Some(EventType::KeyUp),
+        "@oninput" => Some(EventType::Input),
+        "@onfocus" => Some(EventType::Focus),
+        "@onblur" => Some(EventType::Blur),
+        _ => None,
     }
-    None
 }
 
-pub fn parse_if_attribute<State: ComponentState>(
-    attributes: &kuchiki::Attributes,
-) -> Option<String> {
-    if let Some(expression_value) = attributes.get("@if") {
-        let expression = expression_value.to_string().trim().to_string();
-        return Some(expression);
+/*
+ * Parses an event handler attribute, either a handler name (@onclick="save")
+ * or a call whose arguments are evaluated right away (@onclick="select_tab(tab, index)").
+ */
+pub fn parse_event_handler_expression<State: ComponentState>(
+    handler: &CompiledHandler,
+    context: &mut ParsingContext<State>,
+) -> Result<EventHandlerCall, String> {
+    parse_handler_call(handler, context, false)
+        .map(|(handler_call, _)| handler_call)
+}
+
+/*
+ * Parses the handler of a child component output, (saved)="on_saved($event)",
+ * returning the position of the $event argument to be replaced by the emitted payload.
+ */
+pub fn parse_output_handler_expression<State: ComponentState>(
+    handler: &CompiledHandler,
+    context: &mut ParsingContext<State>,
+) -> Result<(EventHandlerCall, Option<usize>), String> {
+    parse_handler_call(handler, context, true)
+}
+
+static EVENT_VARIABLE: &str = "$event";
+
+fn parse_handler_call<State: ComponentState>(
+    handler: &CompiledHandler,
+    context: &mut ParsingContext<State>,
+    allows_event_variable: bool,
+) -> Result<(EventHandlerCall, Option<usize>), String> {
+    let ast = handler.ast.as_ref()
+        .map_err(|_| format!("Failed to parse event handler '{}'", handler.expression))?;
+
+    match ast {
+        ASTNode::Identifier(handler_name) => Ok((EventHandlerCall::new(handler_name.clone(), vec![], context.component_id.clone()), None)),
+        ASTNode::FunctionCall(function_name, args) => {
+            // Legacy form: handle_event('handler_name')
+            if let (true, [ASTNode::String(handler_name)]) = (function_name == "handle_event", args.as_slice()) {
+                return Ok((EventHandlerCall::new(handler_name.clone(), vec![], context.component_id.clone()), None));
+            }
+
+            let (state, functions) = match (context.component_state, context.component_functions) {
+                (Some(state), Some(functions)) => (state, functions),
+                _ => return Err("Event handler arguments used outside of a component".to_string()),
+            };
+
+            let mut arg_values: Vec<Value> = Vec::with_capacity(args.len());
+            let mut event_arg_index = None;
+            for (index, arg) in args.iter().enumerate() {
+                if let (true, ASTNode::Identifier(name)) = (allows_event_variable, arg) {
+                    if name == EVENT_VARIABLE {
+                        event_arg_index = Some(index);
+                        arg_values.push(Value::Null); // Placeholder for the emitted payload
+                        continue;
+                    }
+                }
+
+                ParsingContext::add_ast(context, arg.clone());
+                arg_values.push(ast_evaluator::evaluate_ast::<State>(arg, state, functions, &context.scope)?);
+            }
+
+            Ok((EventHandlerCall::new(function_name.clone(), arg_values, context.component_id.clone()), event_arg_index))
+        },
+        _ => Err(format!("Event handler '{}' must be a handler name or call", handler.expression)),
     }
-    None
 }
 
-pub fn parse_state_placeholder<State: ComponentState>(
-    text: &str,
-    state: &State,
+/*
+ * Evaluates the {{ expression }} placeholders of a text against the component state, returning the text with their values.
+ */
+pub fn evaluate_text_segments<State: ComponentState>(
+    segments: &[TextSegment],
+    context: &mut ParsingContext<State>,
 ) -> Result<String, String> {
-    let re = Regex::new(r"\{\{(\s*[^}]+\s*)\}\}").unwrap();
-    let mut result = text.to_string();
+    let mut text = String::new();
 
-    for cap in re.captures_iter(text) {
-        let matched_text = match cap.get(0) {
-            Some(text) => text,
-            None => continue,
-        };
+    let (state, functions) = match (context.component_state, context.component_functions) {
+        (Some(state), Some(functions)) => (state, functions),
+        _ => return Err("State placeholder used outside of a component".to_string()),
+    };
 
-        let key = cap[1].trim();
-        let property = match state.get_property(key) {
-            Some(prop) => prop,
-            None => return Err(format!("Property '{}' not found in state", key)),
+    for segment in segments {
+        let (expression, ast) = match segment {
+            TextSegment::Literal(literal) => {
+                text.push_str(literal);
+                continue;
+            },
+            TextSegment::Placeholder { expression, ast } => (expression, ast),
         };
 
-        let value = if let Some(val) = property.downcast_ref::<String>() {
-            val.clone()
-        } else if let Some(val) = property.downcast_ref::<f64>() {
-            val.to_string()
-        } else if let Some(val) = property.downcast_ref::<i32>() {
-            val.to_string()
-        } else {
-            return Err(format!("Property '{}' is of an unsupported type", key));
-        };
+        let ast = ast.as_ref()
+            .map_err(|_| format!("Failed to parse expression '{}'", expression))?;
+        ParsingContext::add_ast(context, ast.clone());
+
+        let value = ast_evaluator::evaluate_ast::<State>(ast, state, functions, &context.scope)?;
+        let formatted_value = value_formatter::format_value(&value)
+            .ok_or_else(|| format!("Expression '{}' evaluated to an unsupported type", expression))?;
 
-        result = result.replace(matched_text.as_str(), &value);
+        text.push_str(&formatted_value);
     }
 
-    Ok(result)
+    Ok(text)
 }
 
-pub fn parse_if_expression<State: ComponentState>(
+/*
+ * Resolves the @if / @else-if / @else directive of an element against the conditional chain
+ * opened by its preceding siblings, and updates the chain for the following ones.
+ */
+pub fn parse_conditional_directives<State: ComponentState>(
     context: &mut ParsingContext<State>,
-    attributes: &kuchiki::Attributes,
+    conditional_directive: Option<&ConditionalDirective>,
 ) -> Result<bool, String> {
-    let if_expression_option = parse_if_attribute::<State>(&attributes);
-    if let Some(if_expression) = if_expression_option {
-        let ast_result = ast::parse_string_to_ast(if_expression);
-        if let Ok(ast) = ast_result {
-            ParsingContext::add_ast(context, ast.clone());
-
-            let state = context.component_state.unwrap();
-            let functions = context.component_functions.unwrap();
-            let evaluation_result = ast_evaluator::evaluate_ast::<State>(&ast, state, functions);
-
-            if let Ok(result) = evaluation_result {
-                if let Some(is_if_true) = result.downcast_ref::<bool>() {
-                    println!("If expression evaluated to: {}", is_if_true);
-                    return Ok(*is_if_true);
-                } else {
-                    return Err("If expression did not evaluate to a boolean".to_string());
-                }
-            } else {
-                return Err("Failed to evaluate if expression".to_string());
+    match conditional_directive {
+        None => Ok(true),
+        Some(ConditionalDirective::If(expression)) => {
+            let if_result = evaluate_condition_expression(context, expression);
+            context.conditional_chain = Some(*if_result.as_ref().unwrap_or(&true)); // Skip the @else branches of a failed @if
+            if_result
+        },
+        Some(ConditionalDirective::ElseIf(expression)) => {
+            if is_conditional_branch_taken(context, "@else-if")? {
+                return Ok(false);
+            }
+
+            let is_else_if_true = evaluate_condition_expression(context, expression)?;
+            context.conditional_chain = Some(is_else_if_true);
+            Ok(is_else_if_true)
+        },
+        Some(ConditionalDirective::Else) => {
+            let is_branch_taken = is_conditional_branch_taken(context, "@else")?;
+            context.conditional_chain = None;
+            Ok(!is_branch_taken)
+        },
+    }
+}
+
+fn is_conditional_branch_taken<State: ComponentState>(context: &ParsingContext<State>, directive_name: &str) -> Result<bool, String> {
+    context.conditional_chain
+        .ok_or_else(|| format!("{} directive has no preceding @if", directive_name))
+}
+
+fn evaluate_condition_expression<State: ComponentState>(
+    context: &mut ParsingContext<State>,
+    expression: &CompiledExpression,
+) -> Result<bool, String> {
+    let ast = expression.as_ref()
+        .map_err(|_| "Failed to parse conditional expression".to_string())?;
+    ParsingContext::add_ast(context, ast.clone());
+
+    let (state, functions) = match (context.component_state, context.component_functions) {
+        (Some(state), Some(functions)) => (state, functions),
+        _ => return Err("Conditional directive used outside of a component".to_string()),
+    };
+
+    ast_evaluator::evaluate_ast::<State>(ast, state, functions, &context.scope)
+        .map(|result| result.is_truthy())
+        .map_err(|er| format!("Failed to evaluate conditional expression: {}", er))
+}
+
+/*
+ * Parsed form of @for="item of items; let i = index; track item.id".
+ * Besides the item, each iteration binds the implicit variables index, first and last,
+ * which can be given additional names through `let alias = implicit_variable` clauses.
+ * The track clause gives the identity of the items, see item_identity.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForDirective {
+    pub item_name: String,
+    pub collection_name: String,
+    pub aliases: Vec<(String, String)>,
+    pub track_expression: Option<ASTNode>,
+}
+
+// Iteration of a @for loop, with the key of its item among the items of the loop
+pub struct ForIteration {
+    pub item_key: String,
+    pub variables: HashMap<String, Value>,
+}
+
+pub fn parse_for_directive(for_expression: &str) -> Result<ForDirective, String> {
+    let mut clauses = for_expression.split(';').map(str::trim);
+
+    let loop_clause = clauses.next().unwrap_or_default();
+    let loop_captures = FOR_LOOP_REGEX.captures(loop_clause)
+        .ok_or_else(|| format!("Invalid @for expression '{}', expected 'item of items'", for_expression))?;
+
+    let mut aliases = Vec::new();
+    let mut track_expression = None;
+    for clause in clauses.filter(|clause| !clause.is_empty()) {
+        if let Some(track_captures) = FOR_TRACK_REGEX.captures(clause) {
+            let ast = expression_cache::compile_expression(track_captures[1].trim())
+                .map_err(|er| format!("Invalid @for track expression '{}': {}", &track_captures[1], er))?;
+            track_expression = Some(ast);
+            continue;
+        }
+
+        let alias_captures = FOR_ALIAS_REGEX.captures(clause)
+            .ok_or_else(|| format!("Invalid @for clause '{}', expected 'let alias = index' or 'track expression'", clause))?;
+        let implicit_variable = alias_captures[2].to_string();
+        if !FOR_IMPLICIT_VARIABLES.contains(&implicit_variable.as_str()) {
+            return Err(format!("Unknown @for variable '{}'", implicit_variable));
+        }
+        aliases.push((alias_captures[1].to_string(), implicit_variable));
+    }
+
+    Ok(ForDirective {
+        item_name: loop_captures[1].to_string(),
+        collection_name: loop_captures[2].to_string(),
+        aliases,
+        track_expression,
+    })
+}
+
+/*
+ * Evaluates the @for directive of an element against the component's array getters.
+ * Returns one iteration per item, holding the variables bound for that iteration and the key of its item:
+ * its identity, numbered when several items have the same identity.
+ */
+pub fn parse_for_expression<State: ComponentState>(
+    context: &mut ParsingContext<State>,
+    for_directive: &Result<ForDirective, String>,
+) -> Result<Vec<ForIteration>, String> {
+    let directive = for_directive.as_ref().map_err(Clone::clone)?;
+
+    let (state, functions) = match (context.component_state, context.component_functions) {
+        (Some(state), Some(functions)) => (state, functions),
+        _ => return Err("@for directive used outside of a component".to_string()),
+    };
+    let array_getter = functions.array_getters.get(&directive.collection_name)
+        .ok_or_else(|| format!("Array getter '{}' not found in component functions", directive.collection_name))?;
+
+    // Without declared dependencies, the getter is assumed to read the property it is named after, if there is one
+    let dependencies = array_getter.dependencies.clone().or_else(|| {
+        let is_property = state.get_all_properties().contains(&directive.collection_name.as_str());
+        is_property.then(|| vec![directive.collection_name.clone()])
+    });
+    match dependencies {
+        Some(dependencies) => dependencies.into_iter().for_each(|dependency| context.add_ast(ASTNode::Identifier(dependency))),
+        None => context.has_undeclared_dependencies = true,
+    }
+    if let Some(track_expression) = &directive.track_expression {
+        context.add_ast(track_expression.clone());
+    }
+
+    let items = (array_getter.getter)(state);
+    let item_count = items.len();
+
+    let mut iterations = Vec::with_capacity(item_count);
+    let mut identity_occurrences: HashMap<String, usize> = HashMap::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let mut frame: HashMap<String, Value> = HashMap::new();
+        frame.insert(directive.item_name.clone(), item);
+        frame.insert("index".to_string(), Value::from(index));
+        frame.insert("first".to_string(), Value::Bool(index == 0));
+        frame.insert("last".to_string(), Value::Bool(index + 1 == item_count));
+
+        for (alias, implicit_variable) in directive.aliases.iter() {
+            let value = frame.get(implicit_variable).cloned();
+            if let Some(value) = value {
+                frame.insert(alias.clone(), value);
             }
-        } else {
-            return Err("Failed to parse if expression".to_string());
         }
-    } else {
-        return Ok(true);
+
+        let identity = match &directive.track_expression {
+            Some(track_expression) => {
+                context.scope.push_frame(frame.clone());
+                let tracked_value = ast_evaluator::evaluate_ast::<State>(track_expression, state, functions, &context.scope);
+                context.scope.pop_frame();
+                item_identity(&tracked_value.map_err(|er| format!("Failed to evaluate @for track expression: {}", er))?)
+            },
+            None => item_identity(&frame[&directive.item_name]),
+        };
+        let occurrence = identity_occurrences.entry(identity.clone()).or_insert(0);
+        *occurrence += 1;
+
+        iterations.push(ForIteration { item_key: format!("{}#{}", identity, occurrence), variables: frame });
     }
-}
\ No newline at end of file
+
+    Ok(iterations)
+}
+
+/*
+ * Identity of a @for item, which its elements and child components keep when items are inserted, removed or reordered.
+ * Items are identified by value, except opaque items identified by address: these keep their identity only if
+ * the array getter returns the same Rc each time, otherwise a track clause gives their identity, e.g. track row.id.
+ */
+fn item_identity(value: &Value) -> String {
+    match value {
+        Value::Opaque(value) => format!("{:p}", Rc::as_ptr(value)),
+        Value::List(items) => format!("[{}]", items.iter().map(item_identity).collect::<Vec<String>>().join(", ")),
+        Value::Map(entries) => {
+            let mut entries: Vec<String> = entries.iter().map(|(key, value)| format!("{:?}: {}", key, item_identity(value))).collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(", "))
+        },
+        _ => format!("{:?}", value),
+    }
+}
@@ -1,73 +1,136 @@
-use kuchiki::NodeRef;
+use std::rc::Rc;
 
-use crate::{parsing::css::css_parser, rendering::elements::component::component_state::ComponentState};
+use kuchiki::{Attributes, NodeRef};
+
+use crate::{parsing::{css::{css_parser, selector_matcher::StyledElement, style_context::StyleContext}, diagnostics::Severity}, rendering::elements::component::component_state::ComponentState};
 use crate::rendering::elements::button::Button;
 use crate::rendering::elements::component::component_factory_registry::create_component;
+use crate::rendering::elements::component::content_projection::DEFAULT_SLOT;
 use crate::rendering::elements::container::Container;
 use crate::rendering::elements::element::Element;
+use crate::rendering::elements::element_event_handlers::ElementEventHandlers;
 use crate::rendering::elements::image::Image;
 use crate::rendering::elements::styles::Styles;
 
-use super::{directive_parser, html_parser::{self, ParsingContext}};
+use super::{binding_parser, compiled_nodes::CompiledElement, directive_parser, html_parser::{self, ParsingContext}};
+
+const NATIVE_ELEMENTS: [&str; 4] = ["div", "button", "img", "ng-content"]; // The other tags are custom components
 
 pub fn dispatch_element_processing<State : ComponentState>(
     elem_data: &kuchiki::ElementData, 
+    compiled_element: &CompiledElement,
     node: &NodeRef, 
     parent_styles: Option<&Styles>, 
     context: &mut ParsingContext<State>,
 ) -> Option<Box<dyn Element>> {
-    match elem_data.name.local.as_ref() {
-        "div" => Some(process_div_element::<State>(elem_data, node, parent_styles, context)),
-        "button" => Some(process_button_element::<State>(elem_data, node, parent_styles, context)),
-        "img" => process_image_element::<State>(elem_data, node, parent_styles, context),
-        component_name => process_custom_component::<State>(component_name, elem_data, node, parent_styles, context),
+    // Structural directives (@for is expanded beforehand, as it produces sibling elements)
+    if !apply_structural_directives::<State>(compiled_element, node, context) {
+        return None
+    }
+
+    let tag_name = elem_data.name.local.as_ref();
+    let attributes = resolve_attributes::<State>(compiled_element, !NATIVE_ELEMENTS.contains(&tag_name), node, context);
+    let event_handlers = parse_event_handlers::<State>(compiled_element, node, context);
+
+    // The element is matched by the stylesheet selectors against its ancestors while it and its children are parsed
+    let mut styled_element = StyledElement::new(tag_name, &attributes);
+    styled_element.candidate_rules = Some(compiled_element.candidate_rules.clone());
+    if context.element_path.is_empty() {
+        styled_element.host = context.host_element.clone();
+    }
+    context.element_path.push(Rc::new(styled_element));
+    let mut element = match tag_name {
+        "div" => Some(process_div_element::<State>(node, parent_styles, context)),
+        "button" => Some(process_button_element::<State>(node, parent_styles, context)),
+        "img" => process_image_element::<State>(&attributes, node, parent_styles, context),
+        "ng-content" => Some(process_content_slot::<State>(&attributes, node, parent_styles, context)),
+        component_name => process_custom_component::<State>(component_name, compiled_element, node, parent_styles, context),
+    };
+    context.element_path.pop();
+
+    if let (Some(element), false) = (element.as_mut(), event_handlers.is_empty()) {
+        element.set_event_handlers(event_handlers);
     }
+    element
+}
+
+/*
+ * Applies the @if / @else-if / @else directives, returning whether the element should be added to the DOM.
+ */
+fn apply_structural_directives<State : ComponentState>(
+    compiled_element: &CompiledElement, 
+    node: &NodeRef, 
+    context: &mut ParsingContext<State>,
+) -> bool {
+    match directive_parser::parse_conditional_directives(context, compiled_element.conditional_directive.as_ref()) {
+        Ok(should_add_to_dom) => should_add_to_dom,
+        Err(er) => {
+            context.add_diagnostic(node, Severity::Error, format!("Error parsing conditional directive: {}", er));
+            false
+        }
+    }
+}
+
+/*
+ * Applies the [attr], [class.name] and [style.prop] bindings, falling back to the literal attributes on error.
+ * The [attr] bindings of custom components are their inputs, evaluated by binding_parser::parse_component_inputs.
+ */
+fn resolve_attributes<State : ComponentState>(
+    compiled_element: &CompiledElement, 
+    is_custom_component: bool,
+    node: &NodeRef, 
+    context: &mut ParsingContext<State>,
+) -> Attributes {
+    binding_parser::resolve_attribute_bindings(compiled_element, is_custom_component, context)
+        .unwrap_or_else(|er| {
+            context.add_diagnostic(node, Severity::Error, format!("Error parsing property bindings: {}", er));
+            compiled_element.unbound_attributes.clone()
+        })
+}
+
+fn parse_event_handlers<State : ComponentState>(
+    compiled_element: &CompiledElement, 
+    node: &NodeRef, 
+    context: &mut ParsingContext<State>,
+) -> ElementEventHandlers {
+    directive_parser::parse_event_directives(compiled_element, context)
+        .unwrap_or_else(|er| {
+            context.add_diagnostic(node, Severity::Error, format!("Error parsing event directives: {}", er));
+            ElementEventHandlers::default()
+        })
 }
 
 fn process_div_element<State : ComponentState>(
-    elem_data: &kuchiki::ElementData, 
     node: &NodeRef, 
     parent_styles: Option<&Styles>, 
     context: &mut ParsingContext<State>,
 ) -> Box<dyn Element> {
     let mut container = Container::new();
-    let attributes = elem_data.attributes.borrow();
-
-    let should_add_to_dom = directive_parser::parse_if_expression(context, &attributes);
-    if should_add_to_dom.is_err() {
-        println!("Error parsing @if directive: {:?}", should_add_to_dom.err());
-        return Box::new(container) // TODO: Report error
-    }
-    if !should_add_to_dom.unwrap() {
-        return Box::new(container)
-    }
 
-    let styles = css_parser::parse_styles(&attributes, parent_styles, &context.stylesheet);
+    let styles = parse_element_styles::<State>(node, parent_styles, context);
     container.set_styles(styles);
+    container.set_style_context(create_style_context(context));
 
-    node.children()
-        .filter_map(|child| html_parser::map_dom_to_elements::<State>(&child, Some(&styles), context))
+    html_parser::map_children_to_elements::<State>(node, Some(&styles), context)
+        .into_iter()
         .for_each(|child_element| container.add_child(child_element));
 
     Box::new(container)
 }
 
 fn process_button_element<State : ComponentState>(
-    elem_data: &kuchiki::ElementData, 
     node: &NodeRef, 
     parent_styles: Option<&Styles>, 
     context: &mut ParsingContext<State>,
 ) -> Box<dyn Element> {
-    let attributes = elem_data.attributes.borrow();
-    let styles = css_parser::parse_styles(&attributes, parent_styles, &context.stylesheet);
-
-    let on_click_handler_name = directive_parser::parse_on_click_attribute(&attributes, context);
+    let styles = parse_element_styles::<State>(node, parent_styles, context);
 
-    let mut button = Button::new(on_click_handler_name, None, Some(styles));
+    let mut button = Button::new(None, Some(styles));
+    button.set_style_context(create_style_context(context));
 
     let mut child_container = Container::new();
-    node.children()
-        .filter_map(|child| html_parser::map_dom_to_elements::<State>(&child, Some(&styles), context))
+    html_parser::map_children_to_elements::<State>(node, Some(&styles), context)
+        .into_iter()
         .for_each(|child_element| {
             child_container.add_child(child_element);
             child_container.set_styles(styles.clone());
@@ -79,43 +142,129 @@ fn process_button_element<State : ComponentState>(
 }
 
 fn process_image_element<State : ComponentState>(
-    elem_data: &kuchiki::ElementData, 
-    _: &NodeRef, 
+    attributes: &Attributes, 
+    node: &NodeRef, 
     parent_styles: Option<&Styles>, 
     context: &mut ParsingContext<State>,
 ) -> Option<Box<dyn Element>> {
-    let attributes = elem_data.attributes.borrow();
     let src = attributes.get("src").unwrap_or_default();
-    let styles = css_parser::parse_styles(&attributes, parent_styles, &context.stylesheet);
+    let styles = parse_element_styles::<State>(node, parent_styles, context);
 
     let relative_path = context.angust_config.clone().unwrap_or_default().pathing_config.assets_dir_path.to_string() + "/img";
-    let image = Image::new(
+    let mut image = Image::new(
         relative_path, src.to_string(), Some(styles)
     );
+    image.set_style_context(create_style_context(context));
     Some(Box::new(image))
 }
 
+/*
+ * Renders the elements projected by the parent component into the slot, e.g. <ng-content select=".title"></ng-content>.
+ */
+fn process_content_slot<State : ComponentState>(
+    attributes: &Attributes, 
+    node: &NodeRef, 
+    parent_styles: Option<&Styles>, 
+    context: &mut ParsingContext<State>,
+) -> Box<dyn Element> {
+    let mut container = Container::new();
+
+    let styles = parse_element_styles::<State>(node, parent_styles, context);
+    container.set_styles(styles);
+    container.set_style_context(create_style_context(context));
+
+    let selector = attributes.get("select").map(|selector| selector.trim()).unwrap_or(DEFAULT_SLOT);
+    context.take_projected_elements(selector, container.get_id())
+        .into_iter()
+        .for_each(|projected_element| container.add_child(projected_element));
+
+    Box::new(container)
+}
+
 fn process_custom_component<State : ComponentState>(
     component_name: &str, 
-    elem_data: &kuchiki::ElementData, 
+    compiled_element: &CompiledElement, 
     node: &NodeRef, 
     parent_styles: Option<&Styles>, 
     context: &mut ParsingContext<State>,
 ) -> Option<Box<dyn Element>> {
     let skippable_elements = vec!["!DOCTYPE", "html", "head", "meta", "body", "title", "h1"]; // To be implemented in the future
     if skippable_elements.contains(&component_name) {
+        parse_element_custom_properties::<State>(node, context);
         return html_parser::general_traversal::<State>(node, parent_styles, context)
     }
     
-    let attributes = elem_data.attributes.borrow();
-    let styles = css_parser::parse_styles(&attributes, parent_styles, &context.stylesheet);
+    let styles = parse_element_styles::<State>(node, parent_styles, context);
 
-    if let Some(mut component_box) = create_component(component_name) {
+    // The component rendered from the tag is reused when the template is rendered again, keeping its state
+    let (reused_component, template_key) = context.take_child_component(node);
+    if let Some(mut component_box) = reused_component.or_else(|| create_component(component_name)) {
         component_box.set_styles(styles);
+        component_box.set_style_context(create_style_context(context));
+
+        // The children of the tag are parsed against the parent and projected into the slots of the component
+        let projected_elements = html_parser::map_children_to_projected_elements::<State>(node, Some(&styles), context);
+        component_box.set_projected_content(projected_elements);
+
+        // Inputs are evaluated against the parent, which sets them again each time its template is rendered
+        match binding_parser::parse_component_inputs(compiled_element, context) {
+            Ok(inputs) => component_box.set_inputs(inputs),
+            Err(er) => context.add_diagnostic(node, Severity::Error, format!("Error parsing inputs of component {}: {}", component_name, er)),
+        }
+        let template_patch_impact = component_box.load_outdated_template();
+        context.child_components_patch_impact = context.child_components_patch_impact.max(template_patch_impact);
+        match binding_parser::parse_component_output_bindings(compiled_element, &component_box.get_id(), context) {
+            Ok(output_bindings) => {
+                let declared_outputs = component_box.get_outputs();
+                for binding in output_bindings {
+                    match declared_outputs.contains(&binding.output_name) {
+                        true => context.add_output_binding(binding),
+                        false => context.add_diagnostic(node, Severity::Error, format!("Output '{}' is not declared by component {}", binding.output_name, component_name)),
+                    }
+                }
+            },
+            Err(er) => context.add_diagnostic(node, Severity::Error, format!("Error parsing outputs of component {}: {}", component_name, er)),
+        }
+        if let Some(template_key) = template_key {
+            context.register_child_component(component_box.get_id(), template_key);
+        }
         Some(component_box)
     } else {
-        // Continue processing children (To be reported as an error in the future)
-        println!("Component not found: {}", component_name);
+        // Continue processing children
+        context.add_diagnostic(node, Severity::Error, format!("Component not found: {}", component_name));
         return html_parser::general_traversal::<State>(node, Some(&styles), context)
     }
-}
\ No newline at end of file
+}
+
+// Invalid style declarations are reported as warnings, located at the element
+fn parse_element_styles<State : ComponentState>(
+    node: &NodeRef, 
+    parent_styles: Option<&Styles>, 
+    context: &mut ParsingContext<State>,
+) -> Styles {
+    let mut warnings = Vec::new();
+    let styles = css_parser::parse_styles(&context.element_path, parent_styles, &context.stylesheet, &mut warnings);
+
+    for warning in warnings {
+        context.add_diagnostic(node, Severity::Warning, warning);
+    }
+    if let Some(styled_element) = context.element_path.last() {
+        styled_element.set_styles(styles);
+    }
+    styles
+}
+
+// Custom properties of the elements passing the styles of their parent through, e.g. :root or body declaring them
+fn parse_element_custom_properties<State : ComponentState>(node: &NodeRef, context: &mut ParsingContext<State>) {
+    let mut warnings = Vec::new();
+    css_parser::parse_custom_properties(&context.element_path, &context.stylesheet, &mut warnings);
+
+    for warning in warnings {
+        context.add_diagnostic(node, Severity::Warning, warning);
+    }
+}
+
+// Lets the element be restyled when its interaction state or the one of its ancestors changes
+fn create_style_context<State : ComponentState>(context: &ParsingContext<State>) -> StyleContext {
+    StyleContext::new(&context.element_path, &context.stylesheet)
+}
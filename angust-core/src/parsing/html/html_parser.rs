@@ -1,3 +1,5 @@
+use std::{rc::Rc, sync::Arc};
+
 use kuchiki::parse_html;
 use kuchiki::NodeData;
 use kuchiki::NodeRef;
@@ -5,16 +7,26 @@ use kuchiki::traits::TendrilSink;
 
 use crate::application::angust_configuration::AngustConfiguration;
 use crate::parsing::css::css_parser::merge_styles;
+use crate::parsing::css::selector_matcher::StyledElement;
+use crate::parsing::css::style_context::{RestyleImpact, StyleContext};
 use crate::parsing::css::stylesheet_parser::Stylesheet;
+use crate::parsing::diagnostics::Severity;
 use crate::parsing::expression::ast::ASTNode;
+use crate::parsing::expression::evaluation_scope::EvaluationScope;
+use crate::rendering::elements::component::component_outputs::OutputBinding;
+use crate::rendering::elements::component::child_components::ChildComponents;
 use crate::rendering::elements::component::component_state::ComponentState;
+use crate::rendering::elements::component::content_projection::{ContentProjection, ProjectedElement};
 use crate::rendering::elements::component::functions::component_functions::ComponentFunctions;
 use crate::rendering::elements::element::Element;
+use crate::rendering::elements::element_patcher::TemplateKey;
 use crate::rendering::elements::styles::Styles;
 use crate::rendering::elements::text::Text;
 
+use super::compiled_nodes::{CompiledNode, CompiledNodes};
 use super::directive_parser;
 use super::element_parser;
+use super::template_diagnostics::TemplateDiagnostics;
 
 
 pub fn parse_html_content(html: &str) -> NodeRef {
@@ -31,16 +43,101 @@ pub fn map_dom_to_elements<State : ComponentState>(
 ) -> Option<Box<dyn Element>> {
     match dom.data() {
         NodeData::Document(_) | NodeData::Doctype(_) => process_document_nodes::<State>(dom, parent_styles, context),
-        NodeData::Element(ref elem_data) => {
-            element_parser::dispatch_element_processing::<State>(elem_data, dom, parent_styles, context)
+        NodeData::Element(ref elem_data) => match context.compiled_node(dom).as_ref() {
+            CompiledNode::Element(compiled_element) => {
+                element_parser::dispatch_element_processing::<State>(elem_data, compiled_element, dom, parent_styles, context)
+            },
+            _ => None,
         },
         NodeData::Text(ref text) => {
-            process_text_element::<State>(&text.borrow(), parent_styles, context)
+            process_text_element::<State>(&text.borrow(), dom, parent_styles, context)
         },
         _ => general_traversal::<State>(dom, parent_styles, context),
     }
 }
 
+/*
+ * Function that maps the children of a node into sibling elements,
+ * expanding the structural directives that can produce several elements (@for).
+ */
+pub fn map_children_to_elements<State : ComponentState>(
+    node: &NodeRef, 
+    parent_styles: Option<&Styles>, 
+    context: &mut ParsingContext<State>,
+) -> Vec<Box<dyn Element>> {
+    map_children_to_projected_elements::<State>(node, parent_styles, context)
+        .into_iter()
+        .map(|projected_element| projected_element.element)
+        .collect()
+}
+
+/*
+ * Same as map_children_to_elements, keeping the DOM node each element was created from,
+ * which is needed to project the children of a custom component into its slots.
+ */
+pub fn map_children_to_projected_elements<State : ComponentState>(
+    node: &NodeRef, 
+    parent_styles: Option<&Styles>, 
+    context: &mut ParsingContext<State>,
+) -> Vec<ProjectedElement> {
+    let mut elements = Vec::new();
+    let outer_conditional_chain = context.conditional_chain.take();
+
+    for child in node.children() {
+        if !continues_conditional_chain(&child) {
+            context.conditional_chain = None;
+        }
+
+        let compiled_child = context.compiled_node(&child);
+        let for_iterations = match compiled_child.as_element().and_then(|compiled_element| compiled_element.for_directive.as_ref()) {
+            Some(for_directive) => directive_parser::parse_for_expression(context, for_directive).map(Some),
+            None => Ok(None),
+        };
+
+        // The elements are keyed by their node and @for item, for the element tree to be patched with them
+        match for_iterations {
+            Ok(Some(iterations)) => {
+                for iteration in iterations {
+                    context.scope.push_frame(iteration.variables);
+                    context.for_item_keys.push(iteration.item_key.clone());
+                    let element = map_dom_to_elements::<State>(&child, parent_styles, context);
+                    context.for_item_keys.pop();
+                    context.scope.pop_frame();
+
+                    elements.extend(element.map(|mut element| {
+                        element.set_template_key(TemplateKey::new(&child, Some(iteration.item_key)));
+                        ProjectedElement { node: child.clone(), element }
+                    }));
+                }
+            },
+            Ok(None) => {
+                let element = map_dom_to_elements::<State>(&child, parent_styles, context);
+                elements.extend(element.map(|mut element| {
+                    element.set_template_key(TemplateKey::new(&child, None));
+                    ProjectedElement { node: child.clone(), element }
+                }));
+            },
+            Err(er) => context.add_diagnostic(&child, Severity::Error, format!("Error parsing @for directive: {}", er)),
+        }
+    }
+
+    context.conditional_chain = outer_conditional_chain;
+    elements
+}
+
+// @else-if and @else must follow their @if directly, ignoring whitespace and comments
+fn continues_conditional_chain(node: &NodeRef) -> bool {
+    match node.data() {
+        NodeData::Element(ref elem_data) => {
+            let attributes = elem_data.attributes.borrow();
+            attributes.contains("@else-if") || attributes.contains("@else")
+        },
+        NodeData::Text(ref text) => text.borrow().trim().is_empty(),
+        NodeData::Comment(_) => true,
+        _ => false,
+    }
+}
+
 fn process_document_nodes<State : ComponentState>(
     node: &NodeRef, 
     parent_styles: Option<&Styles>, 
@@ -54,6 +151,7 @@ fn process_document_nodes<State : ComponentState>(
 
 fn process_text_element<State : ComponentState>(
     text: &str,
+    node: &NodeRef,
     parent_styles: Option<&Styles>,
     context: &mut ParsingContext<State>,
 ) -> Option<Box<dyn Element>> {
@@ -63,13 +161,13 @@ fn process_text_element<State : ComponentState>(
     }
 
     // Apply state placeholders
-    let final_text = match context.component_state {
-        Some(state) => directive_parser::parse_state_placeholder(trimmed_text, state)
+    let final_text = match (context.component_state, context.compiled_node(node).as_ref()) {
+        (Some(_), CompiledNode::Text(segments)) => directive_parser::evaluate_text_segments(segments, context)
             .unwrap_or_else(|er| {
-                println!("Error parsing state placeholders in text element: {}", er);
+                context.add_diagnostic(node, Severity::Error, format!("Error parsing state placeholders in text element: {}", er));
                 trimmed_text.to_string()
             }),
-        None => trimmed_text.to_string(),
+        _ => trimmed_text.to_string(),
     };
 
     let mut text_element = Text::new(final_text);
@@ -78,6 +176,7 @@ fn process_text_element<State : ComponentState>(
         merge_styles(styles, &mut element_styles);
         text_element.set_styles(element_styles);
     }
+    text_element.set_style_context(StyleContext::for_text(&context.element_path, &context.stylesheet));
     Some(Box::new(text_element))
 }
 
@@ -88,13 +187,11 @@ pub fn general_traversal<State : ComponentState>(
 ) -> Option<Box<dyn Element>> {
     let mut root_element: Option<Box<dyn Element>> = None;
 
-    for child in node.children() {
-        if let Some(element) = map_dom_to_elements::<State>(&child, parent_styles, context) {
-            if root_element.is_none() {
-                root_element = Some(element);
-            } else {
-                root_element.as_mut().unwrap().add_child(element);
-            }
+    for element in map_children_to_elements::<State>(node, parent_styles, context) {
+        if root_element.is_none() {
+            root_element = Some(element);
+        } else {
+            root_element.as_mut().unwrap().add_child(element);
         }
     }
 
@@ -103,10 +200,23 @@ pub fn general_traversal<State : ComponentState>(
 
 pub struct ParsingContext<'a, State : ComponentState> {
     pub angust_config: Option<AngustConfiguration>,
-    pub stylesheet: Option<Stylesheet>,
+    pub stylesheet: Option<Arc<Stylesheet>>,
     pub component_state: Option<&'a State>,
     pub component_functions: Option<&'a ComponentFunctions<State>>,
     pub template_expressions_asts: Option<&'a mut Vec<ASTNode>>,
+    pub has_undeclared_dependencies: bool, // Whether the template reads properties not recorded in its expressions, e.g. through an array getter
+    pub child_components_patch_impact: RestyleImpact, // Of the templates of the child components rendered again along with the template
+    pub output_bindings: Option<&'a mut Vec<OutputBinding>>,
+    pub content_projection: Option<&'a mut ContentProjection>,
+    pub child_components: Option<&'a mut ChildComponents>,
+    pub component_id: Option<String>, // ID of the component whose template is parsed
+    pub scope: EvaluationScope,
+    pub conditional_chain: Option<bool>, // Whether a branch of the open @if chain was taken, None if no chain is open
+    pub for_item_keys: Vec<String>, // Keys of the items of the @for loops being rendered, from the outermost loop
+    pub element_path: Vec<Rc<StyledElement>>, // Elements being parsed, from the root of the template to the current element
+    pub host_element: Option<Rc<StyledElement>>, // Tag of the component whose template is parsed, see StyledElement::host
+    pub compiled_nodes: Option<Rc<CompiledNodes>>, // None for the templates that are not cached, whose nodes are compiled as they are parsed
+    pub diagnostics: TemplateDiagnostics,
 }
 
 impl<'a, State : ComponentState> Default for ParsingContext<'a, State> {
@@ -117,6 +227,19 @@ impl<'a, State : ComponentState> Default for ParsingContext<'a, State> {
             component_state: None,
             component_functions: None,
             template_expressions_asts: None,
+            has_undeclared_dependencies: false,
+            child_components_patch_impact: RestyleImpact::None,
+            output_bindings: None,
+            content_projection: None,
+            child_components: None,
+            component_id: None,
+            scope: EvaluationScope::new(),
+            conditional_chain: None,
+            for_item_keys: Vec::new(),
+            element_path: Vec::new(),
+            host_element: None,
+            compiled_nodes: None,
+            diagnostics: TemplateDiagnostics::default(),
         }
     }
 }
@@ -124,7 +247,7 @@ impl<'a, State : ComponentState> Default for ParsingContext<'a, State> {
 impl<'a, State : ComponentState> ParsingContext<'a, State> {
     pub fn new(
         angust_config: Option<AngustConfiguration>,
-        stylesheet: Option<Stylesheet>,
+        stylesheet: Option<Arc<Stylesheet>>,
         component_state: Option<&'a State>,
         component_functions: Option<&'a ComponentFunctions<State>>,
         template_expressions_asts: Option<&'a mut Vec<ASTNode>>
@@ -135,6 +258,19 @@ impl<'a, State : ComponentState> ParsingContext<'a, State> {
             component_state,
             component_functions,
             template_expressions_asts,
+            has_undeclared_dependencies: false,
+            child_components_patch_impact: RestyleImpact::None,
+            output_bindings: None,
+            content_projection: None,
+            child_components: None,
+            component_id: None,
+            scope: EvaluationScope::new(),
+            conditional_chain: None,
+            for_item_keys: Vec::new(),
+            element_path: Vec::new(),
+            host_element: None,
+            compiled_nodes: None,
+            diagnostics: TemplateDiagnostics::default(),
         }
     }
 
@@ -143,4 +279,46 @@ impl<'a, State : ComponentState> ParsingContext<'a, State> {
             template_expressions_asts.push(ast);
         }
     }
+
+    pub fn compiled_node(&self, node: &NodeRef) -> Rc<CompiledNode> {
+        self.compiled_nodes.as_ref()
+            .and_then(|compiled_nodes| compiled_nodes.get(node))
+            .unwrap_or_else(|| Rc::new(CompiledNode::compile(node, &self.stylesheet)))
+    }
+
+    pub fn add_diagnostic(&mut self, node: &NodeRef, severity: Severity, message: String) {
+        self.diagnostics.add(node, severity, message);
+    }
+
+    pub fn add_output_binding(&mut self, output_binding: OutputBinding) {
+        if let Some(output_bindings) = &mut self.output_bindings {
+            output_bindings.push(output_binding);
+        }
+    }
+
+    /*
+     * Returns the child component rendered from the tag at its previous rendering, if any, along with its template key.
+     */
+    pub fn take_child_component(&mut self, node: &NodeRef) -> (Option<Box<dyn Element>>, Option<String>) {
+        match &mut self.child_components {
+            Some(child_components) => {
+                let template_key = child_components.next_template_key(node, &self.for_item_keys);
+                (child_components.take_detached_component(&template_key), Some(template_key))
+            },
+            None => (None, None),
+        }
+    }
+
+    pub fn register_child_component(&mut self, component_id: String, template_key: String) {
+        if let Some(child_components) = &mut self.child_components {
+            child_components.register(component_id, template_key);
+        }
+    }
+
+    pub fn take_projected_elements(&mut self, selector: &str, container_id: String) -> Vec<Box<dyn Element>> {
+        match &mut self.content_projection {
+            Some(content_projection) => content_projection.take_slot_elements(selector, container_id),
+            None => vec![],
+        }
+    }
 }
\ No newline at end of file
@@ -1,3 +1,6 @@
 pub mod html_parser;
+pub mod template_diagnostics;
+pub mod compiled_nodes;
 mod element_parser;
-mod directive_parser;
\ No newline at end of file
+mod directive_parser;
+mod binding_parser;
\ No newline at end of file
@@ -0,0 +1,116 @@
+use std::{collections::HashMap, rc::Rc};
+
+use kuchiki::{Node, NodeRef};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::parsing::diagnostics::{self, Diagnostic, Severity, SourceLocation};
+
+
+/*
+ * Locations of the elements of a parsed template in its source, which the DOM does not keep.
+ * The start tags are scanned in the source and matched in order with the elements of the DOM;
+ * elements added by the HTML parser (e.g. <html> and <body>) match no tag and have no location.
+ */
+#[derive(Debug, Default)]
+pub struct SourceLocations {
+    element_locations: HashMap<*const Node, SourceLocation>,
+}
+
+impl SourceLocations {
+    pub fn compute(source: &str, dom: &NodeRef) -> Self {
+        let start_tags = scan_start_tags(source);
+        let mut element_locations = HashMap::new();
+        let mut next_tag_index = 0;
+
+        for node in dom.inclusive_descendants() {
+            let element_name = match node.as_element() {
+                Some(elem_data) => elem_data.name.local.to_string(),
+                None => continue,
+            };
+
+            let matching_tag_offset = start_tags[next_tag_index..].iter()
+                .position(|(tag_name, _)| *tag_name == element_name);
+            if let Some(offset) = matching_tag_offset {
+                element_locations.insert(Rc::as_ptr(&node.0), start_tags[next_tag_index + offset].1);
+                next_tag_index += offset + 1;
+            }
+        }
+
+        SourceLocations { element_locations }
+    }
+
+    // Location of the element, or of its closest located ancestor for text nodes and added elements
+    pub fn locate(&self, node: &NodeRef) -> Option<SourceLocation> {
+        node.inclusive_ancestors()
+            .find_map(|ancestor| self.element_locations.get(&Rc::as_ptr(&ancestor.0)).copied())
+    }
+}
+
+// Comments are matched so that the tags they contain are skipped
+static START_TAG_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<!--.*?-->|<([A-Za-z][A-Za-z0-9-]*)").unwrap());
+
+fn scan_start_tags(source: &str) -> Vec<(String, SourceLocation)> {
+    let mut start_tags = Vec::new();
+    let mut location = SourceLocation { line: 1, column: 1 };
+    let mut scanned_offset = 0;
+
+    for captures in START_TAG_REGEX.captures_iter(source) {
+        let tag_name = match captures.get(1) {
+            Some(tag_name) => tag_name,
+            None => continue,
+        };
+        let tag_offset = tag_name.start() - 1; // Offset of the '<'
+
+        for character in source[scanned_offset..tag_offset].chars() {
+            if character == '\n' {
+                location = SourceLocation { line: location.line + 1, column: 1 };
+            } else {
+                location.column += 1;
+            }
+        }
+        scanned_offset = tag_offset;
+
+        start_tags.push((tag_name.as_str().to_ascii_lowercase(), location));
+    }
+
+    start_tags
+}
+
+/*
+ * Diagnostics collected while mapping a template to elements, located with the DOM node they were found on.
+ * They are reported to the diagnostic sink once the template is mapped, see diagnostics::report.
+ * The same diagnostic is only added once, e.g. for an element repeated by @for.
+ */
+#[derive(Debug, Default)]
+pub struct TemplateDiagnostics {
+    template_path: Option<String>,
+    source_locations: Option<Rc<SourceLocations>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl TemplateDiagnostics {
+    pub fn new(template_path: String, source_locations: Rc<SourceLocations>) -> Self {
+        TemplateDiagnostics {
+            template_path: Some(template_path),
+            source_locations: Some(source_locations),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, node: &NodeRef, severity: Severity, message: String) {
+        let location = self.source_locations.as_ref().and_then(|locations| locations.locate(node));
+        let diagnostic = Diagnostic::new(severity, message).at(self.template_path.clone(), location);
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn flush(&mut self) {
+        diagnostics::report_all(std::mem::take(&mut self.diagnostics));
+    }
+}
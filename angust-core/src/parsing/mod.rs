@@ -1,3 +1,4 @@
 pub mod html;
 pub mod css;
-pub mod expression;
\ No newline at end of file
+pub mod expression;
+pub mod diagnostics;
\ No newline at end of file
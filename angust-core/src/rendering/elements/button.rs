@@ -1,25 +1,29 @@
+use std::{any::Any, collections::HashMap};
+
 use skia_safe::{Canvas, Color, Point};
 
-use crate::rendering::{layout::effective_size_estimator, rendering_interface::element_renderer::ElementRenderer};
+use crate::{parsing::{css::style_context::{self, RestyleImpact, StyleContext}, expression::value::Value}, rendering::{layout::effective_size_estimator, rendering_interface::element_renderer::ElementRenderer}};
 
-use super::{common_types::{OptionalSize, Position, Size}, container::Container, element::{Element, ElementType, EventType}, element_id_generator::IDGenerator, styles::Styles};
+use super::{common_types::{OptionalSize, Position, Size}, component::{component_outputs::OutputEvent, content_projection::ProjectedElement, functions::event_handler_call::EventHandlerCall}, container::Container, element::{Element, ElementType, EventType}, element_event_handlers::ElementEventHandlers, element_id_generator::IDGenerator, element_patcher::{self, TemplateKey}, event_payload::EventPayload, event_propagator, styles::Styles};
 
 
 pub struct Button {
     _id: String,
+    template_key: Option<TemplateKey>,
 
     container: Option<Vec<Box<dyn Element>>>, // Only one container is allowed
-    pub on_click_handler_name: Option<String>,
+    event_handlers: ElementEventHandlers,
 
     position: Position,
     size: Size,
     styles: Styles,
     natural_size: Size,
     requested_size: OptionalSize,
+    style_context: Option<StyleContext>,
 }
 
 impl Button {
-    pub fn new(on_click_handler_name: Option<String>, container: Option<Container>, styles: Option<Styles>) -> Self {
+    pub fn new(container: Option<Container>, styles: Option<Styles>) -> Self {
         let id = IDGenerator::get();
 
         let container_vec = if let Some(container_child) = container {
@@ -31,12 +35,14 @@ impl Button {
         Self {
             _id: id,
             container: container_vec,
-            on_click_handler_name,
+            event_handlers: ElementEventHandlers::default(),
             position: Position::default(),
             size: Size::default(),
             styles: styles.unwrap_or_default(),
             natural_size: Size::default(),
             requested_size: OptionalSize::default(),
+            style_context: None,
+            template_key: None,
         }
     }
     
@@ -82,26 +88,24 @@ impl Element for Button {
 
     fn update(&mut self) {}
 
-    fn handle_event(&mut self, _: Point, _: &EventType) {
-        
-    }
-
-    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType) -> Vec<String> {
-        let mut event_targets = Vec::new();
+    fn handle_event(&mut self, cursor_position: Point, event_type: &EventType) {
+        if let Some(style_context) = &self.style_context {
+            let is_within_bounds = self.position_within_bounds(cursor_position);
+            style_context.update_interaction_state(is_within_bounds, event_type, self.event_handlers.is_focusable());
+        }
 
-        // Check if the cursor_position is within the bounds of the button
-        if self.position_within_bounds(cursor_position) {
-            if let Some(handler_name) = &self.on_click_handler_name {
-                match event_type {
-                    EventType::MouseClick => {
-                        event_targets.push(handler_name.clone());
-                    },
-                    _ => {}
-                }
+        if let Some(child_container) = self.container.as_mut() {
+            for child_element in child_container {
+                child_element.handle_event(cursor_position, event_type);
             }
         }
+    }
+
+    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType, event_payload: &EventPayload) -> Vec<EventHandlerCall> {
+        let mut event_targets = event_propagator::propagate_event(self, cursor_position, event_type, event_payload);
 
-        // TODO: Propagate to children as well
+        let is_within_bounds = self.position_within_bounds(cursor_position);
+        event_targets.extend(self.event_handlers.collect_handler_calls(is_within_bounds, event_type));
 
         event_targets
     }
@@ -123,6 +127,16 @@ impl Element for Button {
         self.styles = styles;
     }
 
+    fn set_event_handlers(&mut self, event_handlers: ElementEventHandlers) {
+        self.event_handlers = event_handlers;
+    }
+
+    fn set_inputs(&mut self, _: HashMap<String, Value>) {}
+
+    fn set_projected_content(&mut self, _: Vec<ProjectedElement>) {}
+
+    fn load_outdated_template(&mut self) -> RestyleImpact { RestyleImpact::None }
+
     fn add_child(&mut self, container: Box<dyn Element>) {
         // Only one container is allowed
         if self.container.is_some() {
@@ -136,6 +150,18 @@ impl Element for Button {
         self._id.clone()
     }
 
+    fn set_template_key(&mut self, template_key: TemplateKey) {
+        self.template_key = Some(template_key);
+    }
+
+    fn get_template_key(&self) -> Option<TemplateKey> {
+        self.template_key.clone()
+    }
+
+    fn get_outputs(&self) -> Vec<String> {
+        vec![]
+    }
+
     fn get_element_type(&self) -> ElementType {
         ElementType::Button
     }
@@ -224,14 +250,73 @@ impl Element for Button {
     }
 
     // Reactivity
-    fn react_to_state_change(&mut self, component_id: String) {
+    fn react_to_state_change(&mut self, component_id: String, property_name: &str) -> RestyleImpact {
         if let Some(child_container) = self.get_children_mut() {
             if child_container.len() != 1 {
-                return;
+                return RestyleImpact::None;
             }
             if let Some(child_element) = child_container.get_mut(0) {
-                child_element.react_to_state_change(component_id);
+                return child_element.react_to_state_change(component_id, property_name);
+            }
+        }
+        RestyleImpact::None
+    }
+
+    fn react_to_output(&mut self, output_event: &mut OutputEvent) {
+        if let Some(child_container) = self.get_children_mut() {
+            if let Some(child_element) = child_container.get_mut(0) {
+                child_element.react_to_output(output_event);
+            }
+        }
+    }
+
+    fn patch(&mut self, new_element: Box<dyn Element>) -> RestyleImpact {
+        match new_element.into_any().downcast::<Button>() {
+            Ok(new_button) => {
+                let new_button = *new_button;
+                let restyle_impact = RestyleImpact::between(&self.styles, &new_button.styles);
+                self._id = new_button._id;
+                self.template_key = new_button.template_key;
+                self.styles = new_button.styles;
+                self.event_handlers.update_handlers(new_button.event_handlers);
+                style_context::replace_style_context(&mut self.style_context, new_button.style_context);
+                let children_patch_impact = match (self.container.as_mut(), new_button.container) {
+                    (Some(container), Some(new_container)) => element_patcher::patch_children(container, new_container),
+                    (_, new_container) => {
+                        self.container = new_container;
+                        RestyleImpact::Layout
+                    },
+                };
+                restyle_impact.max(children_patch_impact)
+            },
+            Err(_) => RestyleImpact::None,
+        }
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> { self }
+
+    // Interactive styles
+    fn set_style_context(&mut self, style_context: StyleContext) {
+        self.style_context = Some(style_context);
+    }
+
+    // The container of the content takes the styles of the button, as when it is parsed
+    fn restyle(&mut self) -> RestyleImpact {
+        let restyled_styles = self.style_context.as_mut().and_then(StyleContext::restyle);
+        let mut restyle_impact = RestyleImpact::None;
+        if let Some(styles) = restyled_styles {
+            restyle_impact = RestyleImpact::between(&self.styles, &styles);
+            self.styles = styles;
+        }
+
+        if let Some(child_container) = self.container.as_mut() {
+            for child_element in child_container {
+                if let Some(styles) = restyled_styles {
+                    child_element.set_styles(styles);
+                }
+                restyle_impact = restyle_impact.max(child_element.restyle());
             }
         }
+        restyle_impact
     }
 }
\ No newline at end of file
@@ -0,0 +1,79 @@
+use std::{collections::HashMap, rc::Rc};
+
+use kuchiki::NodeRef;
+
+use crate::rendering::elements::{container::Container, element::{Element, ElementType}};
+
+
+/*
+ * Child components created by a component template, keyed by their position in the template
+ * (the tag they come from and the keys of the items of the @for loops it is rendered in).
+ * When the template is rendered again, the children are detached from the previous element tree
+ * and reused at the same position, so that they keep their state.
+ */
+#[derive(Default)]
+pub struct ChildComponents {
+    template_keys: HashMap<String, String>, // Child component ID -> template key
+    tag_occurrences: HashMap<String, usize>,
+    detached_components: HashMap<String, Box<dyn Element>>,
+}
+
+impl ChildComponents {
+    /*
+     * Detaches the child components from the element tree before a new rendering of the template.
+     * The components not reused by the rendering are dropped afterwards.
+     */
+    pub fn detach_from(&mut self, content: &mut Box<dyn Element>) {
+        self.detached_components.clear();
+        self.tag_occurrences.clear();
+
+        if let Some(children) = content.get_children_mut() {
+            self.detach_children(children);
+        }
+        self.template_keys.clear();
+    }
+
+    fn detach_children(&mut self, children: &mut Vec<Box<dyn Element>>) {
+        for child in children.iter_mut() {
+            if child.get_element_type() != ElementType::CustomComponent {
+                if let Some(grandchildren) = child.get_children_mut() {
+                    self.detach_children(grandchildren);
+                }
+                continue;
+            }
+
+            // Components projected by the parent are not keyed, and are left in place
+            if let Some(template_key) = self.template_keys.get(&child.get_id()) {
+                let component = std::mem::replace(child, Box::new(Container::new()));
+
+                // The placeholder lets the component be patched in place when it is reused, see element_patcher::patch_element
+                child.set_id(component.get_id());
+                child.set_styles(component.get_styles());
+                if let Some(element_key) = component.get_template_key() {
+                    child.set_template_key(element_key);
+                }
+                self.detached_components.insert(template_key.clone(), component);
+            }
+        }
+    }
+
+    pub fn next_template_key(&mut self, node: &NodeRef, for_item_keys: &[String]) -> String {
+        let tag_key = format!("{:p}/{}", Rc::as_ptr(&node.0), for_item_keys.join("/"));
+        let occurrence = self.tag_occurrences.entry(tag_key.clone()).or_insert(0);
+        *occurrence += 1;
+
+        format!("{}#{}", tag_key, occurrence)
+    }
+
+    pub fn take_detached_component(&mut self, template_key: &str) -> Option<Box<dyn Element>> {
+        self.detached_components.remove(template_key)
+    }
+
+    pub fn register(&mut self, component_id: String, template_key: String) {
+        self.template_keys.insert(component_id, template_key);
+    }
+
+    pub fn release_unused(&mut self) {
+        self.detached_components.clear();
+    }
+}
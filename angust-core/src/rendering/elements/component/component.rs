@@ -1,20 +1,27 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{any::Any, cell::RefCell, collections::{HashMap, HashSet}, rc::Rc};
 
-use crate::{application::event_loop_proxy::get_event_loop_proxy, parsing::expression::ast::ASTNode, rendering::{elements::{
+use kuchiki::Attributes;
+
+use crate::{application::event_loop_proxy::get_event_loop_proxy, parsing::{css::{selector_matcher::StyledElement, style_context::{self, RestyleImpact, StyleContext}}, diagnostics::{self, Diagnostic}, expression::{ast::ASTNode, value::Value}}, rendering::{elements::{
     common_types::{OptionalSize, Position, Size}, 
     container::Container, 
     element::{Element, ElementType, EventType}, 
+    element_event_handlers::ElementEventHandlers, 
     element_id_generator::IDGenerator, 
+    element_patcher::TemplateKey, 
+    event_payload::EventPayload, 
     event_propagator, 
     styles::Styles
 }, layout::effective_size_estimator}};
 
-use super::{functions::component_functions::ComponentFunctions, component_state::ComponentState, reactivity::{ComponentEvent, EventQueue}, template_loader};
+use super::{child_components::ChildComponents, component_outputs::{with_emitting_component, OutputBinding, OutputEvent}, content_projection::{ContentProjection, ProjectedElement}, lifecycle_hooks::LifecycleHooks, functions::{component_functions::ComponentFunctions, event_handler_call::EventHandlerCall}, component_state::ComponentState, reactivity::{ComponentEvent, EventQueue}, template_cache::CompiledTemplate, template_loader};
 
-pub struct Component<State: ComponentState> {
+pub struct Component<State: ComponentState + 'static> {
     _id: String,
+    template_key: Option<TemplateKey>,
     pub name: String,
     pub template_relative_path: String,
+    pub template: Option<Rc<CompiledTemplate>>, // Compiled at the first rendering
     pub content: Box<dyn Element>,
 
     position: Position,
@@ -22,6 +29,9 @@ pub struct Component<State: ComponentState> {
     natural_size: Size,
     requested_size: OptionalSize,
     styles: Styles,
+    event_handlers: ElementEventHandlers, // Handlers of the parent component, attached to the component's tag
+    style_context: Option<StyleContext>, // Context of the component's tag in the template of the parent
+    pub host_element: Rc<StyledElement>, // Custom properties of the tag, inherited by the root of the template
 
     // User-defined properties
     pub state: State,
@@ -32,35 +42,73 @@ pub struct Component<State: ComponentState> {
 
     // Expression evaluation
     pub template_expressions_asts: Vec<ASTNode>,
+    pub template_dependencies: Option<HashSet<String>>, // Properties read by the template, None if unknown
+
+    // Outputs
+    pub outputs: Vec<String>,
+    pub output_bindings: Vec<OutputBinding>, // Bindings of the outputs of the child components
+
+    // Child components
+    pub child_components: ChildComponents,
+    pub content_projection: ContentProjection,
+
+    // Lifecycle
+    pub lifecycle_hooks: LifecycleHooks<State>,
+    is_view_initialized: bool,
+    is_template_outdated: bool, // Inputs or projected content changed since the template was rendered
 
 }
 
-impl<State: ComponentState> Component<State> {
+impl<State: ComponentState + 'static> Component<State> {
     pub fn new(name: String, template_relative_path: String, state: State) -> Self {
+        let host_element = Rc::new(StyledElement::new(&name, &Attributes { map: Default::default() }));
         Self {
             _id: IDGenerator::get(),
             name,
             template_relative_path,
+            template: None,
             content: Box::new(Container::new()),
             position: Position::default(),
             size: Size::default(),
             natural_size: Size::default(),
             requested_size: OptionalSize::default(),
             styles: Styles::default(),
+            event_handlers: ElementEventHandlers::default(),
+            style_context: None,
+            template_key: None,
+            host_element,
             state,
             component_functions: ComponentFunctions::default(),
             event_queue: Rc::new(RefCell::new(EventQueue::new())), 
-            template_expressions_asts: vec![]
+            template_expressions_asts: vec![],
+            template_dependencies: None,
+            outputs: vec![],
+            output_bindings: vec![],
+            child_components: ChildComponents::default(),
+            content_projection: ContentProjection::default(),
+            lifecycle_hooks: LifecycleHooks::default(),
+            is_view_initialized: false,
+            is_template_outdated: false,
         }
     }
 
+    // The template is first loaded once the parent set the style context, projected content and inputs of the component
     pub fn initialize(&mut self) {
         self.setup_listeners();
-        self.load_component_template();
+        self.run_lifecycle_hook(|hooks, state| if let Some(hook) = hooks.on_init.as_mut() { hook(state) });
+        self.is_template_outdated = true;
+    }
+
+    fn load_component_template(&mut self) -> RestyleImpact {
+        self.is_template_outdated = false;
+        template_loader::load_component_template(self)
     }
 
-    fn load_component_template(&mut self) {
-        template_loader::load_component_template(self);
+    // Returns whether the custom properties of the tag changed
+    fn update_host_custom_properties(&self) -> bool {
+        self.style_context.as_ref()
+            .and_then(StyleContext::custom_properties)
+            .is_some_and(|custom_properties| self.host_element.set_custom_properties(custom_properties))
     }
 
     fn setup_listeners(&mut self) {
@@ -80,12 +128,15 @@ impl<State: ComponentState> Component<State> {
             let component_id_clone = component_id.clone(); 
             let event_proxy_clone = event_proxy.clone();
 
+            let changed_property_name = property_name.clone();
+
             self.state.subscribe_to_property(&property_name_clone, move |event: &ComponentEvent| {
                 match event {
-                    ComponentEvent::StateChange(_) => {
-                        event_proxy_clone.send_event(ComponentEvent::StateChange(component_id_clone.clone()))
+                    ComponentEvent::StateChange(_, _) => {
+                        event_proxy_clone.send_event(ComponentEvent::StateChange(component_id_clone.clone(), changed_property_name.clone()))
                             .expect("Failed to send event");
-                    }
+                    },
+                    ComponentEvent::OutputEmitted(_) => {}
                 }
             });
         }
@@ -110,9 +161,87 @@ impl<State: ComponentState> Component<State> {
         }
 
     }
+
+    // Output names are case-insensitive, like the (output) attributes the parent binds them with
+    pub fn add_outputs(&mut self, outputs: Vec<&str>) {
+        self.outputs.extend(outputs.into_iter().map(|output| output.to_lowercase()));
+    }
+
+    pub fn set_on_init<F>(&mut self, hook: F)
+    where
+        F: 'static + FnMut(&mut State),
+    {
+        self.lifecycle_hooks.on_init = Some(Box::new(hook));
+    }
+
+    pub fn set_on_changes<F>(&mut self, hook: F)
+    where
+        F: 'static + FnMut(&mut State, &[String]),
+    {
+        self.lifecycle_hooks.on_changes = Some(Box::new(hook));
+    }
+
+    pub fn set_after_view_init<F>(&mut self, hook: F)
+    where
+        F: 'static + FnMut(&mut State),
+    {
+        self.lifecycle_hooks.after_view_init = Some(Box::new(hook));
+    }
+
+    pub fn set_on_destroy<F>(&mut self, hook: F)
+    where
+        F: 'static + FnMut(&mut State),
+    {
+        self.lifecycle_hooks.on_destroy = Some(Box::new(hook));
+    }
+
+    // Lifecycle (hooks can emit outputs, like event handlers)
+    fn run_lifecycle_hook(&mut self, run: impl FnOnce(&mut LifecycleHooks<State>, &mut State)) {
+        let (component_id, outputs) = (self._id.clone(), self.outputs.clone());
+        let hooks = &mut self.lifecycle_hooks;
+        let state = &mut self.state;
+
+        with_emitting_component(&component_id, &outputs, || run(hooks, state));
+    }
+
+    // Event handling, the handlers failing being reported as diagnostics of the template
+    fn run_event_handler(&mut self, handler_name: &str, args: &[Value], event_payload: &EventPayload) {
+        let (component_id, outputs) = (self._id.clone(), self.outputs.clone());
+        let functions = &mut self.component_functions;
+        let state = &mut self.state;
+
+        let result = with_emitting_component(&component_id, &outputs, || {
+            if let Some(handler) = functions.event_handlers.get_mut(handler_name) {
+                handler(state);
+                Ok(())
+            } else if let Some(handler) = functions.event_handlers_with_args.get_mut(handler_name) {
+                handler(state, args, event_payload).map_err(|er| format!("Error running event handler {}: {}", handler_name, er))
+            } else {
+                Err(format!("Event handler not found: {}", handler_name))
+            }
+        });
+
+        if let Err(er) = result {
+            diagnostics::report(Diagnostic::error(er).at(Some(self.template_relative_path.clone()), None));
+        }
+    }
+
+    fn run_output_handler(&mut self, output_binding: OutputBinding, payload: Option<Box<dyn Any + Send>>) {
+        let mut payload = payload.map(|payload| Value::from_boxed_any(payload as Box<dyn Any>));
+
+        let args: Vec<Value> = output_binding.handler_call.args.iter().enumerate()
+            .map(|(index, arg)| if Some(index) == output_binding.event_arg_index {
+                payload.take().unwrap_or(Value::Null)
+            } else {
+                arg.clone()
+            })
+            .collect();
+
+        self.run_event_handler(&output_binding.handler_call.handler_name, &args, &EventPayload::default());
+    }
 }
 
-impl<State: ComponentState> Element for Component<State> {
+impl<State: ComponentState + 'static> Element for Component<State> {
     
     fn render(&self, canvas: &skia_safe::Canvas) {
         self.content.render(canvas);
@@ -123,19 +252,30 @@ impl<State: ComponentState> Element for Component<State> {
     }
     
     fn handle_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType) {
+        if let Some(style_context) = &self.style_context {
+            let is_within_bounds = event_propagator::is_within_bounds(self, cursor_position);
+            style_context.update_interaction_state(is_within_bounds, event_type, self.event_handlers.is_focusable());
+        }
+
         self.content.handle_event(cursor_position, event_type);
     }
     
-    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType) -> Vec<String> {
-        let event_handler_names = event_propagator::propagate_event(self, cursor_position, event_type);
+    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType, event_payload: &EventPayload) -> Vec<EventHandlerCall> {
+        let event_handler_calls = event_propagator::propagate_event(self, cursor_position, event_type, event_payload);
         
-        for handler_name in event_handler_names.iter() {
-            if let Some(handler) = self.component_functions.event_handlers.get_mut(handler_name) {
-                handler(&mut self.state);
-            }
+        // Calls of projected content belong to the parent component, so they are passed on
+        let (own_handler_calls, mut parent_handler_calls): (Vec<EventHandlerCall>, Vec<EventHandlerCall>) = event_handler_calls
+            .into_iter()
+            .partition(|handler_call| handler_call.component_id.as_ref().map_or(true, |component_id| *component_id == self._id));
+
+        for handler_call in own_handler_calls.iter() {
+            self.run_event_handler(&handler_call.handler_name, &handler_call.args, event_payload);
         }
 
-        vec![]
+        // The handlers attached to the component's tag belong to the parent component
+        let is_within_bounds = event_propagator::is_within_bounds(self, cursor_position);
+        parent_handler_calls.extend(self.event_handlers.collect_handler_calls(is_within_bounds, event_type));
+        parent_handler_calls
     }
 
     fn add_child(&mut self, child: Box<dyn Element>) {
@@ -167,6 +307,48 @@ impl<State: ComponentState> Element for Component<State> {
         self.content.set_styles(styles);
     }
 
+    fn set_event_handlers(&mut self, event_handlers: ElementEventHandlers) {
+        self.event_handlers = event_handlers;
+    }
+
+    // The template is rendered again by load_outdated_template, once the projected content is set as well
+    fn set_inputs(&mut self, inputs: HashMap<String, Value>) {
+        let properties: Vec<String> = self.state.get_input_properties().into_iter().map(|s| s.to_string()).collect();
+        let mut changed_inputs = Vec::new();
+
+        for (property_name, value) in inputs {
+            if !properties.contains(&property_name) {
+                continue; // Not an input, e.g. an attribute of the tag
+            }
+            if self.state.get_property(&property_name).as_ref() == Some(&value) {
+                continue;
+            }
+
+            self.state.set_property(&property_name, value);
+            changed_inputs.push(property_name);
+        }
+
+        if !changed_inputs.is_empty() {
+            self.run_lifecycle_hook(|hooks, state| if let Some(hook) = hooks.on_changes.as_mut() { hook(state, &changed_inputs) });
+            self.is_template_outdated = true;
+        }
+    }
+
+    fn set_projected_content(&mut self, projected_elements: Vec<ProjectedElement>) {
+        if projected_elements.is_empty() && !self.content_projection.has_projected_elements() {
+            return;
+        }
+        self.content_projection.set_projected_elements(projected_elements);
+        self.is_template_outdated = true;
+    }
+
+    fn load_outdated_template(&mut self) -> RestyleImpact {
+        match self.is_template_outdated {
+            true => self.load_component_template(),
+            false => RestyleImpact::None,
+        }
+    }
+
     fn is_text_wrapper(&self) -> bool {
         false
     }
@@ -174,6 +356,18 @@ impl<State: ComponentState> Element for Component<State> {
     fn get_id(&self) -> String {
         self._id.clone()
     }
+
+    fn set_template_key(&mut self, template_key: TemplateKey) {
+        self.template_key = Some(template_key);
+    }
+
+    fn get_template_key(&self) -> Option<TemplateKey> {
+        self.template_key.clone()
+    }
+
+    fn get_outputs(&self) -> Vec<String> {
+        self.outputs.clone()
+    }
     
     fn get_element_type(&self) -> ElementType {
         ElementType::CustomComponent
@@ -213,14 +407,22 @@ impl<State: ComponentState> Element for Component<State> {
         self.set_natural_size(self.content.get_natural_size());
 
         let sizing_policy = self.get_styles().sizing_policy.unwrap_or_default();
-        self.set_requested_size(OptionalSize { width: sizing_policy.width, height: sizing_policy.height }); 
+        self.set_requested_size(effective_size_estimator::estimate_requested_size(&sizing_policy.width, &sizing_policy.height)); 
     }
 
     fn allocate_space(&mut self, allocated_position: Position, allocated_size: Size) {
+        self.position = allocated_position;
+        self.size = allocated_size;
+
         self.content.set_position(allocated_position);
         self.content.set_size(allocated_size);
 
         self.content.allocate_space(allocated_position, allocated_size);
+
+        if !self.is_view_initialized {
+            self.is_view_initialized = true;
+            self.run_lifecycle_hook(|hooks, state| if let Some(hook) = hooks.after_view_init.as_mut() { hook(state) });
+        }
     }
 
     fn layout(&mut self, allocated_position: Position, allocated_size: Size) {
@@ -229,9 +431,59 @@ impl<State: ComponentState> Element for Component<State> {
     }
 
     // Reactivity
-    fn react_to_state_change(&mut self, component_id: String) {
-        if component_id == self.get_id() {
-            self.load_component_template(); // Naive approach; to be replaced later
+    fn react_to_state_change(&mut self, component_id: String, property_name: &str) -> RestyleImpact {
+        if component_id != self._id {
+            return self.content.react_to_state_change(component_id, property_name);
+        }
+
+        let depends_on_property = self.template_dependencies.as_ref()
+            .is_none_or(|dependencies| dependencies.contains(property_name));
+        match depends_on_property {
+            true => self.load_component_template(),
+            false => RestyleImpact::None,
+        }
+    }
+
+    fn react_to_output(&mut self, output_event: &mut OutputEvent) {
+        let output_binding = self.output_bindings.iter()
+            .find(|binding| binding.child_component_id == output_event.component_id && binding.output_name == output_event.output_name)
+            .cloned();
+
+        match output_binding {
+            Some(output_binding) => self.run_output_handler(output_binding, output_event.payload.take()),
+            None => self.content.react_to_output(output_event),
         }
     }
-}
\ No newline at end of file
+
+    // Components are reused or replaced, never patched
+    fn patch(&mut self, _: Box<dyn Element>) -> RestyleImpact { RestyleImpact::None }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> { self }
+
+    // Interactive styles (a reused component keeps its interaction state when the parent template is rendered again)
+    fn set_style_context(&mut self, style_context: StyleContext) {
+        style_context::replace_style_context(&mut self.style_context, Some(style_context));
+
+        // The elements rendered with the previous custom properties of the tag are restyled
+        if self.update_host_custom_properties() && self.template.is_some() {
+            style_context::request_restyle();
+        }
+    }
+
+    fn restyle(&mut self) -> RestyleImpact {
+        let mut restyle_impact = RestyleImpact::None;
+        if let Some(styles) = self.style_context.as_mut().and_then(StyleContext::restyle) {
+            restyle_impact = RestyleImpact::between(&self.styles, &styles);
+            self.set_styles(styles);
+            self.update_host_custom_properties();
+        }
+
+        restyle_impact.max(self.content.restyle())
+    }
+}
+
+impl<State: ComponentState + 'static> Drop for Component<State> {
+    fn drop(&mut self) {
+        self.run_lifecycle_hook(|hooks, state| if let Some(hook) = hooks.on_destroy.as_mut() { hook(state) });
+    }
+}
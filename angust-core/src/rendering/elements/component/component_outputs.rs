@@ -0,0 +1,94 @@
+use std::{any::Any, cell::RefCell, fmt::{Debug, Formatter}};
+
+use crate::{application::event_loop_proxy::get_event_loop_proxy, parsing::diagnostics::{self, Diagnostic}};
+
+use super::{functions::event_handler_call::EventHandlerCall, reactivity::ComponentEvent};
+
+
+/*
+ * Outputs let a child component notify its parent: the child emits a declared output from its handlers,
+ * e.g. component_outputs::emit("saved", title), and the parent binds it in its template as (saved)="on_saved($event)".
+ * Emitted outputs are routed through the event loop proxy to the parent, whose handler receives the payload as $event.
+ */
+pub struct OutputEvent {
+    pub component_id: String,
+    pub output_name: String,
+    pub payload: Option<Box<dyn Any + Send>>,
+}
+
+impl Debug for OutputEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OutputEvent({}, {})", self.component_id, self.output_name)
+    }
+}
+
+// Binding of a child output to a handler of the parent, recorded while parsing the parent's template
+#[derive(Clone)]
+pub struct OutputBinding {
+    pub child_component_id: String,
+    pub output_name: String,
+    pub handler_call: EventHandlerCall,
+    pub event_arg_index: Option<usize>, // Position of $event in the handler arguments
+}
+
+struct EmittingComponent {
+    component_id: String,
+    outputs: Vec<String>,
+}
+
+thread_local! {
+    static EMITTING_COMPONENT: RefCell<Option<EmittingComponent>> = const { RefCell::new(None) };
+}
+
+/*
+ * Emits an output of the component whose handler is currently running.
+ * Output names are case-insensitive, as the HTML parser lowercases the (output) attributes binding them.
+ */
+pub fn emit<T: Any + Send>(output_name: &str, payload: T) {
+    let output_name = output_name.to_lowercase();
+    let component_id = EMITTING_COMPONENT.with(|emitting_component| {
+        match emitting_component.borrow().as_ref() {
+            Some(component) if component.outputs.contains(&output_name) => Ok(component.component_id.clone()),
+            Some(_) => Err(format!("Output '{}' is not declared by the component", output_name)),
+            None => Err(format!("Output '{}' emitted outside of a component handler", output_name)),
+        }
+    });
+    let component_id = match component_id {
+        Ok(component_id) => component_id,
+        Err(er) => {
+            diagnostics::report(Diagnostic::warning(format!("Error emitting output: {}", er)));
+            return;
+        }
+    };
+
+    let event_proxy = match get_event_loop_proxy() {
+        Some(event_proxy) => event_proxy,
+        None => {
+            diagnostics::report(Diagnostic::warning(format!("Output '{}' emitted before the application started", output_name)));
+            return;
+        }
+    };
+    let output_event = OutputEvent {
+        component_id,
+        output_name,
+        payload: Some(Box::new(payload)),
+    };
+    event_proxy.send_event(ComponentEvent::OutputEmitted(output_event))
+        .expect("Failed to send event");
+}
+
+pub(crate) fn with_emitting_component<R>(component_id: &str, outputs: &[String], f: impl FnOnce() -> R) -> R {
+    let previous_component = EMITTING_COMPONENT.with(|emitting_component| {
+        emitting_component.replace(Some(EmittingComponent {
+            component_id: component_id.to_string(),
+            outputs: outputs.to_vec(),
+        }))
+    });
+
+    let result = f();
+
+    EMITTING_COMPONENT.with(|emitting_component| {
+        emitting_component.replace(previous_component);
+    });
+    result
+}
@@ -1,11 +1,17 @@
-use std::any::Any;
+use crate::parsing::expression::value::Value;
 
 use super::reactivity::ComponentEvent;
 
+pub use angust_macros::ComponentState;
+
 pub trait ComponentState {
-    fn get_property(&self, property_name: &str) -> Option<Box<dyn Any>>;
-    fn set_property(&mut self, property_name: &str, value: Box<dyn Any>);
+    fn get_property(&self, property_name: &str) -> Option<Value>;
+    fn set_property(&mut self, property_name: &str, value: Value);
     fn get_all_properties(&self) -> Vec<&str>;
+    // Properties the parent component can set through the attributes of the component's tag
+    fn get_input_properties(&self) -> Vec<&str> {
+        self.get_all_properties()
+    }
     fn subscribe_to_property<F>(&mut self, property_name: &str, callback: F)
         where
             F: 'static + FnMut(&ComponentEvent);
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use kuchiki::{NodeRef, Selectors};
+
+use crate::rendering::elements::element::Element;
+
+use super::template_cache::CompiledTemplate;
+
+
+pub static DEFAULT_SLOT: &str = "";
+
+/*
+ * Element written between the tags of a custom component, e.g. <my-card><div class="title">...</div></my-card>.
+ * It is parsed in the context of the parent component; the DOM node is kept to match the select= of the slots.
+ */
+pub struct ProjectedElement {
+    pub node: NodeRef,
+    pub element: Box<dyn Element>,
+}
+
+/*
+ * Content projected into the <ng-content> slots of a component template.
+ * The projected elements are moved into the slot containers on each template load,
+ * and reclaimed from the previous element tree before the template is loaded again.
+ */
+#[derive(Default)]
+pub struct ContentProjection {
+    unassigned_elements: Vec<ProjectedElement>,
+    slot_elements: HashMap<String, Vec<Box<dyn Element>>>,
+    slot_container_ids: Vec<(String, String)>, // (Slot selector, ID of the container rendering the slot)
+    has_projected_elements: bool,
+}
+
+impl ContentProjection {
+    pub fn set_projected_elements(&mut self, projected_elements: Vec<ProjectedElement>) {
+        self.has_projected_elements = !projected_elements.is_empty();
+        self.unassigned_elements = projected_elements;
+        self.slot_elements.clear();
+        self.slot_container_ids.clear();
+    }
+
+    pub fn has_projected_elements(&self) -> bool {
+        self.has_projected_elements
+    }
+
+    /*
+     * Prepares the slots for a new load of the template: takes back the projected elements from the previous
+     * element tree and assigns the new projected elements to the slots declared in the template.
+     */
+    pub fn prepare_slots(&mut self, template: &CompiledTemplate, previous_content: &mut Box<dyn Element>) {
+        for (selector, container_id) in std::mem::take(&mut self.slot_container_ids) {
+            let reclaimed_elements = find_element_mut(previous_content, &container_id)
+                .and_then(|container| container.get_children_mut())
+                .map(std::mem::take)
+                .unwrap_or_default();
+
+            self.slot_elements.entry(selector).or_default().extend(reclaimed_elements);
+        }
+
+        if self.unassigned_elements.is_empty() {
+            return;
+        }
+        for projected_element in std::mem::take(&mut self.unassigned_elements) {
+            let selector = find_matching_slot(&projected_element.node, &template.slot_selectors);
+            self.slot_elements.entry(selector).or_default().push(projected_element.element);
+        }
+    }
+
+    pub fn take_slot_elements(&mut self, selector: &str, container_id: String) -> Vec<Box<dyn Element>> {
+        self.slot_container_ids.push((selector.to_string(), container_id));
+
+        self.slot_elements.remove(selector).unwrap_or_default()
+    }
+}
+
+// Elements go to the first slot whose selector they match, and to the default slot otherwise
+fn find_matching_slot(node: &NodeRef, slot_selectors: &[(String, Selectors)]) -> String {
+    let element_ref = match node.clone().into_element_ref() {
+        Some(element_ref) => element_ref,
+        None => return DEFAULT_SLOT.to_string(),
+    };
+
+    slot_selectors.iter()
+        .find(|(_, compiled_selector)| compiled_selector.matches(&element_ref))
+        .map(|(selector, _)| selector.clone())
+        .unwrap_or_else(|| DEFAULT_SLOT.to_string())
+}
+
+fn find_element_mut<'a>(element: &'a mut Box<dyn Element>, element_id: &str) -> Option<&'a mut Box<dyn Element>> {
+    if element.get_id() == element_id {
+        return Some(element);
+    }
+
+    element.get_children_mut()?
+        .iter_mut()
+        .find_map(|child| find_element_mut(child, element_id))
+}
@@ -0,0 +1,24 @@
+use crate::parsing::expression::value::Value;
+
+
+pub type ItemsFn<State> = dyn Fn(&State) -> Vec<Value>;
+
+/*
+ * Getter of the items a @for directive iterates over, usually created with wrap_getter!.
+ * The items are converted with Value::from, so user types implement From<T> for Value, e.g. with a Value::Map
+ * of their fields or a Value::opaque. Declaring the state properties it reads lets the template be rendered again
+ * only when one of them changes.
+ */
+pub struct ArrayGetter<State> {
+    pub getter: Box<ItemsFn<State>>,
+    pub dependencies: Option<Vec<String>>, // None if undeclared, the getter being assumed to read the property it is named after
+}
+
+impl<State> ArrayGetter<State> {
+    pub fn new<Item: Into<Value>>(getter: impl Fn(&State) -> Vec<Item> + 'static, dependencies: Option<Vec<String>>) -> Self {
+        ArrayGetter {
+            getter: Box::new(move |state| getter(state).into_iter().map(Into::into).collect()),
+            dependencies,
+        }
+    }
+}
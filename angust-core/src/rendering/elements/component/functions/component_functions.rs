@@ -1,19 +1,25 @@
-use std::{any::Any, collections::HashMap};
+use std::collections::HashMap;
+
+use crate::{parsing::expression::value::Value, rendering::elements::event_payload::EventPayload};
+
+use super::{array_getter::ArrayGetter, dynamic_params_functions::DynamicParamsFunction};
 
 
 pub struct ComponentFunctions<State> {
     pub event_handlers: HashMap<String, Box<dyn FnMut(&mut State)>>,
+    pub event_handlers_with_args: HashMap<String, Box<dyn FnMut(&mut State, &[Value], &EventPayload) -> Result<(), String>>>,
     pub boolean_evaluators: HashMap<String, Box<dyn Fn(&State) -> bool>>,
-    pub array_getters: HashMap<String, Box<dyn Fn(&State) -> Vec<&dyn Any>>>,
-    pub dynamic_params_functions: HashMap<String, Box<dyn Fn(&State, Vec<Box<dyn Any>>) -> Box<dyn Any>>>,
+    pub array_getters: HashMap<String, ArrayGetter<State>>,
+    pub dynamic_params_functions: HashMap<String, DynamicParamsFunction<State>>,
 }
 
 impl<State> ComponentFunctions<State> {
     pub fn new(
         event_handlers: Vec<(&str, Box<dyn FnMut(&mut State)>)>,
         boolean_evaluators: Vec<(&str, Box<dyn Fn(&State) -> bool>)>,
-        array_getters: Vec<(&str, Box<dyn Fn(&State) -> Vec<&dyn Any>>)>,
-        dynamic_params_functions: Vec<(&str, Box<dyn Fn(&State, Vec<Box<dyn Any>>) -> Box<dyn Any>>)>
+        array_getters: Vec<(&str, ArrayGetter<State>)>,
+        dynamic_params_functions: Vec<(&str, DynamicParamsFunction<State>)>,
+        event_handlers_with_args: Vec<(&str, Box<dyn FnMut(&mut State, &[Value], &EventPayload) -> Result<(), String>>)>,
     ) -> Self {
         let mut functions = Self::default();
 
@@ -29,6 +35,9 @@ impl<State> ComponentFunctions<State> {
         for (function_name, function) in dynamic_params_functions {
             functions.dynamic_params_functions.insert(function_name.to_string(), function);
         }
+        for (event_name, handler) in event_handlers_with_args {
+            functions.event_handlers_with_args.insert(event_name.to_string(), handler);
+        }
 
         functions
     }
@@ -38,6 +47,7 @@ impl<State> Default for ComponentFunctions<State> {
     fn default() -> Self {
         Self {
             event_handlers: HashMap::new(),
+            event_handlers_with_args: HashMap::new(),
             boolean_evaluators: HashMap::new(),
             array_getters: HashMap::new(),
             dynamic_params_functions: HashMap::new(),
@@ -1,11 +1,28 @@
-use std::any::Any;
+use crate::parsing::expression::value::{FromValue, Value};
 
 pub trait ArgExtractor {
     type Args;
-    fn extract(iter: &mut dyn Iterator<Item=Box<dyn Any>>) -> Option<Self::Args>;
+    fn extract(iter: &mut dyn Iterator<Item=Value>) -> Option<Self::Args>;
 }
 
 pub trait MethodExt<State, R> {
     type Args: ArgExtractor;
     fn call(state: &State, args: Self::Args) -> R;
 }
+
+/*
+ * Function called with arguments by the template expressions, usually created with wrap_fn!.
+ * Declaring the state properties it reads lets the template be rendered again only when one of them changes.
+ */
+pub struct DynamicParamsFunction<State> {
+    pub function: Box<dyn Fn(&State, Vec<Value>) -> Result<Value, String>>,
+    pub dependencies: Option<Vec<String>>, // None if undeclared, making the template depend on every property
+}
+
+// Converts an argument of a wrapped function or event handler to the type of its parameter, see wrap_fn!
+pub fn convert_argument<T: FromValue>(argument: Option<Value>, position: usize) -> Result<T, String> {
+    let argument = argument.ok_or_else(|| format!("Missing argument {}", position))?;
+    let description = format!("{:?}", argument);
+    T::from_value(argument)
+        .ok_or_else(|| format!("Argument {} ({}) cannot be converted to {}", position, description, std::any::type_name::<T>()))
+}
@@ -0,0 +1,25 @@
+use std::rc::Rc;
+
+use crate::parsing::expression::value::Value;
+
+
+/*
+ * Call of a component event handler requested by an element, e.g. @onclick="select_tab(tab, index)".
+ * The arguments are evaluated when the template is parsed, so that they capture the @for variables.
+ */
+#[derive(Clone)]
+pub struct EventHandlerCall {
+    pub handler_name: String,
+    pub args: Rc<Vec<Value>>,
+    pub component_id: Option<String>, // Component whose template declared the call, None if unknown
+}
+
+impl EventHandlerCall {
+    pub fn new(handler_name: String, args: Vec<Value>, component_id: Option<String>) -> Self {
+        Self {
+            handler_name,
+            args: Rc::new(args),
+            component_id,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+pub mod array_getter;
 pub mod component_functions;
 pub mod dynamic_params_functions;
-pub mod wrap_function_macro;
\ No newline at end of file
+pub mod event_handler_call;
+pub mod wrap_function_macro;
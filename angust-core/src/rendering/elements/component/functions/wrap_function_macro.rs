@@ -1,27 +1,82 @@
-
-
+/*
+ * Wraps a state method taking typed arguments into a function callable from the template expressions,
+ * optionally declaring the state properties it reads, e.g. wrap_fn!(AppState, AppState::is_active_tab, String; reads active_tab).
+ * Without them, the template calling the function is rendered again when any property changes.
+ * A function reading none declares an empty list, e.g. wrap_fn!(AppState, AppState::add_one, f64; reads).
+ * The function fails without calling the method if an argument is missing or cannot be converted.
+ */
 #[macro_export]
 macro_rules! wrap_fn {
-    ($state:ty, $func:path) => {
-        wrap_fn!($state, $func,);
+    (@dependencies) => {
+        None
+    };
+    (@dependencies [$($dependency:ident),*]) => {
+        Some(vec![$(stringify!($dependency).to_string()),*])
     };
-    ($state:ty, $func:path, $($t:ty),+) => {
-        Box::new(move |state: &$state, args: Vec<Box<dyn Any>>| -> Box<dyn Any> {
-            let mut arg_iter = args.into_iter();
+    ($state:ty, $func:path $(, $t:ty)* $(; reads $($dependency:ident),*)?) => {
+        $crate::rendering::elements::component::functions::dynamic_params_functions::DynamicParamsFunction {
+            function: Box::new(move |state: &$state, args: Vec<$crate::parsing::expression::value::Value>| -> Result<$crate::parsing::expression::value::Value, String> {
+                #[allow(unused_mut, unused_variables)]
+                let (mut arg_iter, mut position) = (args.into_iter(), 0);
+
+                let result = {
+                    let func = $func;
+                    func(
+                        state,
+                        $({
+                            position += 1;
+                            $crate::rendering::elements::component::functions::dynamic_params_functions::convert_argument::<$t>(arg_iter.next(), position)?
+                        },)*
+                    )
+                };
+
+                Ok($crate::parsing::expression::value::Value::from(result))
+            }),
+            dependencies: $crate::wrap_fn!(@dependencies $([$($dependency),*])?),
+        }
+    };
+}
 
-            let result = {
-                let func = $func;
-                func(
-                    state,
-                    $(
-                        *arg_iter.next().expect("Missing argument")
-                            .downcast::<$t>()
-                            .expect("Type mismatch in argument downcasting"),
-                    )+
-                )
-            };
 
-            Box::new(result)
-        }) as Box<dyn Fn(&$state, Vec<Box<dyn Any>>) -> Box<dyn Any>>
+/*
+ * Wraps a getter of the items of a @for directive, optionally declaring the state properties it reads,
+ * e.g. wrap_getter!(AppState, AppState::visible_rows; reads rows). Without them, the getter is assumed to read
+ * the property it is named after, and the template is rendered again when any property changes if there is none.
+ */
+#[macro_export]
+macro_rules! wrap_getter {
+    ($state:ty, $func:expr $(; reads $($dependency:ident),*)?) => {
+        $crate::rendering::elements::component::functions::array_getter::ArrayGetter::<$state>::new(
+            $func,
+            $crate::wrap_fn!(@dependencies $([$($dependency),*])?),
+        )
+    };
+}
+
+/*
+ * Wraps a state method taking typed arguments and the event payload into an event handler with arguments,
+ * e.g. wrap_handler!(AppState, AppState::select_tab, String) for fn select_tab(&mut self, tab: String, event: &EventPayload).
+ * The handler fails without calling the method if an argument is missing or cannot be converted.
+ */
+#[macro_export]
+macro_rules! wrap_handler {
+    ($state:ty, $func:path) => {
+        wrap_handler!($state, $func,);
+    };
+    ($state:ty, $func:path, $($t:ty),*) => {
+        Box::new(move |state: &mut $state, args: &[$crate::parsing::expression::value::Value], event: &$crate::rendering::elements::event_payload::EventPayload| -> Result<(), String> {
+            #[allow(unused_mut, unused_variables)]
+            let (mut arg_iter, mut position) = (args.iter().cloned(), 0);
+            let func = $func;
+            func(
+                state,
+                $({
+                    position += 1;
+                    $crate::rendering::elements::component::functions::dynamic_params_functions::convert_argument::<$t>(arg_iter.next(), position)?
+                },)*
+                event,
+            );
+            Ok(())
+        }) as Box<dyn FnMut(&mut $state, &[$crate::parsing::expression::value::Value], &$crate::rendering::elements::event_payload::EventPayload) -> Result<(), String>>
     };
 }
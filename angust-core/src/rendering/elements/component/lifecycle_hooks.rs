@@ -0,0 +1,24 @@
+/*
+ * Optional callbacks run at the key moments of a component's life:
+ * - on_init: when the component is initialized, before its template is first loaded
+ * - on_changes: when the parent sets its inputs, with the names of the inputs
+ * - after_view_init: after the first layout of its template
+ * - on_destroy: when the component is dropped, e.g. when the parent re-renders its template
+ */
+pub struct LifecycleHooks<State> {
+    pub on_init: Option<Box<dyn FnMut(&mut State)>>,
+    pub on_changes: Option<Box<dyn FnMut(&mut State, &[String])>>,
+    pub after_view_init: Option<Box<dyn FnMut(&mut State)>>,
+    pub on_destroy: Option<Box<dyn FnMut(&mut State)>>,
+}
+
+impl<State> Default for LifecycleHooks<State> {
+    fn default() -> Self {
+        Self {
+            on_init: None,
+            on_changes: None,
+            after_view_init: None,
+            on_destroy: None,
+        }
+    }
+}
@@ -3,7 +3,7 @@ macro_rules! define_component_state {
     ($name:ident { $($field:ident: $type:ty),* $(,)? }) => {
         use $crate::rendering::elements::component::component_state::ComponentState;
         use $crate::rendering::elements::component::reactivity::{ReactiveField, ComponentEvent};
-        use std::any::Any;
+        use $crate::parsing::expression::value::Value;
 
         #[derive(Debug)]
         pub struct $name {
@@ -20,19 +20,19 @@ macro_rules! define_component_state {
         }
 
         impl ComponentState for $name {
-            fn get_property(&self, property_name: &str) -> Option<Box<dyn Any>> {
+            fn get_property(&self, property_name: &str) -> Option<Value> {
                 match property_name {
-                    $(stringify!($field) => Some(Box::new(self.$field.value.clone())),)*
+                    $(stringify!($field) => Some(Value::from(self.$field.value.clone())),)*
                     _ => None,
                 }
             }
 
-            fn set_property(&mut self, property_name: &str, value: Box<dyn Any>) {
+            fn set_property(&mut self, property_name: &str, value: Value) {
                 match property_name {
                     $(
                         stringify!($field) => {
-                            if let Ok(casted_value) = value.downcast::<$type>() {
-                                self.$field.set(*casted_value);
+                            if let Some(converted_value) = <$type as $crate::parsing::expression::value::FromValue>::from_value(value) {
+                                self.$field.set(converted_value);
                             }
                         },
                     )*
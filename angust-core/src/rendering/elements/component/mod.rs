@@ -1,9 +1,14 @@
 
+pub mod child_components;
 pub mod component;
 pub mod component_factory_registry;
+pub mod component_outputs;
 pub mod component_state;
+pub mod content_projection;
+pub mod lifecycle_hooks;
 pub mod no_state;
 pub mod macros;
 pub mod reactivity;
 pub mod functions;
+pub mod template_cache;
 mod template_loader;
\ No newline at end of file
@@ -1,5 +1,7 @@
 use std::{fmt::{Debug, Formatter}, ops::{Deref, DerefMut}};
 
+use super::component_outputs::OutputEvent;
+
 
 pub struct ReactiveField<T> {
     pub value: T,
@@ -33,7 +35,7 @@ impl<T> ReactiveField<T> {
 
     pub fn set(&mut self, new_value: T) {
         self.value = new_value;
-        self.notify_listeners(&ComponentEvent::StateChange(String::from("placeholder_id")));
+        self.notify_listeners(&ComponentEvent::StateChange(String::from("placeholder_id"), String::from("placeholder_property")));
     }
 
     fn notify_listeners(&mut self, event: &ComponentEvent) {
@@ -60,7 +62,8 @@ impl<T> DerefMut for ReactiveField<T> {
 
 #[derive(Debug)]
 pub enum ComponentEvent {
-    StateChange(String),
+    StateChange(String, String), // (Component ID, property name)
+    OutputEmitted(OutputEvent),
 }
 
 pub struct EventQueue {
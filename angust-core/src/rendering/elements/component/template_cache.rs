@@ -0,0 +1,74 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use kuchiki::{NodeRef, Selectors};
+
+use crate::{application::resource_loader::embedded_resources, parsing::{css::global_stylesheet, diagnostics::{self, Diagnostic}, html::{compiled_nodes::CompiledNodes, html_parser, template_diagnostics::SourceLocations}}};
+
+
+/*
+ * Compiled form of a component template, shared by all the instances of the component:
+ * the parsed DOM, walked at each rendering, its nodes with their directives, bindings and candidate style rules compiled,
+ * the compiled selectors of its <ng-content> slots, and the source locations of its elements, used to locate its diagnostics.
+ */
+pub struct CompiledTemplate {
+    pub path: String,
+    pub dom: NodeRef,
+    pub nodes: Rc<CompiledNodes>,
+    pub slot_selectors: Vec<(String, Selectors)>,
+    pub source_locations: Rc<SourceLocations>,
+}
+
+thread_local! {
+    static TEMPLATE_CACHE: RefCell<HashMap<String, Rc<CompiledTemplate>>> = RefCell::new(HashMap::new());
+}
+
+/*
+ * Returns the compiled template at the given path, reading and compiling it on first use.
+ */
+pub fn get_compiled_template(template_relative_path: &str) -> Rc<CompiledTemplate> {
+    let cached_template = TEMPLATE_CACHE.with(|cache| cache.borrow().get(template_relative_path).cloned());
+    if let Some(template) = cached_template {
+        return template;
+    }
+
+    let template = Rc::new(compile_template(template_relative_path));
+    TEMPLATE_CACHE.with(|cache| {
+        cache.borrow_mut().insert(template_relative_path.to_string(), template.clone());
+    });
+    template
+}
+
+fn compile_template(template_relative_path: &str) -> CompiledTemplate {
+    let template_content = embedded_resources::read_resource_to_string(template_relative_path)
+        .expect("Failed to read template file");
+
+    let dom = html_parser::parse_html_content(&template_content);
+    let source_locations = Rc::new(SourceLocations::compute(&template_content, &dom));
+    let nodes = Rc::new(CompiledNodes::compile(&dom, &global_stylesheet::get_global_stylesheet()));
+    let slot_selectors = compile_slot_selectors(&dom, template_relative_path, &source_locations);
+
+    CompiledTemplate { path: template_relative_path.to_string(), dom, nodes, slot_selectors, source_locations }
+}
+
+fn compile_slot_selectors(dom: &NodeRef, template_relative_path: &str, source_locations: &SourceLocations) -> Vec<(String, Selectors)> {
+    let slots = match dom.select("ng-content") {
+        Ok(slots) => slots,
+        Err(_) => return vec![],
+    };
+
+    slots
+        .filter_map(|slot| {
+            let selector = slot.attributes.borrow().get("select")?.trim().to_string();
+
+            match Selectors::compile(&selector) {
+                Ok(compiled_selector) => Some((selector, compiled_selector)),
+                Err(_) => {
+                    let diagnostic = Diagnostic::error(format!("Invalid selector of content slot: {}", selector))
+                        .at(Some(template_relative_path.to_string()), source_locations.locate(slot.as_node()));
+                    diagnostics::report(diagnostic);
+                    None
+                }
+            }
+        })
+        .collect()
+}
@@ -1,36 +1,60 @@
-use std::path::PathBuf;
-
 use crate::{
-    application::resource_loader::path_navigator::identify_project_root_path, 
-    parsing::html::html_parser::{self, ParsingContext}, 
-    rendering::elements::{container::Container, element::Element}
+    parsing::{css::{global_stylesheet, style_context::RestyleImpact}, expression::dependency_collector, html::{html_parser::{self, ParsingContext}, template_diagnostics::TemplateDiagnostics}}, 
+    rendering::elements::{container::Container, element::Element, element_patcher}
 };
 
-use super::{component::Component, component_state::ComponentState};
-
+use super::{component::Component, component_state::ComponentState, template_cache};
 
-pub fn load_component_template<State: ComponentState>(component: &mut Component<State>) {
-    // Load template
-    let project_root = PathBuf::from(identify_project_root_path());
-    let template_path = project_root.join(component.template_relative_path.clone());
 
-    let template_content = std::fs::read_to_string(template_path)
-        .expect("Failed to read template file");
+/*
+ * Renders the component template against the current state, patching the element tree rendered previously.
+ * The template is compiled once per path and shared by the instances of the component, see template_cache.
+ * Returns how the patch affects the rendering, including the templates of the child components rendered again.
+ */
+pub fn load_component_template<State: ComponentState + 'static>(component: &mut Component<State>) -> RestyleImpact {
+    let template = match &component.template {
+        Some(template) => template.clone(),
+        None => {
+            let template = template_cache::get_compiled_template(&component.template_relative_path);
+            component.template = Some(template.clone());
+            template
+        }
+    };
 
-    // Parse template
-    let dom = html_parser::parse_html_content(&template_content);
+    component.output_bindings.clear();
+    component.template_expressions_asts.clear();
+    component.content_projection.prepare_slots(&template, &mut component.content);
+    component.child_components.detach_from(&mut component.content);
+    let component_id = component.get_id();
 
-    let mut container = Box::new(Container::new());
+    let mut container: Box<dyn Element> = Box::new(Container::new());
     let mut parsing_context: ParsingContext<State> = html_parser::ParsingContext::new(
         None, 
-        None, 
+        global_stylesheet::get_global_stylesheet(), 
         Some(&component.state),
         Some(&component.component_functions),
         Some(&mut component.template_expressions_asts)
     );
+    parsing_context.output_bindings = Some(&mut component.output_bindings);
+    parsing_context.content_projection = Some(&mut component.content_projection);
+    parsing_context.child_components = Some(&mut component.child_components);
+    parsing_context.component_id = Some(component_id);
+    parsing_context.host_element = Some(component.host_element.clone());
+    parsing_context.compiled_nodes = Some(template.nodes.clone());
+    parsing_context.diagnostics = TemplateDiagnostics::new(template.path.clone(), template.source_locations.clone());
     
-    if let Some(element) = html_parser::map_dom_to_elements::<State>(&dom, None, &mut parsing_context) {
+    if let Some(element) = html_parser::map_dom_to_elements::<State>(&template.dom, None, &mut parsing_context) {
         container.add_child(element);
     }
-    component.content = container;
-}
\ No newline at end of file
+    parsing_context.diagnostics.flush();
+    let has_undeclared_dependencies = parsing_context.has_undeclared_dependencies;
+    let child_components_patch_impact = parsing_context.child_components_patch_impact;
+
+    let patch_impact = element_patcher::patch_element(&mut component.content, container);
+    component.child_components.release_unused();
+    component.template_dependencies = match has_undeclared_dependencies {
+        true => None,
+        false => dependency_collector::collect_dependencies(&component.template_expressions_asts, &component.component_functions),
+    };
+    patch_impact.max(child_components_patch_impact)
+}
@@ -1,6 +1,8 @@
+use std::{any::Any, collections::HashMap};
+
 use skia_safe::{Canvas, Color, Point};
 
-use crate::{application::event_handling::scrollbar_movement_handler::handle_scrollbar_movement, rendering::{
+use crate::{application::event_handling::scrollbar_movement_handler::handle_scrollbar_movement, parsing::{css::style_context::{self, RestyleImpact, StyleContext}, expression::value::Value}, rendering::{
     layout::{
         effective_size_estimator, size_estimator, space_allocation_system::container::container_space_allocator
     },
@@ -8,11 +10,12 @@ use crate::{application::event_handling::scrollbar_movement_handler::handle_scro
 }};
 
 use super::{
-    common_types::{OptionalSize, Position, ScrollbarState, Size}, element::{Element, ElementType, EventType}, element_id_generator::IDGenerator, event_propagator, styles::{Directions, Styles}
+    common_types::{OptionalSize, Position, ScrollbarState, Size}, component::{component_outputs::OutputEvent, content_projection::ProjectedElement, functions::event_handler_call::EventHandlerCall}, element::{Element, ElementType, EventType}, element_event_handlers::ElementEventHandlers, element_id_generator::IDGenerator, element_patcher::{self, TemplateKey}, event_payload::EventPayload, event_propagator, styles::{Directions, Styles}
 };
 
 pub struct Container {
     _id: String,
+    template_key: Option<TemplateKey>,
     position: Position,
     size: Size,
     natural_size: Size,
@@ -20,6 +23,8 @@ pub struct Container {
     styles: Styles,
     pub children: Vec<Box<dyn Element>>,
     pub scrollbar_state: ScrollbarState,
+    event_handlers: ElementEventHandlers,
+    style_context: Option<StyleContext>, // None for the containers created by other elements, e.g. the content of a button
 }
 
 impl Container {
@@ -34,6 +39,9 @@ impl Container {
             styles: Styles::default(),
             children: Vec::new(),
             scrollbar_state: ScrollbarState::default(),
+            event_handlers: ElementEventHandlers::default(),
+            style_context: None,
+            template_key: None,
         }
     }
 
@@ -88,13 +96,23 @@ impl Element for Container {
     fn handle_event(&mut self, cursor_position: Point, event_type: &EventType) {
         handle_scrollbar_movement(self, cursor_position, event_type);
 
+        if let Some(style_context) = &self.style_context {
+            let is_within_bounds = event_propagator::is_within_bounds(self, cursor_position);
+            style_context.update_interaction_state(is_within_bounds, event_type, self.event_handlers.is_focusable());
+        }
+
         for child in &mut self.children {
             child.handle_event(cursor_position, event_type);
         }
     }
 
-    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType) -> Vec<String> {
-        event_propagator::propagate_event(self, cursor_position, event_type)
+    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType, event_payload: &EventPayload) -> Vec<EventHandlerCall> {
+        let mut event_targets = event_propagator::propagate_event(self, cursor_position, event_type, event_payload);
+
+        let is_within_bounds = event_propagator::is_within_bounds(self, cursor_position);
+        event_targets.extend(self.event_handlers.collect_handler_calls(is_within_bounds, event_type));
+
+        event_targets
     }
 
     fn set_id(&mut self, id: String) {
@@ -113,6 +131,16 @@ impl Element for Container {
         self.styles = styles;
     }
 
+    fn set_event_handlers(&mut self, event_handlers: ElementEventHandlers) {
+        self.event_handlers = event_handlers;
+    }
+
+    fn set_inputs(&mut self, _: HashMap<String, Value>) {}
+
+    fn set_projected_content(&mut self, _: Vec<ProjectedElement>) {}
+
+    fn load_outdated_template(&mut self) -> RestyleImpact { RestyleImpact::None }
+
     fn add_child(&mut self, child: Box<dyn Element>) {
         self.children.push(child);
     }
@@ -121,6 +149,18 @@ impl Element for Container {
         self._id.clone()
     }
 
+    fn set_template_key(&mut self, template_key: TemplateKey) {
+        self.template_key = Some(template_key);
+    }
+
+    fn get_template_key(&self) -> Option<TemplateKey> {
+        self.template_key.clone()
+    }
+
+    fn get_outputs(&self) -> Vec<String> {
+        vec![]
+    }
+
     fn get_element_type(&self) -> ElementType {
         ElementType::Container
     }
@@ -199,9 +239,49 @@ impl Element for Container {
     }
 
     // Reactivity
-    fn react_to_state_change(&mut self, component_id: String) {
+    fn react_to_state_change(&mut self, component_id: String, property_name: &str) -> RestyleImpact {
+        self.children.iter_mut()
+            .fold(RestyleImpact::None, |patch_impact, child| patch_impact.max(child.react_to_state_change(component_id.clone(), property_name)))
+    }
+
+    fn react_to_output(&mut self, output_event: &mut OutputEvent) {
         for child in &mut self.children {
-            child.react_to_state_change(component_id.clone());
+            child.react_to_output(output_event);
+        }
+    }
+
+    // The scrollbar state is kept, as well as the hover and focus state of the handlers and of the styles
+    fn patch(&mut self, new_element: Box<dyn Element>) -> RestyleImpact {
+        match new_element.into_any().downcast::<Container>() {
+            Ok(new_container) => {
+                let new_container = *new_container;
+                let restyle_impact = RestyleImpact::between(&self.styles, &new_container.styles);
+                self._id = new_container._id;
+                self.template_key = new_container.template_key;
+                self.styles = new_container.styles;
+                self.event_handlers.update_handlers(new_container.event_handlers);
+                style_context::replace_style_context(&mut self.style_context, new_container.style_context);
+                restyle_impact.max(element_patcher::patch_children(&mut self.children, new_container.children))
+            },
+            Err(_) => RestyleImpact::None,
         }
     }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> { self }
+
+    // Interactive styles
+    fn set_style_context(&mut self, style_context: StyleContext) {
+        self.style_context = Some(style_context);
+    }
+
+    fn restyle(&mut self) -> RestyleImpact {
+        let mut restyle_impact = RestyleImpact::None;
+        if let Some(styles) = self.style_context.as_mut().and_then(StyleContext::restyle) {
+            restyle_impact = RestyleImpact::between(&self.styles, &styles);
+            self.styles = styles;
+        }
+
+        self.children.iter_mut()
+            .fold(restyle_impact, |restyle_impact, child| restyle_impact.max(child.restyle()))
+    }
 }
@@ -1,6 +1,10 @@
+use std::{any::Any, collections::HashMap};
+
 use skia_safe::{Canvas, Point};
 
-use super::{common_types::{OptionalSize, Position, Size}, styles::Styles};
+use crate::parsing::{css::style_context::{RestyleImpact, StyleContext}, expression::value::Value};
+
+use super::{common_types::{OptionalSize, Position, Size}, component::{component_outputs::OutputEvent, content_projection::ProjectedElement, functions::event_handler_call::EventHandlerCall}, element_event_handlers::ElementEventHandlers, element_patcher::TemplateKey, event_payload::EventPayload, styles::Styles};
 
 // This is the trait that all Angust elements must implement.
 pub trait Element {
@@ -10,20 +14,27 @@ pub trait Element {
     fn handle_event(&mut self, cursor_position: Point, event_type: &EventType);
 
     // Experimental: gather all names of event handlers that should be called by a parent Custom Component
-    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType) -> Vec<String>;
+    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType, event_payload: &EventPayload) -> Vec<EventHandlerCall>;
 
     // Getters and setters
     fn set_id(&mut self, id: String);
     fn set_position(&mut self, position: Position);
     fn set_size(&mut self, size: Size);
     fn set_styles(&mut self, styles: Styles);
+    fn set_event_handlers(&mut self, event_handlers: ElementEventHandlers);
+    fn set_inputs(&mut self, inputs: HashMap<String, Value>);
+    fn set_projected_content(&mut self, projected_elements: Vec<ProjectedElement>);
+    fn load_outdated_template(&mut self) -> RestyleImpact; // Renders the template of a component again if its inputs or projected content changed
     fn add_child(&mut self, child: Box<dyn Element>);
+    fn set_template_key(&mut self, template_key: TemplateKey);
 
     fn get_id(&self) -> String;
     fn get_element_type(&self) -> ElementType;
     fn get_position(&self) -> Position;
     fn get_size(&self) -> Size;
     fn get_styles(&self) -> Styles;
+    fn get_template_key(&self) -> Option<TemplateKey>; // None for the elements not rendered from a node of a template
+    fn get_outputs(&self) -> Vec<String>; // Declared by a component, lowercased
 
     fn get_children_mut(&mut self) -> Option<&mut Vec<Box<dyn Element>>>;
     
@@ -39,7 +50,14 @@ pub trait Element {
     fn layout(&mut self, allocated_position: Position, allocated_size: Size);
 
     // Reactivity
-    fn react_to_state_change(&mut self, component_id: String);
+    fn react_to_state_change(&mut self, component_id: String, property_name: &str) -> RestyleImpact; // How updating the element tree affects its rendering
+    fn react_to_output(&mut self, output_event: &mut OutputEvent);
+    fn patch(&mut self, new_element: Box<dyn Element>) -> RestyleImpact; // Updates the element in place with an element of the same type
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+
+    // Interactive styles (:hover, :active, :focus), the interaction state being updated in handle_event
+    fn set_style_context(&mut self, style_context: StyleContext);
+    fn restyle(&mut self) -> RestyleImpact; // How the changes of the styles of the element and of its descendants affect it
 }
 
 
@@ -51,7 +69,15 @@ pub enum EventType {
     MouseDrag,
     MouseUp,
     MouseRoll(f32),
+    MouseEnter,
+    MouseLeave,
+    DoubleClick,
     KeyPress(char),
+    KeyDown,
+    KeyUp,
+    Input,
+    Focus,
+    Blur,
 }
 
 #[derive(Clone, Debug, PartialEq)]
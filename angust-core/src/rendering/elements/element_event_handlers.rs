@@ -0,0 +1,119 @@
+use std::mem::discriminant;
+
+use super::{component::functions::event_handler_call::EventHandlerCall, element::EventType};
+
+
+/*
+ * Event handlers attached to an element through the event directives (@onclick, @onmouseenter, @onkeydown, ...),
+ * along with the hover and focus state needed to synthesize the enter / leave and focus / blur events,
+ * and the press state needed to dispatch clicks to the elements both pressed and released over.
+ */
+#[derive(Clone, Default)]
+pub struct ElementEventHandlers {
+    handlers: Vec<(EventType, EventHandlerCall)>,
+    is_hovered: bool,
+    is_pressed: bool,
+    is_focused: bool,
+}
+
+impl ElementEventHandlers {
+    pub fn new(handlers: Vec<(EventType, EventHandlerCall)>) -> Self {
+        Self {
+            handlers,
+            ..Default::default()
+        }
+    }
+
+    // Replaces the handlers, keeping the hover, press and focus state
+    pub fn update_handlers(&mut self, event_handlers: ElementEventHandlers) {
+        self.handlers = event_handlers.handlers;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /*
+     * Returns the handler calls triggered by an event, updating the hover, press and focus state.
+     * Mouse events reach the element when the cursor is within its bounds, keyboard events when it is focused.
+     * Clicks, dispatched on release, only reach the element if it was pressed as well.
+     */
+    pub fn collect_handler_calls(&mut self, is_within_bounds: bool, event_type: &EventType) -> Vec<EventHandlerCall> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let mut handler_calls = Vec::new();
+
+        match event_type {
+            EventType::MouseMove => {
+                if is_within_bounds != self.is_hovered {
+                    self.is_hovered = is_within_bounds;
+                    let hover_event = if is_within_bounds { EventType::MouseEnter } else { EventType::MouseLeave };
+                    handler_calls.extend(self.get_handler_calls(&hover_event));
+                }
+                if is_within_bounds {
+                    handler_calls.extend(self.get_handler_calls(event_type));
+                }
+            },
+            EventType::MouseDown => {
+                self.is_pressed = is_within_bounds;
+                if is_within_bounds {
+                    handler_calls.extend(self.get_handler_calls(event_type));
+                }
+            },
+            EventType::MouseClick => {
+                let is_clicked = is_within_bounds && self.is_pressed;
+                if is_clicked {
+                    handler_calls.extend(self.get_handler_calls(event_type));
+                }
+                if self.is_focusable() && is_clicked != self.is_focused {
+                    self.is_focused = is_clicked;
+                    let focus_event = if is_clicked { EventType::Focus } else { EventType::Blur };
+                    handler_calls.extend(self.get_handler_calls(&focus_event));
+                }
+            },
+            EventType::DoubleClick => {
+                if is_within_bounds && self.is_pressed {
+                    handler_calls.extend(self.get_handler_calls(event_type));
+                }
+            },
+            // The cursor left the window
+            EventType::MouseLeave => {
+                if self.is_hovered {
+                    self.is_hovered = false;
+                    handler_calls.extend(self.get_handler_calls(event_type));
+                }
+            },
+            EventType::MouseUp | EventType::MouseDrag | EventType::MouseRoll(_) => {
+                if is_within_bounds {
+                    handler_calls.extend(self.get_handler_calls(event_type));
+                }
+            },
+            EventType::KeyDown | EventType::KeyUp | EventType::KeyPress(_) | EventType::Input => {
+                if self.is_focused {
+                    handler_calls.extend(self.get_handler_calls(event_type));
+                }
+            },
+            // Synthesized from the events above
+            EventType::MouseEnter | EventType::Focus | EventType::Blur => {},
+        }
+
+        handler_calls
+    }
+
+    fn get_handler_calls(&self, event_type: &EventType) -> Vec<EventHandlerCall> {
+        self.handlers.iter()
+            .filter(|(handled_event_type, _)| discriminant(handled_event_type) == discriminant(event_type))
+            .map(|(_, handler_call)| handler_call.clone())
+            .collect()
+    }
+
+    // Elements listening to focus or keyboard events get focused when clicked
+    pub fn is_focusable(&self) -> bool {
+        self.handlers.iter().any(|(event_type, _)| matches!(
+            event_type,
+            EventType::Focus | EventType::Blur | EventType::KeyDown | EventType::KeyUp | EventType::KeyPress(_) | EventType::Input
+        ))
+    }
+}
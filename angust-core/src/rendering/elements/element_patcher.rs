@@ -0,0 +1,91 @@
+use std::{collections::HashMap, rc::Rc};
+
+use kuchiki::NodeRef;
+
+use crate::parsing::css::style_context::RestyleImpact;
+
+use super::element::{Element, ElementType};
+
+
+/*
+ * Identity of an element rendered from a template: the node it comes from and, for the elements of a @for loop,
+ * the item they were rendered for (see directive_parser::parse_for_expression). Children are patched with the new
+ * children of the same key, so that they keep their state when items are inserted, removed or reordered.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TemplateKey {
+    node: usize, // Address of the node in the DOM of the template
+    item_key: Option<String>,
+}
+
+impl TemplateKey {
+    pub fn new(node: &NodeRef, item_key: Option<String>) -> Self {
+        TemplateKey { node: Rc::as_ptr(&node.0) as usize, item_key }
+    }
+}
+
+/*
+ * Patches an element of the rendered tree with the element rendered from the updated template:
+ * elements of the same type are updated in place, keeping their state (scroll position, hover, focus),
+ * while the others are replaced. Components are reused through their template key beforehand, leaving a placeholder
+ * with their ID and styles in the tree (see ChildComponents::detach_from), so they replace their placeholder.
+ * Returns how the patch affects the rendering, the changes of the reused components being reported by their own patch.
+ */
+pub fn patch_element(element: &mut Box<dyn Element>, new_element: Box<dyn Element>) -> RestyleImpact {
+    let element_type = element.get_element_type();
+
+    if new_element.get_element_type() == ElementType::CustomComponent {
+        let is_reused = element.get_id() == new_element.get_id();
+        let patch_impact = match is_reused {
+            true => RestyleImpact::between(&element.get_styles(), &new_element.get_styles()),
+            false => RestyleImpact::Layout,
+        };
+        *element = new_element;
+        return patch_impact;
+    }
+    if element_type != new_element.get_element_type() || element_type == ElementType::CustomComponent {
+        *element = new_element;
+        return RestyleImpact::Layout;
+    }
+
+    element.patch(new_element)
+}
+
+/*
+ * Patches the children with the new children of the same template key, the unkeyed ones being paired by position.
+ * New children without a match are inserted, and the children left are dropped.
+ */
+pub fn patch_children(children: &mut Vec<Box<dyn Element>>, new_children: Vec<Box<dyn Element>>) -> RestyleImpact {
+    let mut patch_impact = match children.len() == new_children.len() {
+        true => RestyleImpact::None,
+        false => RestyleImpact::Layout,
+    };
+
+    let mut previous_children: Vec<Option<Box<dyn Element>>> = children.drain(..).map(Some).collect();
+    let keyed_indices: HashMap<TemplateKey, usize> = previous_children.iter().enumerate()
+        .filter_map(|(index, child)| Some((child.as_ref()?.get_template_key()?, index)))
+        .collect();
+
+    for (index, new_child) in new_children.into_iter().enumerate() {
+        let previous_index = match new_child.get_template_key() {
+            Some(template_key) => keyed_indices.get(&template_key).copied(),
+            None => Some(index).filter(|index| previous_children.get(*index).is_some_and(|child| child.as_ref().is_some_and(|child| child.get_template_key().is_none()))),
+        };
+
+        match previous_index.and_then(|previous_index| previous_children[previous_index].take()) {
+            Some(mut child) => {
+                if previous_index != Some(index) {
+                    patch_impact = RestyleImpact::Layout;
+                }
+                patch_impact = patch_impact.max(patch_element(&mut child, new_child));
+                children.push(child);
+            },
+            None => {
+                patch_impact = RestyleImpact::Layout;
+                children.push(new_child);
+            },
+        }
+    }
+
+    patch_impact
+}
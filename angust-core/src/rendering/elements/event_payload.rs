@@ -0,0 +1,42 @@
+use skia_safe::Point;
+
+
+/*
+ * Event object passed to the component event handlers, describing the event that triggered them.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EventPayload {
+    pub cursor_position: Point,
+    pub mouse_button: Option<MouseButton>,
+    pub modifiers: KeyModifiers,
+    pub key: Option<String>, // Key name for keyboard events, e.g. "a" or "Enter"
+    pub text: Option<String>, // Text produced by the key, for input events
+    pub scroll_delta: f32,
+}
+
+impl EventPayload {
+    pub fn new(cursor_position: Point, mouse_button: Option<MouseButton>, modifiers: KeyModifiers) -> Self {
+        Self {
+            cursor_position,
+            mouse_button,
+            modifiers,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Other(u16),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    pub super_key: bool,
+}
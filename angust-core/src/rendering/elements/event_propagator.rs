@@ -1,13 +1,21 @@
-use super::element::{Element, EventType};
+use super::{component::functions::event_handler_call::EventHandlerCall, element::{Element, EventType}, event_payload::EventPayload};
 
 
-pub fn propagate_event(element: &mut dyn Element, cursor_position: skia_safe::Point, event_type: &EventType) -> Vec<String> {
+pub fn propagate_event(element: &mut dyn Element, cursor_position: skia_safe::Point, event_type: &EventType, event_payload: &EventPayload) -> Vec<EventHandlerCall> {
     let mut event_targets = Vec::new();
 
     for child in element.get_children_mut().unwrap_or(&mut vec![]) {
-        let child_event_targets = child.propagate_event(cursor_position, event_type);
+        let child_event_targets = child.propagate_event(cursor_position, event_type, event_payload);
         event_targets.extend(child_event_targets);
     }
 
     event_targets
+}
+
+pub fn is_within_bounds(element: &dyn Element, cursor_position: skia_safe::Point) -> bool {
+    let position = element.get_position();
+    let size = element.get_size();
+
+    cursor_position.x >= position.x && cursor_position.x <= position.x + size.width &&
+    cursor_position.y >= position.y && cursor_position.y <= position.y + size.height
 }
\ No newline at end of file
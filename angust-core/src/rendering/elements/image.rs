@@ -1,13 +1,16 @@
+use std::{any::Any, collections::HashMap};
+
 use image::DynamicImage;
 use skia_safe::{Canvas, Point};
 
-use crate::{application::resource_loader::image_loader, rendering::{layout::effective_size_estimator, rendering_interface::element_renderer::ElementRenderer}};
+use crate::{application::resource_loader::image_loader, parsing::{css::style_context::{self, RestyleImpact, StyleContext}, expression::value::Value}, rendering::{layout::effective_size_estimator, rendering_interface::element_renderer::ElementRenderer}};
 
-use super::{common_types::{OptionalSize, Position, Size}, element::{Element, ElementType, EventType}, element_id_generator::IDGenerator, event_propagator, styles::Styles};
+use super::{common_types::{OptionalSize, Position, Size}, component::{component_outputs::OutputEvent, content_projection::ProjectedElement, functions::event_handler_call::EventHandlerCall}, element::{Element, ElementType, EventType}, element_event_handlers::ElementEventHandlers, element_id_generator::IDGenerator, element_patcher::TemplateKey, event_payload::EventPayload, event_propagator, styles::Styles};
 
 
 pub struct Image {
     _id: String,
+    template_key: Option<TemplateKey>,
     image_path: String,
     image: Option<DynamicImage>,
     position: Position,
@@ -15,6 +18,8 @@ pub struct Image {
     styles: Styles,
     natural_size: Size,
     requested_size: OptionalSize,
+    event_handlers: ElementEventHandlers,
+    style_context: Option<StyleContext>,
 }
 
 impl Image {
@@ -32,6 +37,9 @@ impl Image {
             styles: styles.unwrap_or_default(),
             natural_size: Size::default(),
             requested_size: OptionalSize::default(),
+            event_handlers: ElementEventHandlers::default(),
+            style_context: None,
+            template_key: None,
         }
     }
 
@@ -59,10 +67,16 @@ impl Element for Image {
 
     fn update(&mut self) {}
 
-    fn handle_event(&mut self, _: Point, _: &EventType) {}
+    fn handle_event(&mut self, cursor_position: Point, event_type: &EventType) {
+        if let Some(style_context) = &self.style_context {
+            let is_within_bounds = event_propagator::is_within_bounds(self, cursor_position);
+            style_context.update_interaction_state(is_within_bounds, event_type, self.event_handlers.is_focusable());
+        }
+    }
     
-    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType) -> Vec<String> {
-        event_propagator::propagate_event(self, cursor_position, event_type)
+    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType, _: &EventPayload) -> Vec<EventHandlerCall> {
+        let is_within_bounds = event_propagator::is_within_bounds(self, cursor_position);
+        self.event_handlers.collect_handler_calls(is_within_bounds, event_type)
     }
 
     fn set_id(&mut self, id: String) {
@@ -81,12 +95,34 @@ impl Element for Image {
         self.styles = styles;
     }
 
+    fn set_event_handlers(&mut self, event_handlers: ElementEventHandlers) {
+        self.event_handlers = event_handlers;
+    }
+
+    fn set_inputs(&mut self, _: HashMap<String, Value>) {}
+
+    fn set_projected_content(&mut self, _: Vec<ProjectedElement>) {}
+
+    fn load_outdated_template(&mut self) -> RestyleImpact { RestyleImpact::None }
+
     fn add_child(&mut self, _: Box<dyn Element>) {}
 
     fn get_id(&self) -> String {
         self._id.clone()
     }
 
+    fn set_template_key(&mut self, template_key: TemplateKey) {
+        self.template_key = Some(template_key);
+    }
+
+    fn get_template_key(&self) -> Option<TemplateKey> {
+        self.template_key.clone()
+    }
+
+    fn get_outputs(&self) -> Vec<String> {
+        vec![]
+    }
+
     fn get_element_type(&self) -> ElementType {
         ElementType::Image
     }
@@ -135,7 +171,7 @@ impl Element for Image {
         self.set_natural_size(estimated_image_size);
 
         let sizing_policy = self.get_styles().sizing_policy.unwrap_or_default();
-        self.set_requested_size(OptionalSize { width: sizing_policy.width, height: sizing_policy.height });
+        self.set_requested_size(effective_size_estimator::estimate_requested_size(&sizing_policy.width, &sizing_policy.height));
     }
 
     fn allocate_space(&mut self, allocated_position: Position, allocated_size: Size) {
@@ -148,6 +184,45 @@ impl Element for Image {
         self.allocate_space(allocated_position, allocated_size);
     }
 
-    fn react_to_state_change(&mut self, component_id: String) {}
-}
+    fn react_to_state_change(&mut self, _: String, _: &str) -> RestyleImpact { RestyleImpact::None }
+
+    fn react_to_output(&mut self, _: &mut OutputEvent) {}
+
+    fn patch(&mut self, new_element: Box<dyn Element>) -> RestyleImpact {
+        match new_element.into_any().downcast::<Image>() {
+            Ok(new_image) => {
+                let mut restyle_impact = RestyleImpact::between(&self.styles, &new_image.styles);
+                self._id = new_image._id;
+                self.template_key = new_image.template_key;
+                self.styles = new_image.styles;
+                self.event_handlers.update_handlers(new_image.event_handlers);
+                style_context::replace_style_context(&mut self.style_context, new_image.style_context);
+                if self.image_path != new_image.image_path {
+                    self.image_path = new_image.image_path;
+                    self.image = new_image.image;
+                    restyle_impact = RestyleImpact::Layout;
+                }
+                restyle_impact
+            },
+            Err(_) => RestyleImpact::None,
+        }
+    }
 
+    fn into_any(self: Box<Self>) -> Box<dyn Any> { self }
+
+    // Interactive styles
+    fn set_style_context(&mut self, style_context: StyleContext) {
+        self.style_context = Some(style_context);
+    }
+
+    fn restyle(&mut self) -> RestyleImpact {
+        match self.style_context.as_mut().and_then(StyleContext::restyle) {
+            Some(styles) => {
+                let restyle_impact = RestyleImpact::between(&self.styles, &styles);
+                self.styles = styles;
+                restyle_impact
+            },
+            None => RestyleImpact::None,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,53 @@
+use super::element::EventType;
+
+
+/*
+ * State of an element matched by the :hover, :active and :focus pseudo-classes, updated from the mouse events.
+ * As in browsers, the ancestors of the hovered or pressed element are hovered or active as well, as they contain the cursor.
+ * The :disabled pseudo-class is matched against the disabled attribute instead, see StyledElement::is_disabled.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InteractionState {
+    pub is_hovered: bool,
+    pub is_active: bool,
+    pub is_focused: bool,
+}
+
+impl InteractionState {
+    /*
+     * Returns the state following a mouse event. The element is active from the press of the left button on it
+     * to its release, and focused when pressed if focusable, until a press elsewhere.
+     */
+    pub fn after_event(self, is_within_bounds: bool, event_type: &EventType, is_focusable: bool, is_disabled: bool) -> Self {
+        let mut state = self;
+
+        match event_type {
+            EventType::MouseMove | EventType::MouseDrag => state.is_hovered = is_within_bounds,
+            EventType::MouseDown => {
+                state.is_hovered = is_within_bounds;
+                state.is_active = is_within_bounds;
+                state.is_focused = is_within_bounds && is_focusable;
+            },
+            EventType::MouseUp => {
+                state.is_hovered = is_within_bounds;
+                state.is_active = false;
+            },
+            // The cursor left the window
+            EventType::MouseLeave => {
+                state.is_hovered = false;
+                state.is_active = false;
+            },
+            _ => {},
+        }
+
+        state.adjusted_to_disabled(is_disabled)
+    }
+
+    // Disabled elements are neither active nor focused
+    pub fn adjusted_to_disabled(self, is_disabled: bool) -> Self {
+        match is_disabled {
+            true => InteractionState { is_active: false, is_focused: false, ..self },
+            false => self,
+        }
+    }
+}
@@ -11,4 +11,8 @@ pub mod component;
 pub mod common_types;
 pub mod styles;
 
+pub mod element_event_handlers;
+pub mod interaction_state;
+pub mod element_patcher;
+pub mod event_payload;
 mod event_propagator;
\ No newline at end of file
@@ -1,7 +1,7 @@
 use skia_safe::Color;
 
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Styles {
     pub display: Option<DisplayType>,
     pub flex_direction: Option<FlexDirection>,
@@ -59,7 +59,7 @@ impl Default for Styles {
 }
 
 // Layout properties
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DisplayType {
     Block,          // The element takes up the full width of its container, starting on a new line.
     InlineBlock,    // The element does not start on a new line but can have width and height set.
@@ -112,7 +112,7 @@ impl Default for JustifyContent {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum AlignItems {
     FlexStart,      // Items are aligned at the start of the cross axis.
     FlexEnd,        // Items are aligned at the end of the cross axis.
@@ -127,7 +127,7 @@ impl Default for AlignItems {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum AlignContent {
     FlexStart,      // Lines are packed at the start of the container.
     FlexEnd,        // Lines are packed at the end of the container.
@@ -159,14 +159,14 @@ impl Default for Overflow {
 }
 
 // Dimension properties
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct SizingPolicy {
-    pub width: Option<Dimension>,
-    pub height: Option<Dimension>,
-    pub min_width: Option<Dimension>,
-    pub max_width: Option<Dimension>,
-    pub min_height: Option<Dimension>,
-    pub max_height: Option<Dimension>,
+    pub width: Option<Length>,
+    pub height: Option<Length>,
+    pub min_width: Option<Length>,
+    pub max_width: Option<Length>,
+    pub min_height: Option<Length>,
+    pub max_height: Option<Length>,
 }
 
 impl Default for SizingPolicy {
@@ -182,7 +182,7 @@ impl Default for SizingPolicy {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Dimension {
     pub value: f32,
     pub unit: Unit,
@@ -212,7 +212,33 @@ impl Default for Unit {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+/*
+ * Value of the sizing properties (width, height and their bounds): a dimension,
+ * or a calc() expression combining several units, e.g. calc(100% - 2rem).
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Length {
+    Dimension(Dimension),
+    Calc(CalcSum),
+}
+
+// Font size of the root element, which the rem unit is relative to
+pub const ROOT_FONT_SIZE: f32 = 16.0;
+
+/*
+ * calc() expression reduced to the sum of its terms per unit, e.g. calc((100% - 2rem) / 2) to 50% - 1rem.
+ * The percentages and viewport units are resolved during layout, see dimension_resolver.rs.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CalcSum {
+    pub px: f32,
+    pub vh: f32,
+    pub vw: f32,
+    pub rem: f32,
+    pub percent: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Spacing {
     pub spacing_x: Dimension,
     pub spacing_y: Dimension,
@@ -227,7 +253,7 @@ impl Default for Spacing {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Margin {
     pub top: Dimension,
     pub right: Dimension,
@@ -256,7 +282,7 @@ impl Margin {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Padding {
     pub top: Dimension,
     pub right: Dimension,
@@ -286,7 +312,7 @@ impl Padding {
 }
 
 // Appearance properties
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Border {
     pub width: Dimension,
     pub color: Color,
@@ -303,7 +329,7 @@ impl Default for Border {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct BorderRadius {
     pub top_left: Dimension,
     pub top_right: Dimension,
@@ -322,7 +348,7 @@ impl Default for BorderRadius {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Directions {
     pub horizontal: bool,
     pub vertical: bool,
@@ -353,7 +379,7 @@ impl Default for WhiteSpace {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum FontFamily {
     Arial,
     Helvetica,
@@ -380,7 +406,7 @@ impl FontFamily {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum FontWeight {
     FW100,
     FW200,
@@ -415,7 +441,7 @@ impl FontWeight {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum FontStyle {
     Normal,
     Italic,
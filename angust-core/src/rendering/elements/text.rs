@@ -1,18 +1,22 @@
+use std::{any::Any, collections::HashMap};
+
 use skia_safe::{Canvas, Color, Point};
 
-use crate::rendering::{layout::space_allocation_system::text::size_estimator::{determine_text_element_lines, estimate_text_element_size}, rendering_interface::element_renderer::ElementRenderer};
+use crate::{parsing::{css::style_context::{self, RestyleImpact, StyleContext}, expression::value::Value}, rendering::{layout::space_allocation_system::text::size_estimator::{determine_text_element_lines, estimate_text_element_size}, rendering_interface::element_renderer::ElementRenderer}};
 
-use super::{common_types::{OptionalSize, Position, Size}, element::{Element, ElementType, EventType}, element_id_generator::IDGenerator, event_propagator, styles::{Dimension, Styles, Unit, WhiteSpace}};
+use super::{common_types::{OptionalSize, Position, Size}, component::{component_outputs::OutputEvent, content_projection::ProjectedElement, functions::event_handler_call::EventHandlerCall}, element::{Element, ElementType, EventType}, element_event_handlers::ElementEventHandlers, element_id_generator::IDGenerator, element_patcher::TemplateKey, event_payload::EventPayload, event_propagator, styles::{Dimension, Styles, Unit, WhiteSpace}};
 
 
 pub struct Text {
     _id: String,
+    template_key: Option<TemplateKey>,
     content: String,
     lines: Vec<String>,
     position: Position,
     size: Size,
     styles: Styles,
     natural_size: Size,
+    style_context: Option<StyleContext>,
 }
 
 impl Text {
@@ -26,6 +30,8 @@ impl Text {
             size: Size::default(),
             styles: Styles::default(),
             natural_size: Size::default(),
+            style_context: None,
+            template_key: None,
         }
     }
 
@@ -62,8 +68,8 @@ impl Element for Text {
 
     fn handle_event(&mut self, _: Point, _: &EventType) {}
     
-    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType) -> Vec<String> {
-        event_propagator::propagate_event(self, cursor_position, event_type)
+    fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType, event_payload: &EventPayload) -> Vec<EventHandlerCall> {
+        event_propagator::propagate_event(self, cursor_position, event_type, event_payload)
     }
 
     fn set_id(&mut self, id: String) {
@@ -81,6 +87,14 @@ impl Element for Text {
     fn set_styles(&mut self, styles: Styles) {
         self.styles = styles;
     }
+
+    fn set_event_handlers(&mut self, _: ElementEventHandlers) {}
+
+    fn set_inputs(&mut self, _: HashMap<String, Value>) {}
+
+    fn set_projected_content(&mut self, _: Vec<ProjectedElement>) {}
+
+    fn load_outdated_template(&mut self) -> RestyleImpact { RestyleImpact::None }
     
     fn add_child(&mut self, _: Box<dyn Element>) {}
 
@@ -88,6 +102,18 @@ impl Element for Text {
         self._id.clone()
     }
 
+    fn set_template_key(&mut self, template_key: TemplateKey) {
+        self.template_key = Some(template_key);
+    }
+
+    fn get_template_key(&self) -> Option<TemplateKey> {
+        self.template_key.clone()
+    }
+
+    fn get_outputs(&self) -> Vec<String> {
+        vec![]
+    }
+
     fn get_element_type(&self) -> ElementType {
         ElementType::Text
     }
@@ -150,5 +176,44 @@ impl Element for Text {
         self.allocate_space(allocated_position, allocated_size);
     }
 
-    fn react_to_state_change(&mut self, component_id: String) {}
+    fn react_to_state_change(&mut self, _: String, _: &str) -> RestyleImpact { RestyleImpact::None }
+
+    fn react_to_output(&mut self, _: &mut OutputEvent) {}
+
+    fn patch(&mut self, new_element: Box<dyn Element>) -> RestyleImpact {
+        match new_element.into_any().downcast::<Text>() {
+            Ok(new_text) => {
+                let mut restyle_impact = RestyleImpact::between(&self.styles, &new_text.styles);
+                self._id = new_text._id;
+                self.template_key = new_text.template_key;
+                self.styles = new_text.styles;
+                style_context::replace_style_context(&mut self.style_context, new_text.style_context);
+                if self.content != new_text.content {
+                    self.lines = vec![new_text.content.clone()];
+                    self.content = new_text.content;
+                    restyle_impact = RestyleImpact::Layout;
+                }
+                restyle_impact
+            },
+            Err(_) => RestyleImpact::None,
+        }
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> { self }
+
+    // Interactive styles
+    fn set_style_context(&mut self, style_context: StyleContext) {
+        self.style_context = Some(style_context);
+    }
+
+    fn restyle(&mut self) -> RestyleImpact {
+        match self.style_context.as_mut().and_then(StyleContext::restyle) {
+            Some(styles) => {
+                let restyle_impact = RestyleImpact::between(&self.styles, &styles);
+                self.styles = styles;
+                restyle_impact
+            },
+            None => RestyleImpact::None,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,55 @@
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+
+use crate::rendering::elements::{common_types::Size, styles::{CalcSum, Dimension, Length, Unit, ROOT_FONT_SIZE}};
+
+
+// Size of the window, which the vh and vw units are relative to, set before each layout
+static VIEWPORT_SIZE: Lazy<RwLock<Size>> = Lazy::new(|| RwLock::new(Size::default()));
+
+pub fn set_viewport_size(viewport_size: Size) {
+    *VIEWPORT_SIZE.write().unwrap() = viewport_size;
+}
+
+pub fn get_viewport_size() -> Size {
+    *VIEWPORT_SIZE.read().unwrap()
+}
+
+/*
+ * Resolves the length to pixels, the percentages being relative to the given size of the parent.
+ */
+pub fn resolve_length(length: &Length, percentage_base: f32) -> f32 {
+    match length {
+        Length::Dimension(dimension) => resolve_dimension(dimension, percentage_base),
+        Length::Calc(sum) => resolve_calc_sum(sum, percentage_base),
+    }
+}
+
+pub fn resolve_dimension(dimension: &Dimension, percentage_base: f32) -> f32 {
+    let viewport_size = get_viewport_size();
+
+    let unit_size = match dimension.unit {
+        Unit::Px => 1.0,
+        Unit::Vh => viewport_size.height / 100.0,
+        Unit::Vw => viewport_size.width / 100.0,
+        Unit::Rem => ROOT_FONT_SIZE,
+        Unit::Percent => percentage_base / 100.0,
+    };
+    dimension.value * unit_size
+}
+
+fn resolve_calc_sum(sum: &CalcSum, percentage_base: f32) -> f32 {
+    let viewport_size = get_viewport_size();
+
+    sum.px + sum.vh * viewport_size.height / 100.0 + sum.vw * viewport_size.width / 100.0
+        + sum.rem * ROOT_FONT_SIZE + sum.percent * percentage_base / 100.0
+}
+
+// Whether the length can only be resolved once the size of the parent is known
+pub fn depends_on_parent_size(length: &Length) -> bool {
+    match length {
+        Length::Dimension(dimension) => dimension.unit == Unit::Percent,
+        Length::Calc(sum) => sum.percent != 0.0,
+    }
+}
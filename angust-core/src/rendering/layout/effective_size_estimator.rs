@@ -1,4 +1,6 @@
-use crate::rendering::elements::{common_types::{OptionalSize, Size}, container::Container, element::Element, styles::{Dimension, Margin, Unit}};
+use crate::rendering::elements::{common_types::{OptionalSize, Size}, container::Container, element::Element, styles::{Dimension, Length, Margin, Unit}};
+
+use super::dimension_resolver;
 
 // Width computations
 pub fn estimate_effective_size(requested_size: &OptionalSize, natural_size: &Size) -> Size {
@@ -20,24 +22,20 @@ pub fn estimate_effective_size(requested_size: &OptionalSize, natural_size: &Siz
 }
 
 // - Ignore percentage-width children in the first pass of layout algorithm
-pub fn estimate_requested_size(width: &Option<Dimension>, height: &Option<Dimension>) -> OptionalSize {
-    let mut requested_size = OptionalSize::default();
-    if let Some(width) = width {
-        if width.unit != Unit::Percent {
-            requested_size.width = Some(width.clone());
-        } else {
-            requested_size.width = Some(Dimension { value: 0.0, unit: Unit::Percent });
-        }
-    } 
-    if let Some(height) = height {
-        if height.unit != Unit::Percent {
-            requested_size.height = Some(height.clone());
-        } else {
-            requested_size.height = Some(Dimension { value: 0.0, unit: Unit::Percent });
-        }
+// - Resolve the other dimensions to pixels, e.g. the viewport units and calc() expressions without percentages
+pub fn estimate_requested_size(width: &Option<Length>, height: &Option<Length>) -> OptionalSize {
+    OptionalSize {
+        width: width.as_ref().map(estimate_requested_dimension),
+        height: height.as_ref().map(estimate_requested_dimension),
     }
+}
 
-    requested_size
+fn estimate_requested_dimension(length: &Length) -> Dimension {
+    if dimension_resolver::depends_on_parent_size(length) {
+        Dimension { value: 0.0, unit: Unit::Percent }
+    } else {
+        Dimension { value: dimension_resolver::resolve_length(length, 0.0), unit: Unit::Px }
+    }
 }
 
 /* 
@@ -50,13 +48,16 @@ pub fn estimate_percentage_width_sizes(container: &mut Container, allocated_widt
         if child.get_styles().sizing_policy.unwrap_or_default().width.is_none() {
             continue;
         }
-        let dimension = child.get_styles().sizing_policy.unwrap_or_default().width.unwrap();
-        if dimension.unit != Unit::Percent {
+        let length = child.get_styles().sizing_policy.unwrap_or_default().width.unwrap();
+        if !dimension_resolver::depends_on_parent_size(&length) {
             continue;
         }
 
-        let effective_percentage = dimension.value * scale_factor;
-        let calculated_width = (effective_percentage / 100.0) * allocated_width;
+        // The percentages of calc() expressions are not scaled down with the others
+        let calculated_width = match length {
+            Length::Dimension(Dimension { value, unit: Unit::Percent }) => (value * scale_factor / 100.0) * allocated_width,
+            _ => dimension_resolver::resolve_length(&length, allocated_width),
+        };
 
         child.set_requested_size(OptionalSize {
             width: Some(Dimension {
@@ -1,4 +1,5 @@
 pub mod size_estimator;
 pub mod effective_size_estimator;
+pub mod dimension_resolver;
 
 pub mod space_allocation_system;
\ No newline at end of file
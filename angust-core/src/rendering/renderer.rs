@@ -5,7 +5,9 @@ use skia_safe::{
 use winit::window::Window;
 use skia_safe::gpu::DirectContext;
 
-use super::{elements::{common_types::{Position, Size}, element::{Element, EventType}}, ui_manager::UIManager};
+use crate::parsing::css::style_context::{self, RestyleImpact};
+
+use super::{layout::dimension_resolver, elements::{common_types::{Position, Size}, component::{component_outputs::OutputEvent, functions::event_handler_call::EventHandlerCall}, element::{Element, EventType}, event_payload::EventPayload}, ui_manager::UIManager};
 
 pub struct Renderer {
     pub surface: Surface,
@@ -32,6 +34,7 @@ impl Renderer {
     }
 
     pub fn layout(self: &mut Self) {
+        dimension_resolver::set_viewport_size(self.screen_size);
         self.ui_manager.layout(
             Position { x: 0.0, y: 0.0 },
             self.screen_size
@@ -49,12 +52,24 @@ impl Renderer {
         self.ui_manager.handle_event(cursor_position, &event_type);
     }
 
-    pub fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType) -> Vec<String> {
-        self.ui_manager.propagate_event(cursor_position, event_type)
+    pub fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType, event_payload: &EventPayload) -> Vec<EventHandlerCall> {
+        self.ui_manager.propagate_event(cursor_position, event_type, event_payload)
+    }
+
+    pub fn react_to_state_change(&mut self, component_id: String, property_name: &str) -> RestyleImpact {
+        self.ui_manager.react_to_state_change(component_id, property_name)
     }
 
-    pub fn react_to_state_change(&mut self, component_id: String) {
-        self.ui_manager.react_to_state_change(component_id);
+    pub fn react_to_output(&mut self, output_event: &mut OutputEvent) {
+        self.ui_manager.react_to_output(output_event);
+    }
+
+    // Restyles the elements affected by the changes since the last restyle, if any, see style_context::request_restyle
+    pub fn restyle(&mut self) -> RestyleImpact {
+        match style_context::take_pending_restyle() {
+            true => self.ui_manager.restyle(),
+            false => RestyleImpact::None,
+        }
     }
     
     fn create_surface(
@@ -1,4 +1,6 @@
-use super::elements::{common_types::{Position, Size}, element::{Element, EventType}};
+use crate::parsing::css::style_context::RestyleImpact;
+
+use super::elements::{common_types::{Position, Size}, component::{component_outputs::OutputEvent, functions::event_handler_call::EventHandlerCall}, element::{Element, EventType}, event_payload::EventPayload};
 
 pub struct UIManager {
     root_element: Box<dyn Element>,
@@ -22,15 +24,23 @@ impl UIManager {
         self.root_element.handle_event(cursor_position, event_type);
     }
 
-    pub fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType) -> Vec<String> {
-        self.root_element.propagate_event(cursor_position, event_type)
+    pub fn propagate_event(&mut self, cursor_position: skia_safe::Point, event_type: &EventType, event_payload: &EventPayload) -> Vec<EventHandlerCall> {
+        self.root_element.propagate_event(cursor_position, event_type, event_payload)
     }
 
     pub fn layout(&mut self, allocated_position: Position, allocated_size: Size) {
         self.root_element.layout(allocated_position, allocated_size);
     }
 
-    pub fn react_to_state_change(&mut self, component_id: String) {
-        self.root_element.react_to_state_change(component_id);
+    pub fn react_to_state_change(&mut self, component_id: String, property_name: &str) -> RestyleImpact {
+        self.root_element.react_to_state_change(component_id, property_name)
+    }
+
+    pub fn react_to_output(&mut self, output_event: &mut OutputEvent) {
+        self.root_element.react_to_output(output_event);
+    }
+
+    pub fn restyle(&mut self) -> RestyleImpact {
+        self.root_element.restyle()
     }
 }
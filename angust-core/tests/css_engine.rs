@@ -0,0 +1,544 @@
+use std::{env, fs, process, rc::Rc, sync::Arc};
+
+use angust::{
+    application::resource_loader::stylesheet_loader,
+    parsing::{
+        css::{
+            cascade::{self, DeclarationOrigin},
+            css_parser,
+            custom_properties,
+            css_tokenizer::{self, TokenKind},
+            selector_matcher::{self, StyledElement},
+            selector_parser::{self, Selector},
+            stylesheet_parser::{self, AtRuleBlock, Stylesheet},
+        },
+        diagnostics::{Diagnostic, Severity},
+        html::html_parser,
+    },
+    rendering::elements::{interaction_state::InteractionState, styles::{CalcSum, Dimension, Length, Margin, Padding, Styles, Unit}},
+};
+
+
+fn parse_stylesheet(source: &str) -> (Stylesheet, Vec<Diagnostic>) {
+    let mut diagnostics = Vec::new();
+    let stylesheet = stylesheet_parser::parse_stylesheet(source, Some("styles.css"), &mut diagnostics);
+    (stylesheet, diagnostics)
+}
+
+// Declarations of the rule at the given index, as (name, value) pairs
+fn declarations(stylesheet: &Stylesheet, rule_index: usize) -> Vec<(&str, &str)> {
+    stylesheet.rules[rule_index].declarations.iter()
+        .map(|declaration| (declaration.name.as_str(), declaration.value.as_str()))
+        .collect()
+}
+
+fn parse_selector(source: &str) -> Result<Selector, String> {
+    selector_parser::parse_selector(&css_tokenizer::tokenize(source, &mut Vec::new()))
+}
+
+/*
+ * Path of the first element of the document matching the CSS selector, from the root of the document (<html>) to the element.
+ */
+fn element_path(html: &str, element_selector: &str) -> Vec<Rc<StyledElement>> {
+    let document = html_parser::parse_html_content(html);
+    let element = document.select_first(element_selector).expect("No element matches the selector");
+
+    let mut path: Vec<Rc<StyledElement>> = element.as_node().inclusive_ancestors()
+        .filter_map(|node| node.into_element_ref())
+        .map(|ancestor| Rc::new(StyledElement::new(&ancestor.name.local, &ancestor.attributes.borrow())))
+        .collect();
+    path.reverse();
+    path
+}
+
+fn matches(selector: &str, element_path: &[Rc<StyledElement>]) -> bool {
+    selector_matcher::matches_selector(&parse_selector(selector).expect("Failed to parse selector"), element_path)
+}
+
+/*
+ * Value and origin of the declaration winning the cascade for the property on the last element of the path,
+ * given the stylesheet and the inline declarations of the element.
+ */
+fn cascaded_declaration(element_path: &[Rc<StyledElement>], css: &str, inline_css: &str, property: &str) -> Option<(String, DeclarationOrigin)> {
+    let (stylesheet, _) = parse_stylesheet(css);
+    let inline_declarations = stylesheet_parser::parse_declarations(inline_css, &mut Vec::new());
+
+    cascade::cascade_declarations(element_path, Some(&stylesheet), &inline_declarations).into_iter()
+        .find(|cascaded_declaration| cascaded_declaration.declaration.name == property)
+        .map(|cascaded_declaration| (cascaded_declaration.declaration.value.clone(), cascaded_declaration.origin))
+}
+
+fn cascaded_value(element_path: &[Rc<StyledElement>], css: &str, property: &str) -> Option<String> {
+    cascaded_declaration(element_path, css, "", property).map(|(value, _)| value)
+}
+
+/*
+ * Computed styles of the first element of the document matching the CSS selector, its ancestors being styled first
+ * for it to inherit their styles and custom properties, with the warnings of the whole path.
+ */
+fn computed_styles(html: &str, element_selector: &str, css: &str) -> (Styles, Vec<String>) {
+    let path = element_path(html, element_selector);
+    let (stylesheet, _) = parse_stylesheet(css);
+    let stylesheet = Some(Arc::new(stylesheet));
+
+    let mut warnings = Vec::new();
+    let mut styles = None;
+    for path_length in 1..=path.len() {
+        styles = Some(css_parser::parse_styles(&path[..path_length], styles.as_ref(), &stylesheet, &mut warnings));
+    }
+    (styles.expect("The path is empty"), warnings)
+}
+
+fn computed_width(css: &str) -> Option<Length> {
+    let (styles, _) = computed_styles(TOOLBAR, "#save", css);
+    styles.sizing_policy.and_then(|sizing_policy| sizing_policy.width)
+}
+
+fn dimension(value: f32, unit: Unit) -> Option<Length> {
+    Some(Length::Dimension(Dimension { value, unit }))
+}
+
+fn calc(sum: CalcSum) -> Option<Length> {
+    Some(Length::Calc(sum))
+}
+
+static TOOLBAR: &str = r#"<div class="toolbar"><button id="save" class="primary large" type="submit" lang="en-US">Save</button><div><button>Nested</button></div></div>"#;
+
+// Tokenizer
+#[test]
+fn comments_are_dropped_by_the_tokenizer() {
+    let tokens = css_tokenizer::tokenize("a /* b { c } */ d", &mut Vec::new());
+
+    let words: Vec<&str> = tokens.iter()
+        .filter(|token| token.kind != TokenKind::Whitespace)
+        .map(|token| token.text.as_str())
+        .collect();
+    assert_eq!(words, vec!["a", "d"]);
+}
+
+#[test]
+fn strings_are_single_tokens_keeping_their_quotes() {
+    let tokens = css_tokenizer::tokenize(r#""A; {B}""#, &mut Vec::new());
+
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].kind, TokenKind::String);
+    assert_eq!(tokens[0].text, r#""A; {B}""#);
+}
+
+#[test]
+fn unterminated_comments_and_strings_are_reported() {
+    let mut diagnostics = Vec::new();
+    css_tokenizer::tokenize("'open\n/* open", &mut diagnostics);
+
+    let messages: Vec<&str> = diagnostics.iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+    assert_eq!(messages, vec!["Unterminated string", "Unterminated comment"]);
+    assert_eq!(diagnostics[1].location.map(|location| location.line), Some(2));
+}
+
+// Stylesheet parser
+#[test]
+fn rules_written_on_one_line_keep_their_declarations() {
+    let (stylesheet, diagnostics) = parse_stylesheet(".a { color: red; width: 10px; }");
+
+    assert!(diagnostics.is_empty());
+    assert_eq!(declarations(&stylesheet, 0), vec![("color", "red"), ("width", "10px")]);
+}
+
+#[test]
+fn values_containing_colons_are_kept_whole() {
+    let (stylesheet, _) = parse_stylesheet(".a { background: url(http://example.com/a.png) }");
+
+    assert_eq!(declarations(&stylesheet, 0), vec![("background", "url(http://example.com/a.png)")]);
+}
+
+#[test]
+fn semicolons_within_strings_do_not_end_declarations() {
+    let (stylesheet, _) = parse_stylesheet(r#".a { font-family: "A; B", sans-serif; color: red }"#);
+
+    assert_eq!(declarations(&stylesheet, 0), vec![("font-family", r#""A; B", sans-serif"#), ("color", "red")]);
+}
+
+#[test]
+fn comments_are_ignored_between_and_within_rules() {
+    let (stylesheet, diagnostics) = parse_stylesheet("/* header */\n.a { /* color: blue; */ color: red; }\n/* .b { color: blue } */");
+
+    assert!(diagnostics.is_empty());
+    assert_eq!(stylesheet.rules.len(), 1);
+    assert_eq!(declarations(&stylesheet, 0), vec![("color", "red")]);
+}
+
+#[test]
+fn important_declarations_are_flagged_without_the_annotation_in_their_value() {
+    let (stylesheet, _) = parse_stylesheet(".a { color: red !important; width: 10px ! IMPORTANT; height: 5px }");
+
+    let rule = &stylesheet.rules[0];
+    assert_eq!(rule.declarations[0].value, "red");
+    assert!(rule.declarations[0].is_important);
+    assert_eq!(rule.declarations[1].value, "10px");
+    assert!(rule.declarations[1].is_important);
+    assert!(!rule.declarations[2].is_important);
+}
+
+#[test]
+fn property_names_are_lowercased_except_custom_properties() {
+    let (stylesheet, _) = parse_stylesheet(".a { COLOR: red; --Primary-Color: blue }");
+
+    assert_eq!(declarations(&stylesheet, 0), vec![("color", "red"), ("--Primary-Color", "blue")]);
+}
+
+#[test]
+fn selector_lists_give_a_selector_per_item() {
+    let (stylesheet, _) = parse_stylesheet(".a,\n.b > button, #c { color: red }");
+
+    assert_eq!(stylesheet.rules[0].selectors.len(), 3);
+}
+
+#[test]
+fn invalid_declarations_are_reported_and_skipped() {
+    let (stylesheet, diagnostics) = parse_stylesheet(".a {\n  color red;\n  width: 10px;\n  : 5px;\n}");
+
+    assert_eq!(declarations(&stylesheet, 0), vec![("width", "10px")]);
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics.iter().all(|diagnostic| diagnostic.severity == Severity::Error));
+    assert_eq!(diagnostics[0].template_path.as_deref(), Some("styles.css"));
+    assert_eq!(diagnostics[0].location.map(|location| location.line), Some(2));
+}
+
+#[test]
+fn parsing_recovers_after_an_invalid_rule() {
+    let (stylesheet, diagnostics) = parse_stylesheet(".a > > b { color: red }\n.c { color: blue }");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(stylesheet.rules.len(), 1);
+    assert_eq!(declarations(&stylesheet, 0), vec![("color", "blue")]);
+}
+
+#[test]
+fn at_rules_keep_their_prelude_and_block() {
+    let (stylesheet, diagnostics) = parse_stylesheet(
+        "@import url(\"theme.css\");\n@media (max-width: 600px) { .a { color: red } }\n@font-face { font-family: Inter; src: url(inter.woff) }"
+    );
+
+    assert!(diagnostics.is_empty());
+    assert!(stylesheet.rules.is_empty());
+
+    let at_rules = &stylesheet.at_rules;
+    assert_eq!((at_rules[0].name.as_str(), at_rules[0].prelude.as_str()), ("import", "url(\"theme.css\")"));
+    assert!(at_rules[0].block.is_none());
+    assert!(matches!(&at_rules[1].block, Some(AtRuleBlock::Rules(rules)) if rules.rules.len() == 1));
+    assert!(matches!(&at_rules[2].block, Some(AtRuleBlock::Declarations(declarations)) if declarations.len() == 2));
+}
+
+#[test]
+fn loaded_stylesheets_report_the_unsupported_at_rules() {
+    let styles_dir = env::temp_dir().join(format!("angust-styles-{}", process::id()));
+    fs::create_dir_all(&styles_dir).unwrap();
+    fs::write(styles_dir.join("theme.css"), ".a { color: green }").unwrap();
+    fs::write(styles_dir.join("styles.css"), "@import \"theme.css\";\n@media (max-width: 600px) { .b { color: red } }\n.c { color: blue }").unwrap();
+
+    let mut diagnostics = Vec::new();
+    let stylesheet = stylesheet_loader::load_stylesheet(styles_dir.to_str().unwrap(), "styles.css", &mut diagnostics).unwrap();
+    fs::remove_dir_all(&styles_dir).unwrap();
+
+    assert_eq!(stylesheet.rules.len(), 2);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!((diagnostics[0].severity, diagnostics[0].message.as_str()), (Severity::Warning, "Unsupported at-rule @media, ignored"));
+    assert_eq!(diagnostics[0].location.map(|location| location.line), Some(2));
+}
+
+#[test]
+fn inline_declarations_are_parsed_without_braces() {
+    let mut diagnostics = Vec::new();
+    let declarations = stylesheet_parser::parse_declarations("width: 10px; colour red; height: 5px", &mut diagnostics);
+
+    assert_eq!(declarations.len(), 2);
+    assert_eq!(diagnostics.len(), 1);
+}
+
+// Selectors
+#[test]
+fn type_id_class_and_compound_selectors_match_the_element() {
+    let path = element_path(TOOLBAR, "#save");
+
+    assert!(matches("button", &path));
+    assert!(matches("#save", &path));
+    assert!(matches(".large", &path));
+    assert!(matches("button.primary.large#save", &path));
+    assert!(matches("*", &path));
+    assert!(!matches("div", &path));
+    assert!(!matches("button.secondary", &path));
+}
+
+#[test]
+fn child_combinators_only_match_the_parent() {
+    let save_button = element_path(TOOLBAR, "#save");
+    let nested_button = element_path(TOOLBAR, "div div button");
+
+    assert!(matches(".toolbar > button", &save_button));
+    assert!(!matches(".toolbar > button", &nested_button));
+    assert!(matches("body > .toolbar > div > button", &nested_button));
+}
+
+#[test]
+fn descendant_combinators_match_any_ancestor() {
+    let nested_button = element_path(TOOLBAR, "div div button");
+
+    assert!(matches(".toolbar button", &nested_button));
+    assert!(matches("html .toolbar div button", &nested_button));
+    assert!(!matches(".toolbar .toolbar button", &nested_button));
+}
+
+#[test]
+fn attribute_selectors_match_with_their_operator() {
+    let path = element_path(TOOLBAR, "#save");
+
+    assert!(matches("[type]", &path));
+    assert!(matches(r#"[type="submit"]"#, &path));
+    assert!(matches("[class~=primary]", &path));
+    assert!(matches("[lang|=en]", &path));
+    assert!(matches("[id^=sa]", &path));
+    assert!(matches("[id$=ve]", &path));
+    assert!(matches("[id*=av]", &path));
+    assert!(!matches("[class~=prim]", &path));
+    assert!(!matches("[lang|=US]", &path));
+    assert!(!matches("[disabled]", &path));
+}
+
+#[test]
+fn interaction_pseudo_classes_match_the_state_of_the_element() {
+    let path = element_path(TOOLBAR, "#save");
+    let hover_selector = parse_selector(".toolbar:hover > button").unwrap();
+
+    assert!(!selector_matcher::matches_selector(&hover_selector, &path));
+    assert!(selector_matcher::matches_selector_in_any_state(&hover_selector, &path));
+
+    path[path.len() - 2].set_interaction_state(InteractionState { is_hovered: true, ..Default::default() });
+    assert!(selector_matcher::matches_selector(&hover_selector, &path));
+}
+
+#[test]
+fn disabled_and_root_pseudo_classes_match_without_interaction_state() {
+    let path = element_path(r#"<button disabled>Save</button>"#, "button");
+
+    assert!(matches("button:disabled", &path));
+    assert!(matches(":root button", &path));
+    assert!(!matches("button:root", &path));
+}
+
+#[test]
+fn specificity_compares_ids_then_classes_then_types() {
+    let specificity = |selector: &str| parse_selector(selector).unwrap().specificity();
+
+    assert!(specificity("#a") > specificity(".a.b.c"));
+    assert!(specificity(".a.b") > specificity(".a button"));
+    assert!(specificity("[type]") == specificity(":hover"));
+    assert!(specificity("div button") > specificity("button"));
+    assert!(specificity("button") > specificity("*"));
+}
+
+#[test]
+fn unsupported_and_incomplete_selectors_are_rejected() {
+    assert!(parse_selector(".a + .b").is_err());
+    assert!(parse_selector(".a ~ .b").is_err());
+    assert!(parse_selector(".a >").is_err());
+    assert!(parse_selector(".").is_err());
+}
+
+// Cascade
+#[test]
+fn more_specific_rules_win_regardless_of_source_order() {
+    let path = element_path(TOOLBAR, "#save");
+
+    assert_eq!(cascaded_value(&path, "#save { color: red } .primary.large { color: blue } button { color: green }", "color").as_deref(), Some("red"));
+    assert_eq!(cascaded_value(&path, ".toolbar button { color: red } button { color: blue }", "color").as_deref(), Some("red"));
+}
+
+#[test]
+fn later_rules_win_between_equally_specific_ones() {
+    let path = element_path(TOOLBAR, "#save");
+
+    assert_eq!(cascaded_value(&path, ".primary { color: red } .large { color: blue }", "color").as_deref(), Some("blue"));
+    assert_eq!(cascaded_value(&path, ".primary { color: red; color: blue }", "color").as_deref(), Some("blue"));
+}
+
+#[test]
+fn rules_use_their_most_specific_matching_selector() {
+    let path = element_path(TOOLBAR, "#save");
+
+    assert_eq!(cascaded_value(&path, "div, #save { color: red } .primary.large { color: blue }", "color").as_deref(), Some("red"));
+}
+
+#[test]
+fn important_declarations_beat_more_specific_rules() {
+    let path = element_path(TOOLBAR, "#save");
+
+    assert_eq!(cascaded_value(&path, "button { color: red !important } #save { color: blue }", "color").as_deref(), Some("red"));
+    assert_eq!(cascaded_value(&path, "button { color: red !important } #save { color: blue !important }", "color").as_deref(), Some("blue"));
+}
+
+#[test]
+fn inline_declarations_beat_rules_unless_these_are_important() {
+    let path = element_path(TOOLBAR, "#save");
+
+    let inline_declaration = cascaded_declaration(&path, "#save { color: red }", "color: blue", "color");
+    assert_eq!(inline_declaration, Some(("blue".to_string(), DeclarationOrigin::Inline)));
+
+    let important_declaration = cascaded_declaration(&path, "#save { color: red !important }", "color: blue", "color");
+    assert_eq!(important_declaration, Some(("red".to_string(), DeclarationOrigin::Stylesheet)));
+
+    let important_inline_declaration = cascaded_declaration(&path, "#save { color: red !important }", "color: blue !important", "color");
+    assert_eq!(important_inline_declaration, Some(("blue".to_string(), DeclarationOrigin::Inline)));
+}
+
+#[test]
+fn user_agent_defaults_apply_unless_overridden() {
+    let path = element_path(TOOLBAR, "#save");
+
+    assert_eq!(cascaded_declaration(&path, "", "", "display"), Some(("block".to_string(), DeclarationOrigin::UserAgent)));
+    assert_eq!(cascaded_declaration(&path, "* { display: flex }", "", "display"), Some(("flex".to_string(), DeclarationOrigin::Stylesheet)));
+}
+
+#[test]
+fn rules_not_matching_the_element_are_left_out() {
+    let path = element_path(TOOLBAR, "#save");
+
+    assert_eq!(cascaded_value(&path, "div { width: 10px } .toolbar > div > button { width: 20px }", "width"), None);
+}
+
+#[test]
+fn computed_styles_apply_the_style_attribute_and_css_wide_keywords() {
+    let path = element_path(r#"<div class="card"><button style="height: 20px">Save</button></div>"#, "button");
+    let (stylesheet, _) = parse_stylesheet(".card { width: 50px; font-size: 20px } .card button { width: inherit; font-size: initial }");
+    let stylesheet = Some(Arc::new(stylesheet));
+
+    let mut warnings = Vec::new();
+    let card_styles = css_parser::parse_styles(&path[..path.len() - 1], None, &stylesheet, &mut warnings);
+    let button_styles = css_parser::parse_styles(&path, Some(&card_styles), &stylesheet, &mut warnings);
+
+    assert!(warnings.is_empty());
+    let sizing_policy = button_styles.sizing_policy.unwrap();
+    assert_eq!(sizing_policy.width, card_styles.sizing_policy.unwrap().width);
+    assert_eq!(sizing_policy.height, dimension(20.0, Unit::Px));
+    assert_eq!(button_styles.font_size, None);
+}
+
+#[test]
+fn unknown_properties_are_reported_as_warnings_with_their_line() {
+    let path = element_path(TOOLBAR, "#save");
+    let (stylesheet, _) = parse_stylesheet(".primary {\n  width: 10px;\n  colour: red;\n}");
+
+    let mut warnings = Vec::new();
+    css_parser::parse_styles(&path, None, &Some(Arc::new(stylesheet)), &mut warnings);
+
+    assert_eq!(warnings, vec!["Unknown style property 'colour' (stylesheet rule at line 3)"]);
+}
+
+// calc()
+#[test]
+fn calc_with_mixed_units_keeps_the_sum_of_each_unit() {
+    let width = computed_width("button { width: calc(100% - 2rem) }");
+
+    assert_eq!(width, calc(CalcSum { percent: 100.0, rem: -2.0, ..Default::default() }));
+}
+
+#[test]
+fn calc_with_a_single_unit_gives_a_plain_dimension() {
+    assert_eq!(computed_width("button { width: calc(2px + 3px * 2) }"), dimension(8.0, Unit::Px));
+    assert_eq!(computed_width("button { width: calc((100% - 20%) / 2) }"), dimension(40.0, Unit::Percent));
+    assert_eq!(computed_width("button { width: calc(2 * calc(1vw + 1vw)) }"), dimension(4.0, Unit::Vw));
+}
+
+#[test]
+fn calc_groups_and_scales_every_term() {
+    let width = computed_width("button { width: calc((100% - 2rem) / 2 + 4px) }");
+
+    assert_eq!(width, calc(CalcSum { percent: 50.0, rem: -1.0, px: 4.0, ..Default::default() }));
+}
+
+#[test]
+fn invalid_calc_expressions_give_no_dimension() {
+    assert_eq!(computed_width("button { width: calc(100% * 2rem) }"), None);
+    assert_eq!(computed_width("button { width: calc(100% -2rem) }"), None);
+    assert_eq!(computed_width("button { width: calc(2 * 3) }"), None);
+    assert_eq!(computed_width("button { width: calc(100% - 2rem }"), None);
+}
+
+#[test]
+fn fixed_dimensions_resolve_rems_and_calc_to_pixels() {
+    let (styles, warnings) = computed_styles(TOOLBAR, "#save", "button { padding: calc(1rem + 4px) 2px; font-size: 1.5rem }");
+
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    let padding = styles.padding.unwrap();
+    assert_eq!((padding.top, padding.left), (Dimension { value: 20.0, unit: Unit::Px }, Dimension { value: 2.0, unit: Unit::Px }));
+    assert_eq!(styles.font_size, Some(Dimension { value: 24.0, unit: Unit::Px }));
+}
+
+#[test]
+fn fixed_dimensions_with_percentages_or_viewport_units_are_reported_and_reset() {
+    let (styles, warnings) = computed_styles(TOOLBAR, "#save", "button { padding: calc(5% + 4px); margin: 2vw; border-width: 10% }");
+
+    assert_eq!((styles.padding, styles.margin), (Some(Padding::default()), Some(Margin::default())));
+    assert_eq!(styles.border.unwrap_or_default().width, Dimension::default());
+    assert_eq!(warnings.len(), 3, "{:?}", warnings);
+    assert!(warnings.iter().all(|warning| warning.contains("Percentages and viewport units are not supported")), "{:?}", warnings);
+}
+
+// Custom properties
+#[test]
+fn var_substitutes_the_custom_properties_inherited_from_ancestors() {
+    let width = computed_width(":root { --gap: 2rem } .toolbar { --offset: 4px } button { width: calc(100% - var(--gap) - var(--offset)) }");
+
+    assert_eq!(width, calc(CalcSum { percent: 100.0, rem: -2.0, px: -4.0, ..Default::default() }));
+}
+
+#[test]
+fn custom_properties_are_overridden_by_descendants() {
+    let width = computed_width(":root { --gap: 2rem } .toolbar { --gap: 8px } button { width: var(--gap) }");
+
+    assert_eq!(width, dimension(8.0, Unit::Px));
+}
+
+#[test]
+fn custom_properties_referencing_others_are_substituted_on_the_declaring_element() {
+    let css = ":root { --base: 2px; --gap: var(--base) } .toolbar { --base: 3px } button { width: var(--gap) }";
+
+    assert_eq!(computed_width(css), dimension(2.0, Unit::Px));
+}
+
+#[test]
+fn var_fallbacks_are_used_for_undefined_custom_properties() {
+    assert_eq!(computed_width("button { width: var(--gap, 6px) }"), dimension(6.0, Unit::Px));
+    assert_eq!(computed_width("button { width: var(--gap, var(--other, 1rem)) }"), dimension(1.0, Unit::Rem));
+    assert_eq!(computed_width("button { width: calc(var(--gap, 50%) + 1px) }"), calc(CalcSum { percent: 50.0, px: 1.0, ..Default::default() }));
+}
+
+#[test]
+fn undefined_custom_properties_without_fallback_leave_the_property_unset() {
+    let (styles, warnings) = computed_styles(TOOLBAR, "#save", "button { width: 10px; width: var(--gap) }");
+
+    assert_eq!(styles.sizing_policy.and_then(|sizing_policy| sizing_policy.width), None);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("Undefined custom property '--gap'"), "{}", warnings[0]);
+}
+
+#[test]
+fn cyclic_custom_properties_are_reported_and_invalid() {
+    let (styles, warnings) = computed_styles(TOOLBAR, "#save", "button { --a: var(--b); --b: var(--a); width: var(--a, 3px) }");
+
+    assert_eq!(styles.sizing_policy.and_then(|sizing_policy| sizing_policy.width), dimension(3.0, Unit::Px));
+    assert!(warnings.iter().any(|warning| warning.contains("references itself")), "{:?}", warnings);
+}
+
+#[test]
+fn custom_property_names_are_case_sensitive() {
+    assert_eq!(computed_width("button { --Gap: 2px; width: var(--gap, 5px) }"), dimension(5.0, Unit::Px));
+}
+
+#[test]
+fn substitution_reports_malformed_var_functions() {
+    let mut lookup = |_: &str| None;
+
+    assert!(custom_properties::substitute_variables("var(--a", &mut lookup).is_err());
+    assert!(custom_properties::substitute_variables("var(gap)", &mut lookup).is_err());
+    assert_eq!(custom_properties::substitute_variables("navy solid", &mut lookup), Ok("navy solid".to_string()));
+}
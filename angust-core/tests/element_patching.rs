@@ -0,0 +1,68 @@
+use angust::{
+    parsing::{css::style_context::RestyleImpact, html::html_parser},
+    rendering::elements::{
+        common_types::Position,
+        element::Element,
+        element_patcher::{self, TemplateKey},
+        text::Text,
+    },
+};
+use kuchiki::NodeRef;
+
+
+fn template_node() -> NodeRef {
+    html_parser::parse_html_content("<div>{{ tag }}</div>").select_first("div").unwrap().as_node().clone()
+}
+
+// Text element rendered for the given @for item, laid out at the given x coordinate
+fn item_text(node: &NodeRef, item_key: &str, x: f32) -> Box<dyn Element> {
+    let mut text = Text::new(item_key.to_string());
+    text.set_template_key(TemplateKey::new(node, Some(format!("{}#0", item_key))));
+    text.set_position(Position { x, y: 0.0 });
+    Box::new(text)
+}
+
+fn positions(children: &[Box<dyn Element>]) -> Vec<f32> {
+    children.iter().map(|child| child.get_position().x).collect()
+}
+
+
+#[test]
+fn unchanged_children_need_no_layout() {
+    let node = template_node();
+    let mut children = vec![item_text(&node, "a", 1.0), item_text(&node, "b", 2.0)];
+    let new_children = vec![item_text(&node, "a", 0.0), item_text(&node, "b", 0.0)];
+
+    assert_eq!(element_patcher::patch_children(&mut children, new_children), RestyleImpact::None);
+    assert_eq!(positions(&children), vec![1.0, 2.0]);
+}
+
+#[test]
+fn keyed_children_keep_their_state_when_an_item_is_inserted_before_them() {
+    let node = template_node();
+    let mut children = vec![item_text(&node, "a", 1.0), item_text(&node, "b", 2.0)];
+    let new_children = vec![item_text(&node, "z", 0.0), item_text(&node, "a", 0.0), item_text(&node, "b", 0.0)];
+
+    assert_eq!(element_patcher::patch_children(&mut children, new_children), RestyleImpact::Layout);
+    assert_eq!(positions(&children), vec![0.0, 1.0, 2.0]);
+}
+
+#[test]
+fn keyed_children_keep_their_state_when_an_item_is_removed_before_them() {
+    let node = template_node();
+    let mut children = vec![item_text(&node, "a", 1.0), item_text(&node, "b", 2.0), item_text(&node, "c", 3.0)];
+    let new_children = vec![item_text(&node, "b", 0.0), item_text(&node, "c", 0.0)];
+
+    assert_eq!(element_patcher::patch_children(&mut children, new_children), RestyleImpact::Layout);
+    assert_eq!(positions(&children), vec![2.0, 3.0]);
+}
+
+#[test]
+fn unkeyed_children_are_paired_by_position() {
+    let mut children: Vec<Box<dyn Element>> = vec![Box::new(Text::new("a".to_string())), Box::new(Text::new("b".to_string()))];
+    children[1].set_position(Position { x: 2.0, y: 0.0 });
+    let new_children: Vec<Box<dyn Element>> = vec![Box::new(Text::new("a".to_string())), Box::new(Text::new("c".to_string()))];
+
+    assert_eq!(element_patcher::patch_children(&mut children, new_children), RestyleImpact::Layout);
+    assert_eq!(positions(&children), vec![0.0, 2.0]);
+}
@@ -0,0 +1,57 @@
+use angust::rendering::elements::{
+    component::functions::event_handler_call::EventHandlerCall,
+    element::EventType,
+    element_event_handlers::ElementEventHandlers,
+};
+
+
+fn handlers(event_types: Vec<EventType>) -> ElementEventHandlers {
+    ElementEventHandlers::new(event_types.into_iter()
+        .enumerate()
+        .map(|(index, event_type)| (event_type, EventHandlerCall::new(format!("handler_{}", index), vec![], None)))
+        .collect())
+}
+
+// Names of the handlers called for the event
+fn handler_names(event_handlers: &mut ElementEventHandlers, is_within_bounds: bool, event_type: EventType) -> Vec<String> {
+    event_handlers.collect_handler_calls(is_within_bounds, &event_type).into_iter()
+        .map(|handler_call| handler_call.handler_name)
+        .collect()
+}
+
+
+#[test]
+fn clicks_reach_the_elements_pressed_and_released_over() {
+    let mut event_handlers = handlers(vec![EventType::MouseClick]);
+
+    handler_names(&mut event_handlers, true, EventType::MouseDown);
+    assert_eq!(handler_names(&mut event_handlers, true, EventType::MouseClick), vec!["handler_0"]);
+}
+
+#[test]
+fn clicks_released_over_an_element_pressed_elsewhere_are_ignored() {
+    let mut event_handlers = handlers(vec![EventType::MouseClick, EventType::DoubleClick]);
+
+    handler_names(&mut event_handlers, false, EventType::MouseDown);
+    assert!(handler_names(&mut event_handlers, true, EventType::MouseClick).is_empty());
+    assert!(handler_names(&mut event_handlers, true, EventType::DoubleClick).is_empty());
+}
+
+#[test]
+fn clicks_released_outside_of_the_pressed_element_are_ignored() {
+    let mut event_handlers = handlers(vec![EventType::MouseClick]);
+
+    handler_names(&mut event_handlers, true, EventType::MouseDown);
+    assert!(handler_names(&mut event_handlers, false, EventType::MouseClick).is_empty());
+}
+
+#[test]
+fn focusable_elements_get_focused_when_clicked_and_blurred_when_clicked_outside() {
+    let mut event_handlers = handlers(vec![EventType::Focus, EventType::Blur]);
+
+    handler_names(&mut event_handlers, true, EventType::MouseDown);
+    assert_eq!(handler_names(&mut event_handlers, true, EventType::MouseClick), vec!["handler_0"]);
+
+    handler_names(&mut event_handlers, false, EventType::MouseDown);
+    assert_eq!(handler_names(&mut event_handlers, false, EventType::MouseClick), vec!["handler_1"]);
+}
@@ -0,0 +1,431 @@
+use std::{collections::HashMap, time::Duration};
+
+use angust::{
+    parsing::{
+        expression::{
+            ast::{self, ASTNode, Operator},
+            ast_evaluator,
+            dependency_collector,
+            evaluation_scope::EvaluationScope,
+            pipes::pipe_registry::{self, Pipe},
+            value::{FromValue, Value},
+        },
+        html::html_parser::{self, ParsingContext},
+    },
+    rendering::elements::{
+        component::{
+            component_state::ComponentState,
+            functions::component_functions::ComponentFunctions,
+            reactivity::ReactiveField,
+        },
+        element::Element,
+    },
+    wrap_fn,
+    wrap_getter,
+};
+
+
+#[derive(ComponentState)]
+pub struct TestState {
+    #[reactive]
+    count: ReactiveField<i32>,
+    #[reactive]
+    price: ReactiveField<f64>,
+    name: String,
+    is_enabled: bool,
+    tags: Vec<String>,
+    user: HashMap<String, Value>,
+}
+
+impl TestState {
+    fn new() -> Self {
+        let mut user = HashMap::new();
+        user.insert("name".to_string(), Value::from("Ann"));
+        user.insert("scores".to_string(), Value::from(vec![7, 9]));
+        user.insert("session".to_string(), Value::opaque(Duration::from_secs(60)));
+
+        Self {
+            count: ReactiveField::new(3),
+            price: ReactiveField::new(2.5),
+            name: String::from("angust"),
+            is_enabled: true,
+            tags: vec![String::from("ui"), String::from("rust")],
+            user,
+        }
+    }
+
+    fn double(&self, number: f64) -> f64 {
+        number * 2.0
+    }
+
+    fn greet(&self, name: String) -> String {
+        format!("Hello, {}", name)
+    }
+
+    fn total(&self, quantity: i32) -> f64 {
+        self.price.value * quantity as f64
+    }
+
+    fn short_tags(&self) -> Vec<String> {
+        self.tags.iter().filter(|tag| tag.len() <= 2).cloned().collect()
+    }
+
+    fn letters(&self) -> Vec<&'static str> {
+        vec!["a", "b"]
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        vec![Row { id: 7, label: String::from("first") }, Row { id: 9, label: String::from("second") }]
+    }
+}
+
+// User type iterated over by @for directives, converted to a map of its fields
+struct Row {
+    id: i64,
+    label: String,
+}
+
+impl From<Row> for Value {
+    fn from(row: Row) -> Self {
+        let mut fields = HashMap::new();
+        fields.insert("id".to_string(), Value::Integer(row.id));
+        fields.insert("label".to_string(), Value::from(row.label));
+        Value::Map(fields)
+    }
+}
+
+fn parse(expression: &str) -> ASTNode {
+    ast::parse_string_to_ast(expression.to_string()).expect("Failed to parse expression")
+}
+
+fn test_functions() -> ComponentFunctions<TestState> {
+    ComponentFunctions::new(
+        vec![],
+        vec![],
+        vec![
+            ("tags", wrap_getter!(TestState, |state: &TestState| state.tags.clone())),
+            ("short_tags", wrap_getter!(TestState, TestState::short_tags; reads tags)),
+            ("letters", wrap_getter!(TestState, TestState::letters)),
+            ("rows", wrap_getter!(TestState, TestState::rows; reads)),
+        ],
+        vec![
+            ("double", wrap_fn!(TestState, TestState::double, f64)),
+            ("greet", wrap_fn!(TestState, TestState::greet, String; reads)),
+            ("total", wrap_fn!(TestState, TestState::total, i32; reads price)),
+        ],
+        vec![],
+    )
+}
+
+fn evaluate_in_scope(expression: &str, scope: &EvaluationScope) -> Result<Value, String> {
+    let state = TestState::new();
+    ast_evaluator::evaluate_ast(&parse(expression), &state, &test_functions(), scope)
+}
+
+fn evaluate(expression: &str) -> Result<Value, String> {
+    evaluate_in_scope(expression, &EvaluationScope::new())
+}
+
+fn binary(operator: Operator, left: ASTNode, right: ASTNode) -> ASTNode {
+    ASTNode::BinaryOperation { operator, left: Box::new(left), right: Box::new(right) }
+}
+
+// Parsing
+#[test]
+fn multiplication_binds_tighter_than_addition() {
+    assert_eq!(
+        parse("1 + 2 * 3"),
+        binary(Operator::Add, ASTNode::Integer(1), binary(Operator::Multiply, ASTNode::Integer(2), ASTNode::Integer(3)))
+    );
+}
+
+#[test]
+fn operators_of_same_precedence_are_left_associative() {
+    assert_eq!(
+        parse("8 - 4 - 2"),
+        binary(Operator::Subtract, binary(Operator::Subtract, ASTNode::Integer(8), ASTNode::Integer(4)), ASTNode::Integer(2))
+    );
+}
+
+#[test]
+fn and_binds_tighter_than_or() {
+    let ast = parse("a || b && c");
+
+    match ast {
+        ASTNode::LogicalOperation { operator: Operator::Or, right, .. } => {
+            assert!(matches!(*right, ASTNode::LogicalOperation { operator: Operator::And, .. }));
+        },
+        _ => panic!("Expected an || operation, got {:?}", ast),
+    }
+}
+
+#[test]
+fn two_character_comparison_operators_are_parsed() {
+    assert!(matches!(parse("a <= b"), ASTNode::Comparison { operator: Operator::LessEqual, .. }));
+    assert!(matches!(parse("a >= b"), ASTNode::Comparison { operator: Operator::GreaterEqual, .. }));
+}
+
+#[test]
+fn unary_operators_bind_tighter_than_binary_operators() {
+    assert_eq!(
+        parse("-2 * 3"),
+        binary(
+            Operator::Multiply,
+            ASTNode::UnaryOperation { operator: Operator::Negate, operand: Box::new(ASTNode::Integer(2)) },
+            ASTNode::Integer(3)
+        )
+    );
+}
+
+// Arithmetic
+#[test]
+fn arithmetic_follows_precedence() {
+    assert_eq!(evaluate("1 + 2 * 3").unwrap(), Value::Integer(7));
+    assert_eq!(evaluate("(1 + 2) * 3").unwrap(), Value::Integer(9));
+    assert_eq!(evaluate("10 - 4 / 2").unwrap(), Value::Number(8.0));
+    assert_eq!(evaluate("2 * 3 - 4 * 5").unwrap(), Value::Integer(-14));
+    assert_eq!(evaluate("count * 2 + 1").unwrap(), Value::Integer(7));
+}
+
+#[test]
+fn integers_stay_integers_except_in_divisions() {
+    assert!(matches!(evaluate("count + 1").unwrap(), Value::Integer(4)));
+    assert!(matches!(evaluate("count / 2").unwrap(), Value::Number(number) if number == 1.5));
+    assert!(matches!(evaluate("count * price").unwrap(), Value::Number(number) if number == 7.5));
+}
+
+#[test]
+fn numeric_literals_are_parsed() {
+    assert_eq!(evaluate("1.25").unwrap(), Value::Number(1.25));
+    assert_eq!(evaluate("2e3").unwrap(), Value::Number(2000.0));
+    assert_eq!(evaluate("-count").unwrap(), Value::Integer(-3));
+    assert_eq!(evaluate("3 - -1").unwrap(), Value::Integer(4));
+}
+
+#[test]
+fn arithmetic_on_non_numbers_fails() {
+    assert!(evaluate("tags * 2").is_err());
+    assert!(evaluate("is_enabled - 1").is_err());
+    assert!(evaluate("-name").is_err());
+}
+
+// Strings
+#[test]
+fn adding_a_string_concatenates() {
+    assert_eq!(evaluate("'v' + count").unwrap(), Value::from("v3"));
+    assert_eq!(evaluate("name + ' ' + 1 + 2").unwrap(), Value::from("angust 12"));
+    assert_eq!(evaluate("name + ' ' + (1 + 2)").unwrap(), Value::from("angust 3"));
+}
+
+#[test]
+fn string_literals_support_both_quotes_and_escapes() {
+    assert_eq!(evaluate("\"double\"").unwrap(), Value::from("double"));
+    assert_eq!(evaluate("'it\\'s'").unwrap(), Value::from("it's"));
+}
+
+// Comparisons
+#[test]
+fn comparisons_follow_the_value_rules() {
+    assert_eq!(evaluate("name == 'angust'").unwrap(), Value::Bool(true));
+    assert_eq!(evaluate("count == 3.0").unwrap(), Value::Bool(true));
+    assert_eq!(evaluate("count != '3'").unwrap(), Value::Bool(true));
+    assert_eq!(evaluate("count <= 3").unwrap(), Value::Bool(true));
+    assert_eq!(evaluate("price >= 3").unwrap(), Value::Bool(false));
+    assert_eq!(evaluate("'abc' < 'abd'").unwrap(), Value::Bool(true));
+    assert!(evaluate("tags < 3").is_err());
+}
+
+#[test]
+fn comparisons_bind_tighter_than_logical_operators() {
+    assert_eq!(evaluate("count > 1 && count < 5").unwrap(), Value::Bool(true));
+    assert_eq!(evaluate("count + 1 == 2 * 2").unwrap(), Value::Bool(true));
+}
+
+// Logical operators
+#[test]
+fn logical_operators_use_truthiness() {
+    assert_eq!(evaluate("is_enabled && name").unwrap(), Value::Bool(true));
+    assert_eq!(evaluate("'' || 0").unwrap(), Value::Bool(false));
+    assert_eq!(evaluate("!tags").unwrap(), Value::Bool(false));
+    assert_eq!(evaluate("true || false && false").unwrap(), Value::Bool(true));
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+    assert_eq!(evaluate("false && missing_property").unwrap(), Value::Bool(false));
+    assert_eq!(evaluate("true || missing_property").unwrap(), Value::Bool(true));
+    assert!(evaluate("true && missing_property").is_err());
+    assert!(evaluate("false || missing_property").is_err());
+}
+
+// Conditionals
+#[test]
+fn ternary_evaluates_only_the_chosen_branch() {
+    assert_eq!(evaluate("count > 2 ? 'many' : missing_property").unwrap(), Value::from("many"));
+    assert_eq!(evaluate("count > 5 ? 'many' : count > 1 ? 'some' : 'few'").unwrap(), Value::from("some"));
+}
+
+// Member access and indexing
+#[test]
+fn members_and_items_are_read() {
+    assert_eq!(evaluate("user.name").unwrap(), Value::from("Ann"));
+    assert_eq!(evaluate("user['name']").unwrap(), Value::from("Ann"));
+    assert_eq!(evaluate("user.scores[1] + 1").unwrap(), Value::Integer(10));
+    assert_eq!(evaluate("tags[count - 2]").unwrap(), Value::from("rust"));
+    assert_eq!(evaluate("tags.length").unwrap(), Value::Integer(2));
+    assert!(evaluate("tags[2]").is_err());
+    assert!(evaluate("user.age").is_err());
+}
+
+// Functions, pipes and scope
+#[test]
+fn component_functions_receive_converted_arguments() {
+    assert_eq!(evaluate("double(count)").unwrap(), Value::Number(6.0));
+    assert_eq!(evaluate("greet(user.name)").unwrap(), Value::from("Hello, Ann"));
+    assert_eq!(evaluate("total(count)").unwrap(), Value::Number(7.5));
+}
+
+#[test]
+fn component_functions_fail_on_missing_or_unconvertible_arguments() {
+    assert!(evaluate("double()").unwrap_err().contains("Missing argument 1"));
+    assert!(evaluate("double(tags)").unwrap_err().contains("Argument 1"));
+    assert!(evaluate("missing_function(1)").is_err());
+}
+
+#[test]
+fn pipes_apply_to_the_whole_expression() {
+    assert_eq!(evaluate("name + '!' | uppercase").unwrap(), Value::from("ANGUST!"));
+    assert_eq!(evaluate("price * 2 | number:'1.2-2'").unwrap(), Value::from("5.00"));
+    assert_eq!(evaluate("is_enabled ? name : 'off' | titlecase").unwrap(), Value::from("Angust"));
+}
+
+#[test]
+fn pipes_registered_after_others_were_applied_are_available() {
+    assert_eq!(evaluate("name | uppercase").unwrap(), Value::from("ANGUST"));
+
+    let mut pipes: HashMap<String, Pipe> = HashMap::new();
+    pipes.insert("exclaim".to_string(), Box::new(|value: &Value, _: &[Value]| Ok(Value::from(format!("{}!", value)))));
+    pipe_registry::initialize_pipe_registry(pipes);
+
+    assert_eq!(evaluate("name | exclaim").unwrap(), Value::from("angust!"));
+}
+
+#[test]
+fn scope_variables_shadow_state_properties() {
+    let mut scope = EvaluationScope::new();
+    let mut frame = HashMap::new();
+    frame.insert("count".to_string(), Value::from(10));
+    scope.push_frame(frame);
+
+    assert_eq!(evaluate_in_scope("count + 1", &scope).unwrap(), Value::Integer(11));
+}
+
+// Values
+#[test]
+fn values_are_coerced_to_rust_types() {
+    assert_eq!(f64::from_value(Value::from(" 4.5 ")), Some(4.5));
+    assert_eq!(i32::from_value(Value::Number(2.0)), Some(2));
+    assert_eq!(i32::from_value(Value::Number(2.5)), None);
+    assert_eq!(usize::from_value(Value::Integer(-1)), None);
+    assert_eq!(String::from_value(Value::Integer(7)), Some(String::from("7")));
+    assert_eq!(bool::from_value(Value::from("false")), Some(false));
+    assert_eq!(Option::<i64>::from_value(Value::Null), Some(None));
+    assert_eq!(Vec::<String>::from_value(Value::from(vec!["a", "b"])), Some(vec![String::from("a"), String::from("b")]));
+}
+
+#[test]
+fn state_properties_are_set_from_values() {
+    let mut state = TestState::new();
+    state.set_property("count", Value::from("8"));
+    state.set_property("name", Value::Integer(5));
+    state.set_property("is_enabled", Value::from(vec![1])); // Not convertible, ignored
+
+    assert_eq!(state.count.value, 8);
+    assert_eq!(state.name, "5");
+    assert!(state.is_enabled);
+}
+
+// Dependencies
+#[test]
+fn dependencies_are_collected_from_all_operands() {
+    let asts = vec![parse("count > 1 ? user.name : tags[price]")];
+    let dependencies = dependency_collector::collect_dependencies(&asts, &test_functions()).unwrap();
+
+    let mut sorted_dependencies: Vec<&String> = dependencies.iter().collect();
+    sorted_dependencies.sort();
+    assert_eq!(sorted_dependencies, vec!["count", "price", "tags", "user"]);
+}
+
+#[test]
+fn functions_add_the_dependencies_they_declare() {
+    let functions = test_functions();
+    let dependencies = dependency_collector::collect_dependencies(&[parse("total(count) + greet(name)")], &functions).unwrap();
+
+    let mut sorted_dependencies: Vec<&String> = dependencies.iter().collect();
+    sorted_dependencies.sort();
+    assert_eq!(sorted_dependencies, vec!["count", "name", "price"]);
+    assert!(dependency_collector::collect_dependencies(&[parse("double(count)")], &functions).is_none());
+}
+
+/*
+ * Dependencies of a template rendered against the test state, None if they are unknown.
+ */
+fn template_dependencies(html: &str) -> Option<Vec<String>> {
+    let (state, functions) = (TestState::new(), test_functions());
+    let mut asts = Vec::new();
+
+    let mut context = ParsingContext::new(None, None, Some(&state), Some(&functions), Some(&mut asts));
+    html_parser::map_dom_to_elements(&html_parser::parse_html_content(html), None, &mut context);
+    if context.has_undeclared_dependencies {
+        return None;
+    }
+
+    let mut dependencies: Vec<String> = dependency_collector::collect_dependencies(&asts, &functions)?.into_iter().collect();
+    dependencies.sort();
+    Some(dependencies)
+}
+
+#[test]
+fn for_directives_depend_on_the_properties_their_getter_reads() {
+    assert_eq!(template_dependencies(r#"<div @for="tag of short_tags">Tag</div>"#), Some(vec!["tags".to_string()]));
+    assert_eq!(template_dependencies(r#"<div @for="tag of tags">{{ count }}</div>"#), Some(vec!["count".to_string(), "tags".to_string()]));
+}
+
+#[test]
+fn for_directives_over_undeclared_getters_not_named_after_a_property_have_unknown_dependencies() {
+    assert_eq!(template_dependencies(r#"<div @for="letter of letters">Letter</div>"#), None);
+}
+
+/*
+ * Number of elements rendered from a template against the test state.
+ */
+fn rendered_element_count(html: &str) -> usize {
+    fn count(element: &mut Box<dyn Element>) -> usize {
+        1 + element.get_children_mut().map_or(0, |children| children.iter_mut().map(count).sum())
+    }
+
+    let (state, functions) = (TestState::new(), test_functions());
+    let mut context = ParsingContext::new(None, None, Some(&state), Some(&functions), None);
+    html_parser::map_dom_to_elements(&html_parser::parse_html_content(html), None, &mut context)
+        .as_mut()
+        .map_or(0, count)
+}
+
+#[test]
+fn for_directives_iterate_over_user_types_converted_to_values() {
+    assert_eq!(
+        rendered_element_count(r#"<div><p @for="row of rows; track row.id">{{ row.label }}</p></div>"#),
+        rendered_element_count("<div><p>first</p><p>second</p></div>"),
+    );
+}
+
+#[test]
+fn inputs_of_custom_components_are_not_resolved_as_attributes() {
+    let (state, functions) = (TestState::new(), test_functions());
+    let mut context = ParsingContext::new(None, None, Some(&state), Some(&functions), None);
+    let html = r#"<div><session-panel [session]="user.session"></session-panel></div>"#;
+    html_parser::map_dom_to_elements(&html_parser::parse_html_content(html), None, &mut context);
+
+    let messages: Vec<&str> = context.diagnostics.diagnostics().iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+    assert_eq!(messages, vec!["Component not found: session-panel"]);
+}
@@ -0,0 +1,151 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Field, Fields, GenericArgument, Ident, PathArguments, Result, Type};
+
+
+struct StateProperty<'a> {
+    name: &'a Ident,
+    kind: PropertyKind<'a>,
+    is_input: bool,
+}
+
+enum PropertyKind<'a> {
+    Reactive(&'a Type), // Type of the value of the ReactiveField
+    Plain(&'a Type),
+}
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(&input.ident, "ComponentState can only be derived for structs with named fields")),
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "ComponentState can only be derived for structs")),
+    };
+
+    let mut properties = Vec::new();
+    for field in fields.iter() {
+        if let Some(property) = parse_property(field)? {
+            properties.push(property);
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let property_names: Vec<String> = properties.iter().map(|property| property.name.to_string()).collect();
+    let has_inputs = properties.iter().any(|property| property.is_input);
+    let input_names: Vec<&String> = properties.iter().zip(property_names.iter())
+        .filter(|(property, _)| property.is_input || !has_inputs)
+        .map(|(_, property_name)| property_name)
+        .collect();
+
+    let getters = properties.iter().zip(property_names.iter()).map(|(property, property_name)| {
+        let field = property.name;
+        match property.kind {
+            PropertyKind::Reactive(_) => quote! { #property_name => Some(::std::convert::Into::<::angust::parsing::expression::value::Value>::into(self.#field.value.clone())), },
+            PropertyKind::Plain(_) => quote! { #property_name => Some(::std::convert::Into::<::angust::parsing::expression::value::Value>::into(self.#field.clone())), },
+        }
+    });
+    let setters = properties.iter().zip(property_names.iter()).map(|(property, property_name)| {
+        let field = property.name;
+        match property.kind {
+            PropertyKind::Reactive(value_type) => quote! {
+                #property_name => {
+                    if let Some(converted_value) = <#value_type as ::angust::parsing::expression::value::FromValue>::from_value(value) {
+                        self.#field.set(converted_value);
+                    }
+                },
+            },
+            PropertyKind::Plain(value_type) => quote! {
+                #property_name => {
+                    if let Some(converted_value) = <#value_type as ::angust::parsing::expression::value::FromValue>::from_value(value) {
+                        self.#field = converted_value;
+                    }
+                },
+            },
+        }
+    });
+    let subscriptions = properties.iter().zip(property_names.iter())
+        .filter(|(property, _)| matches!(property.kind, PropertyKind::Reactive(_)))
+        .map(|(property, property_name)| {
+            let field = property.name;
+            quote! { #property_name => self.#field.subscribe(callback), }
+        });
+
+    Ok(quote! {
+        impl #impl_generics ::angust::rendering::elements::component::component_state::ComponentState for #name #ty_generics #where_clause {
+            fn get_property(&self, property_name: &str) -> Option<::angust::parsing::expression::value::Value> {
+                match property_name {
+                    #(#getters)*
+                    _ => None,
+                }
+            }
+
+            #[allow(unused_variables)]
+            fn set_property(&mut self, property_name: &str, value: ::angust::parsing::expression::value::Value) {
+                match property_name {
+                    #(#setters)*
+                    _ => {},
+                }
+            }
+
+            fn get_all_properties(&self) -> Vec<&str> {
+                vec![#(#property_names),*]
+            }
+
+            fn get_input_properties(&self) -> Vec<&str> {
+                vec![#(#input_names),*]
+            }
+
+            #[allow(unused_variables)]
+            fn subscribe_to_property<F>(&mut self, property_name: &str, callback: F)
+            where
+                F: 'static + FnMut(&::angust::rendering::elements::component::reactivity::ComponentEvent),
+            {
+                match property_name {
+                    #(#subscriptions)*
+                    _ => {},
+                }
+            }
+        }
+    })
+}
+
+fn parse_property(field: &Field) -> Result<Option<StateProperty<'_>>> {
+    let has_attribute = |attribute_name: &str| field.attrs.iter().any(|attribute| attribute.path().is_ident(attribute_name));
+    let name = field.ident.as_ref().ok_or_else(|| Error::new_spanned(field, "Expected a named field"))?;
+
+    if has_attribute("skip") {
+        if has_attribute("reactive") || has_attribute("input") {
+            return Err(Error::new_spanned(field, "#[skip] fields cannot be #[reactive] or #[input]"));
+        }
+        return Ok(None);
+    }
+
+    let kind = if has_attribute("reactive") {
+        let value_type = get_reactive_value_type(&field.ty)
+            .ok_or_else(|| Error::new_spanned(&field.ty, "#[reactive] fields must be of type ReactiveField<T>"))?;
+        PropertyKind::Reactive(value_type)
+    } else {
+        PropertyKind::Plain(&field.ty)
+    };
+
+    Ok(Some(StateProperty { name, kind, is_input: has_attribute("input") }))
+}
+
+fn get_reactive_value_type(field_type: &Type) -> Option<&Type> {
+    let path = match field_type {
+        Type::Path(type_path) => &type_path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last().filter(|segment| segment.ident == "ReactiveField")?;
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => arguments.args.iter().find_map(|argument| match argument {
+            GenericArgument::Type(value_type) => Some(value_type),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
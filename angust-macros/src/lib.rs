@@ -0,0 +1,22 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod component_state_derive;
+
+
+/*
+ * Derives ComponentState for a struct with named fields. Field attributes:
+ * - #[reactive]: the field is a ReactiveField<T>, whose changes re-render the component
+ * - #[input]: the property can be set by the parent component, e.g. [title]="..." (all properties if no field is marked)
+ * - #[skip]: the field is not a property, so it can be private state of any type
+ * The other fields are plain properties, read and written without notifying the component.
+ * The types of the properties must convert to and from Value, through Value::from and FromValue.
+ */
+#[proc_macro_derive(ComponentState, attributes(reactive, input, skip))]
+pub fn derive_component_state(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    component_state_derive::expand(input)
+        .unwrap_or_else(|er| er.to_compile_error())
+        .into()
+}
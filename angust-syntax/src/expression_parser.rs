@@ -3,9 +3,9 @@ use pest_derive::Parser;
 
 
 #[derive(Parser)]
-#[grammar = "src/parsing/expression/expression.pest"]
+#[grammar = "src/expression.pest"]
 pub struct ExpressionParser;
 
-pub fn parse_expression(input: &str) -> Result<pest::iterators::Pairs<Rule>, pest::error::Error<Rule>> {
-    ExpressionParser::parse(Rule::expression, input)
-}
\ No newline at end of file
+pub fn parse_expression(input: &str) -> Result<pest::iterators::Pairs<'_, Rule>, pest::error::Error<Rule>> {
+    ExpressionParser::parse(Rule::complete_expression, input)
+}
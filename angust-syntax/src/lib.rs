@@ -0,0 +1,6 @@
+/*
+ * Syntax of the templates shared by the runtime (angust) and the build-time validation (angust-build):
+ * the grammar of the template expressions and the patterns of the directives and placeholders.
+ */
+pub mod expression_parser;
+pub mod template_patterns;
@@ -0,0 +1,12 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+
+// @for="item of items; let i = index; track item.id": the loop clause, then alias and track clauses separated by ';'
+pub static FOR_IMPLICIT_VARIABLES: [&str; 3] = ["index", "first", "last"];
+pub static FOR_LOOP_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:let\s+)?([A-Za-z_][A-Za-z0-9_]*)\s+of\s+([A-Za-z_][A-Za-z0-9_]*)$").unwrap());
+pub static FOR_ALIAS_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^let\s+([A-Za-z_][A-Za-z0-9_]*)\s*=\s*([A-Za-z_][A-Za-z0-9_]*)$").unwrap());
+pub static FOR_TRACK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^track\s+(.+)$").unwrap());
+
+// {{ expression }} placeholders of the text nodes
+pub static PLACEHOLDER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{(\s*[^}]+\s*)\}\}").unwrap());
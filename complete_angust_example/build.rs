@@ -0,0 +1,3 @@
+fn main() {
+    angust_build::embed_resources(&["angust.config.json", "src/index.html", "src/app", "src/styles"]);
+}
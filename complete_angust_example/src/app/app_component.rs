@@ -4,11 +4,14 @@ use std::collections::HashMap;
 
 use angust::{
     define_component_state, 
-    rendering::elements::component::{
-        component::Component, 
-        component_factory_registry::ComponentFactory, 
-        functions::component_functions::ComponentFunctions
-    }, wrap_fn
+    rendering::elements::{
+        component::{
+            component::Component, 
+            component_factory_registry::ComponentFactory, 
+            functions::component_functions::ComponentFunctions
+        }, 
+        event_payload::EventPayload
+    }, wrap_fn, wrap_getter, wrap_handler
 };
 
 
@@ -16,7 +19,8 @@ define_component_state! {
     AppComponentState {
         content: String,
         count: f64,
-        active_tab: String
+        active_tab: String,
+        tabs: Vec<String>
     }
 }
 
@@ -30,6 +34,14 @@ impl AppComponentState {
         }
     }
 
+    pub fn select_tab(&mut self, tab_name: String, _: &EventPayload) {
+        self.active_tab.set(tab_name);
+    }
+
+    pub fn on_saved(&mut self, title: String, _: &EventPayload) {
+        self.content.set(format!("Saved: {}", title));
+    }
+
     pub fn increment_count(&mut self) {
         let current_value = self.count.value + 1.0;
         self.count.set(current_value);
@@ -61,7 +73,8 @@ impl AppComponent {
         let state_factory = || AppComponentState::new(
             String::from("Hello, App Component!"),
             0.0,
-            String::from("app-component")
+            String::from("app-component"),
+            vec![String::from("Home"), String::from("Settings"), String::from("About")]
         );
 
         registry.insert("app-component".to_string(), Box::new(move || {
@@ -77,11 +90,17 @@ impl AppComponent {
                     ("increment_count", Box::new(|state: &mut AppComponentState| state.increment_count()))
                 ],
                 vec![],
-                vec![],
                 vec![
-                    ("is_active_tab", wrap_fn!(AppComponentState, AppComponentState::is_active_tab, String, bool, bool)),
-                    ("get_something", wrap_fn!(AppComponentState, AppComponentState::get_something, String)),
-                    ("get_number_plus_one", wrap_fn!(AppComponentState, AppComponentState::get_number_plus_one, f64))
+                    ("tabs", wrap_getter!(AppComponentState, |state: &AppComponentState| state.tabs.value.clone(); reads tabs))
+                ],
+                vec![
+                    ("is_active_tab", wrap_fn!(AppComponentState, AppComponentState::is_active_tab, String, bool, bool; reads active_tab)),
+                    ("get_something", wrap_fn!(AppComponentState, AppComponentState::get_something, String; reads)),
+                    ("get_number_plus_one", wrap_fn!(AppComponentState, AppComponentState::get_number_plus_one, f64; reads))
+                ],
+                vec![
+                    ("select_tab", wrap_handler!(AppComponentState, AppComponentState::select_tab, String)),
+                    ("on_saved", wrap_handler!(AppComponentState, AppComponentState::on_saved, String))
                 ]
             );
             component.add_component_functions(component_functions);
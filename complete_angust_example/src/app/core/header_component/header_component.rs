@@ -1,43 +1,73 @@
+use std::collections::HashMap;
 
-// use std::collections::HashMap;
-
-// use angust::{component_state, rendering::elements::component::{component::Component, component_factory_registry::ComponentFactory}};
-
-
-// pub struct HeaderComponent {
-//     component: Component<HeaderComponentState>,    
-// }
-
-
-// component_state! {
-//     HeaderComponentState {
-//         content: String,
-//     }
-// }
-
-// impl HeaderComponentState {
-//     // pub fn new() -> HeaderComponentState {
-//     //     HeaderComponentState {
-//     //         content: "Hello from header-component".to_string(),
-//     //     }
-//     // }
-// }
-
-// impl HeaderComponent {
-//     pub fn register(registry: &mut HashMap<String, ComponentFactory>) {
-//         let state_factory = || HeaderComponentState::new(
-//             String::from("Hello from header-component")
-//         );
-
-//         registry.insert("header-component".to_string(), Box::new(move || {
-//             Box::new(
-//                 Component::new(
-//                     "header-component".to_string(),
-//                     "src/app/core/header_component/header_component.html".to_string(),
-//                     state_factory() 
-//                 )
-//             )
-//         }));
-//     }
-// }
-    
\ No newline at end of file
+use angust::rendering::elements::component::{
+    component::Component, 
+    component_factory_registry::ComponentFactory,
+    component_outputs,
+    component_state::ComponentState,
+    reactivity::ReactiveField,
+};
+
+
+pub struct HeaderComponent {
+    component: Component<HeaderComponentState>,    
+}
+
+#[derive(ComponentState)]
+pub struct HeaderComponentState {
+    /// Title shown in the header, set by the parent component
+    #[reactive]
+    #[input]
+    title: ReactiveField<String>,
+    #[reactive]
+    #[input]
+    count: ReactiveField<f64>,
+    #[skip]
+    save_count: usize,
+}
+
+impl HeaderComponentState {
+    pub fn new(title: String, count: f64) -> Self {
+        Self {
+            title: ReactiveField::new(title),
+            count: ReactiveField::new(count),
+            save_count: 0,
+        }
+    }
+
+    pub fn save(&mut self) {
+        self.save_count += 1;
+        component_outputs::emit("saved", self.title.value.clone());
+    }
+}
+
+impl HeaderComponent {
+    pub fn register(registry: &mut HashMap<String, ComponentFactory>) {
+        let state_factory = || HeaderComponentState::new(
+            String::from("Hello from header-component"),
+            0.0
+        );
+
+        registry.insert("header-component".to_string(), Box::new(move || {
+            let mut component = Component::new(
+                "header-component".to_string(),
+                "src/app/core/header_component/header_component.html".to_string(),
+                state_factory() 
+            );
+
+            component.add_event_handlers(vec![
+                ("save", Box::new(|state: &mut HeaderComponentState| state.save()))
+            ]);
+            component.add_outputs(vec!["saved"]);
+            component.set_on_changes(|state: &mut HeaderComponentState, changed_inputs: &[String]| {
+                if changed_inputs.contains(&"title".to_string()) {
+                    state.title.value = state.title.value.to_uppercase();
+                }
+            });
+
+            component.initialize();
+
+            Box::new(component)
+        }));
+    }
+}
@@ -12,6 +12,9 @@ pub struct AppGlobalState {
 }
 
 fn main() {
+    #[cfg(feature = "embed-resources")]
+    angust::include_resources!();
+
     let initial_state = AppGlobalState {
         message: "Hello, Angust user!".to_string(),
     };